@@ -0,0 +1,13 @@
+// Generates the gRPC server and messages from `proto/`. A `protoc` from `PROTOC` is used
+// when set; otherwise the one vendored with `protoc-bin-vendored`, so building needs no
+// system install.
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    if std::env::var_os("PROTOC").is_none() {
+        std::env::set_var("PROTOC", protoc_bin_vendored::protoc_bin_path()?);
+    }
+    let well_known = protoc_bin_vendored::include_path()?;
+
+    tonic_prost_build::configure()
+        .compile_protos(&["proto/blockchat/v1/blockchat.proto"], &["proto", well_known.to_str().unwrap()])?;
+    Ok(())
+}
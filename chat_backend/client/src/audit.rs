@@ -0,0 +1,9 @@
+use crate::models::audit_models::{AuditEventFilter, GetAuditEventsResponse};
+use crate::{Client, Result};
+
+impl Client {
+    /// Admin only. A page of the audit trail, newest first.
+    pub async fn audit_events(&self, filter: &AuditEventFilter) -> Result<GetAuditEventsResponse> {
+        self.authed_json(self.http.get(self.url("/audit-events")).query(filter)).await
+    }
+}
@@ -0,0 +1,48 @@
+use crate::models::user_models::{
+    ForgotPassword, LoginResponse, RegisterUser, ResetPassword, ResponseMessage, VerifyEmail,
+};
+use crate::{Client, Result};
+
+impl Client {
+    /// Creates an account. Depending on the server's registration mode it may need an
+    /// invite code and stay unverified until the emailed link is followed.
+    pub async fn register(&self, form: &RegisterUser) -> Result<ResponseMessage> {
+        self.public(self.http.post(self.url("/users/register")).json(form)).await
+    }
+
+    /// Signs in and keeps both the token and the credentials, so later calls can sign in
+    /// again when the session expires or is revoked.
+    pub async fn login(&self, username: &str, password: &str) -> Result<LoginResponse> {
+        let response = self.sign_in(username, password).await?;
+        let mut auth = self.auth.lock().await;
+        auth.token = Some(response.token.clone());
+        auth.credentials = Some((username.to_string(), password.to_string()));
+        Ok(response)
+    }
+
+    /// Forgets the token and credentials. The session stays valid on the server until it
+    /// expires; revoke it first with [`Client::revoke_session`] to end it there too.
+    pub async fn logout(&self) {
+        let mut auth = self.auth.lock().await;
+        auth.token = None;
+        auth.credentials = None;
+    }
+
+    pub async fn forgot_password(&self, email: &str) -> Result<ResponseMessage> {
+        let form = ForgotPassword { email: email.to_string() };
+        self.public(self.http.post(self.url("/users/password/forgot")).json(&form)).await
+    }
+
+    pub async fn reset_password(&self, form: &ResetPassword) -> Result<ResponseMessage> {
+        self.public(self.http.post(self.url("/users/password/reset")).json(form)).await
+    }
+
+    pub async fn verify_email(&self, token: &str) -> Result<ResponseMessage> {
+        let form = VerifyEmail { token: token.to_string() };
+        self.public(self.http.post(self.url("/users/verify-email")).json(&form)).await
+    }
+
+    pub async fn resend_verification(&self) -> Result<ResponseMessage> {
+        self.authed_json(self.http.post(self.url("/users/verify-email/resend"))).await
+    }
+}
@@ -0,0 +1,458 @@
+use std::io;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::Duration;
+use blockchat_client::models::chat_models::{Chat, Message};
+use blockchat_client::{Client, Error, MessageStream};
+use chrono::Local;
+use clap::Parser;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use tokio::task::JoinHandle;
+
+// Messages loaded when a chat is opened; the server serves at most 100.
+const HISTORY: u64 = 100;
+// Messages moved by PageUp and PageDown.
+const PAGE: usize = 10;
+
+/// Terminal client for BlockChat. The token from the last login is kept in the OS config
+/// directory, so the password is only asked for once per session.
+#[derive(Parser)]
+#[command(name = "blockchat-tui", version)]
+struct Cli {
+    /// Base URL of the BlockChat server.
+    #[arg(long, env = "BLOCKCHAT_URL", default_value = "http://localhost:8080")]
+    server: String,
+}
+
+// What is written to `<config dir>/blockchat/session.json`; never the password.
+#[derive(Serialize, Deserialize)]
+struct SavedSession {
+    server: String,
+    username: String,
+    token: String,
+}
+
+fn session_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("blockchat").join("session.json"))
+}
+
+fn load_session(server: &str) -> Option<SavedSession> {
+    let data = std::fs::read(session_path()?).ok()?;
+    let saved: SavedSession = serde_json::from_slice(&data).ok()?;
+    (saved.server == server).then_some(saved)
+}
+
+fn save_session(saved: &SavedSession) -> io::Result<()> {
+    let path = session_path().ok_or_else(|| io::Error::other("No config directory"))?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(&path, serde_json::to_vec(saved)?)?;
+    // The token is as good as the password until it expires.
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))?;
+    }
+    Ok(())
+}
+
+fn forget_session() {
+    if let Some(path) = session_path() {
+        std::fs::remove_file(path).ok();
+    }
+}
+
+#[derive(Default)]
+struct LoginForm {
+    username: String,
+    password: String,
+    on_password: bool,
+    error: Option<String>,
+}
+
+struct ChatView {
+    username: String,
+    chats: Vec<Chat>,
+    selected: usize,
+    // History of the selected chat, oldest first, followed by what arrived live.
+    messages: Vec<Message>,
+    // Messages hidden below the bottom of the pane; at 0 the pane follows new messages.
+    scroll: usize,
+    input: String,
+    status: Option<String>,
+    // Streams the selected chat into the update channel.
+    live: Option<JoinHandle<()>>,
+}
+
+// Sent by the task streaming the selected chat.
+enum Update {
+    Message(Message),
+    Ended { chat_id: i32, reason: String },
+}
+
+enum Screen {
+    Login(LoginForm),
+    Chats(ChatView),
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let saved = load_session(&cli.server);
+    let client = match &saved {
+        Some(saved) => Client::new(&cli.server).with_token(&saved.token),
+        None => Client::new(&cli.server),
+    };
+
+    let mut terminal = ratatui::init();
+    let result = run(&mut terminal, &cli.server, &client, saved).await;
+    ratatui::restore();
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run(
+    terminal: &mut DefaultTerminal,
+    server: &str,
+    client: &Client,
+    saved: Option<SavedSession>,
+) -> io::Result<()> {
+    let (updates, mut incoming) = mpsc::unbounded_channel();
+    let mut screen = match saved {
+        Some(saved) => match ChatView::load(client).await {
+            Ok(mut view) => {
+                view.open(client, &updates).await;
+                Screen::Chats(view)
+            }
+            // Expired or revoked; ask for the password again.
+            Err(_) => Screen::Login(LoginForm {
+                username: saved.username,
+                on_password: true,
+                ..LoginForm::default()
+            }),
+        },
+        None => Screen::Login(LoginForm::default()),
+    };
+
+    loop {
+        if let Screen::Chats(view) = &mut screen {
+            view.apply_updates(&mut incoming);
+        }
+        terminal.draw(|frame| draw(frame, server, &screen))?;
+        if !event::poll(Duration::from_millis(100))? {
+            continue;
+        }
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        if key.code == KeyCode::Esc || (ctrl && key.code == KeyCode::Char('c')) {
+            return Ok(());
+        }
+
+        match &mut screen {
+            Screen::Login(form) => match key.code {
+                KeyCode::Tab | KeyCode::Up | KeyCode::Down => form.on_password = !form.on_password,
+                KeyCode::Backspace => {
+                    form.field().pop();
+                }
+                KeyCode::Char(c) => form.field().push(c),
+                KeyCode::Enter if !form.on_password => form.on_password = true,
+                KeyCode::Enter => {
+                    if let Some(view) = log_in(server, client, form, &updates).await {
+                        screen = Screen::Chats(view);
+                    }
+                }
+                _ => {}
+            },
+            Screen::Chats(view) => match key.code {
+                KeyCode::Char('l') if ctrl => {
+                    view.stop_live();
+                    client.logout().await;
+                    forget_session();
+                    screen = Screen::Login(LoginForm::default());
+                }
+                KeyCode::Char('r') if ctrl => match ChatView::load(client).await {
+                    Ok(mut reloaded) => {
+                        view.stop_live();
+                        // Stay on the same chat if it is still there.
+                        let selected = view.chat_id();
+                        reloaded.selected = reloaded.chats.iter().position(|chat| Some(chat.id) == selected).unwrap_or(0);
+                        reloaded.open(client, &updates).await;
+                        *view = reloaded;
+                    }
+                    Err(err) => view.status = Some(err.to_string()),
+                },
+                KeyCode::Up if view.selected > 0 => {
+                    view.selected -= 1;
+                    view.open(client, &updates).await;
+                }
+                KeyCode::Down if view.selected + 1 < view.chats.len() => {
+                    view.selected += 1;
+                    view.open(client, &updates).await;
+                }
+                KeyCode::PageUp => view.scroll = (view.scroll + PAGE).min(view.messages.len().saturating_sub(1)),
+                KeyCode::PageDown => view.scroll = view.scroll.saturating_sub(PAGE),
+                KeyCode::End => view.scroll = 0,
+                KeyCode::Backspace => {
+                    view.input.pop();
+                }
+                KeyCode::Char(c) => view.input.push(c),
+                KeyCode::Enter => view.send(client).await,
+                _ => {}
+            },
+        }
+    }
+}
+
+// Signs in with the form's credentials and remembers the token; on failure the form
+// shows why.
+async fn log_in(
+    server: &str,
+    client: &Client,
+    form: &mut LoginForm,
+    updates: &UnboundedSender<Update>,
+) -> Option<ChatView> {
+    let login = match client.login(&form.username, &form.password).await {
+        Ok(login) => login,
+        Err(err) => {
+            form.error = Some(match &err {
+                Error::Api { message, .. } => message.clone(),
+                other => other.to_string(),
+            });
+            return None;
+        }
+    };
+    form.password.clear();
+
+    let saved = SavedSession {
+        server: server.to_string(),
+        username: form.username.clone(),
+        token: login.token,
+    };
+    if let Err(err) = save_session(&saved) {
+        form.error = Some(format!("Could not save the session: {}", err));
+    }
+    match ChatView::load(client).await {
+        Ok(mut view) => {
+            view.open(client, updates).await;
+            Some(view)
+        }
+        Err(err) => {
+            form.error = Some(err.to_string());
+            None
+        }
+    }
+}
+
+impl LoginForm {
+    fn field(&mut self) -> &mut String {
+        if self.on_password {
+            &mut self.password
+        } else {
+            &mut self.username
+        }
+    }
+}
+
+impl ChatView {
+    // The signed-in user's chats, with the first one selected; `open` shows it.
+    async fn load(client: &Client) -> blockchat_client::Result<Self> {
+        let user = client.me().await?;
+        let chats = client.chats().await?;
+        Ok(Self {
+            username: user.username,
+            chats,
+            selected: 0,
+            messages: Vec::new(),
+            scroll: 0,
+            input: String::new(),
+            status: None,
+            live: None,
+        })
+    }
+
+    fn chat_id(&self) -> Option<i32> {
+        self.chats.get(self.selected).map(|chat| chat.id)
+    }
+
+    // Shows the selected chat: its history, then whatever is posted to it from now on.
+    async fn open(&mut self, client: &Client, updates: &UnboundedSender<Update>) {
+        self.stop_live();
+        self.messages.clear();
+        self.scroll = 0;
+        self.status = None;
+        let Some(chat_id) = self.chat_id() else {
+            return;
+        };
+        // Subscribed before the history is loaded, so nothing posted in between is lost;
+        // messages in both are only shown once.
+        match client.subscribe_messages(chat_id).await {
+            Ok(stream) => self.live = Some(tokio::spawn(stream_chat(stream, chat_id, updates.clone()))),
+            Err(err) => self.status = Some(format!("No live updates: {}", err)),
+        }
+        match client.messages(chat_id, HISTORY).await {
+            Ok(Some(messages)) => messages.into_iter().for_each(|message| self.add(message)),
+            Ok(None) => self.status = Some("This chat is no longer available; Ctrl-R reloads".to_string()),
+            Err(err) => self.status = Some(err.to_string()),
+        }
+    }
+
+    fn stop_live(&mut self) {
+        if let Some(task) = self.live.take() {
+            task.abort();
+        }
+    }
+
+    fn add(&mut self, message: Message) {
+        if Some(message.chat_id) != self.chat_id() || self.messages.iter().any(|known| known.id == message.id) {
+            return;
+        }
+        let at = self.messages.partition_point(|known| known.id < message.id);
+        self.messages.insert(at, message);
+        // Keep the same messages in view while scrolled back.
+        if self.scroll > 0 && at == self.messages.len() - 1 {
+            self.scroll += 1;
+        }
+    }
+
+    fn apply_updates(&mut self, incoming: &mut UnboundedReceiver<Update>) {
+        while let Ok(update) = incoming.try_recv() {
+            match update {
+                Update::Message(message) => self.add(message),
+                Update::Ended { chat_id, reason } if Some(chat_id) == self.chat_id() => {
+                    self.live = None;
+                    self.status = Some(format!("Live updates stopped: {}; Ctrl-R reconnects", reason));
+                }
+                // From a chat that is no longer open.
+                Update::Ended { .. } => {}
+            }
+        }
+    }
+
+    async fn send(&mut self, client: &Client) {
+        let Some(chat_id) = self.chat_id() else {
+            return;
+        };
+        if self.input.trim().is_empty() {
+            return;
+        }
+        match client.send_message(chat_id, &self.input).await {
+            Ok(message) => {
+                self.input.clear();
+                self.scroll = 0;
+                self.add(message);
+            }
+            Err(err) => self.status = Some(err.to_string()),
+        }
+    }
+}
+
+// Forwards the messages of one chat until its stream ends, which it reports.
+async fn stream_chat(mut stream: MessageStream, chat_id: i32, updates: UnboundedSender<Update>) {
+    loop {
+        let update = match stream.recv().await {
+            Some(Ok(message)) => Update::Message(message),
+            Some(Err(err)) => Update::Ended { chat_id, reason: err.to_string() },
+            None => Update::Ended { chat_id, reason: "The server ended the subscription".to_string() },
+        };
+        let ended = matches!(update, Update::Ended { .. });
+        if updates.send(update).is_err() || ended {
+            return;
+        }
+    }
+}
+
+fn draw(frame: &mut Frame, server: &str, screen: &Screen) {
+    let [body, footer] = Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+    match screen {
+        Screen::Login(form) => {
+            draw_login(frame, body, server, form);
+            frame.render_widget(Line::from("Enter: next/sign in  Tab: switch field  Esc: quit"), footer);
+        }
+        Screen::Chats(view) => {
+            draw_chats(frame, body, view);
+            let hint = view.status.clone().unwrap_or_else(|| {
+                "Up/Down: chat  PgUp/PgDn/End: scroll  Enter: send  Ctrl-R: reload  Ctrl-L: sign out  Esc: quit"
+                    .to_string()
+            });
+            frame.render_widget(Line::from(hint), footer);
+        }
+    }
+}
+
+fn draw_login(frame: &mut Frame, area: Rect, server: &str, form: &LoginForm) {
+    let [_, area, _] = Layout::vertical([Constraint::Fill(1), Constraint::Length(6), Constraint::Fill(1)]).areas(area);
+    let [_, area, _] = Layout::horizontal([Constraint::Fill(1), Constraint::Length(50), Constraint::Fill(1)]).areas(area);
+
+    let marker = |active: bool| if active { "> " } else { "  " };
+    let mut lines = vec![
+        Line::from(format!("{}Username: {}", marker(!form.on_password), form.username)),
+        Line::from(format!("{}Password: {}", marker(form.on_password), "*".repeat(form.password.chars().count()))),
+    ];
+    if let Some(error) = &form.error {
+        lines.push(Line::styled(error.clone(), Style::default().fg(Color::Red)));
+    }
+    let block = Block::bordered().title(format!(" Sign in to {} ", server));
+    frame.render_widget(Paragraph::new(lines).block(block).wrap(Wrap { trim: true }), area);
+}
+
+fn draw_chats(frame: &mut Frame, area: Rect, view: &ChatView) {
+    let [chats, right] = Layout::horizontal([Constraint::Percentage(30), Constraint::Min(0)]).areas(area);
+    let [messages, input] = Layout::vertical([Constraint::Min(0), Constraint::Length(3)]).areas(right);
+
+    let items: Vec<ListItem> = view.chats.iter().map(|chat| ListItem::new(chat.name.clone())).collect();
+    let list = List::new(items)
+        .block(Block::bordered().title(format!(" Chats of {} ", view.username)))
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    let mut state = ListState::default().with_selected((!view.chats.is_empty()).then_some(view.selected));
+    frame.render_stateful_widget(list, chats, &mut state);
+
+    let Some(chat) = view.chats.get(view.selected) else {
+        let pane = Paragraph::new("You are not in any chats.").block(Block::bordered().title(" Messages "));
+        frame.render_widget(pane, messages);
+        return;
+    };
+    let mut title = format!(" {} ", chat.name);
+    if view.scroll > 0 {
+        title.push_str(&format!("({} newer below) ", view.scroll));
+    }
+    // One line per message, the newest at the bottom unless scrolled back.
+    let height = messages.height.saturating_sub(2) as usize;
+    let end = view.messages.len() - view.scroll.min(view.messages.len());
+    let lines: Vec<Line> = view.messages[end.saturating_sub(height)..end].iter().map(message_line).collect();
+    frame.render_widget(Paragraph::new(lines).block(Block::bordered().title(title)), messages);
+    frame.render_widget(Paragraph::new(view.input.as_str()).block(Block::bordered().title(" Message ")), input);
+}
+
+fn message_line(message: &Message) -> Line<'_> {
+    let time = message
+        .timestamp
+        .map(|at| at.with_timezone(&Local).format("%H:%M ").to_string())
+        .unwrap_or_default();
+    Line::from(vec![
+        Span::styled(time, Style::default().fg(Color::DarkGray)),
+        Span::styled(
+            format!("{}: ", message.author_name.as_deref().unwrap_or("unknown")),
+            Style::default().add_modifier(Modifier::BOLD),
+        ),
+        Span::raw(message.content.as_str()),
+    ])
+}
@@ -0,0 +1,59 @@
+use serde::Deserialize;
+use serde_json::json;
+use crate::models::chat_models::{Chat, Message};
+use crate::{Client, Result};
+
+pub(crate) const MESSAGE_FIELDS: &str = "id chatId content timestamp authorName";
+
+impl Client {
+    /// The chats the signed-in user takes part in.
+    pub async fn chats(&self) -> Result<Vec<Chat>> {
+        #[derive(Deserialize)]
+        struct Data {
+            me: Option<Me>,
+        }
+        #[derive(Deserialize)]
+        struct Me {
+            chats: Vec<Chat>,
+        }
+        let data: Data = self
+            .graphql("query { me { chats { id name createdAt } } }", json!({}))
+            .await?;
+        Ok(data.me.map(|me| me.chats).unwrap_or_default())
+    }
+
+    /// The latest `last` messages of a chat, oldest first; the server caps `last`. `None`
+    /// when there is no such chat or the user does not take part in it.
+    pub async fn messages(&self, chat_id: i32, last: u64) -> Result<Option<Vec<Message>>> {
+        #[derive(Deserialize)]
+        struct Data {
+            chat: Option<ChatMessages>,
+        }
+        #[derive(Deserialize)]
+        struct ChatMessages {
+            messages: Vec<Message>,
+        }
+        let query = format!(
+            "query($id: Int!, $last: Int!) {{ chat(id: $id) {{ messages(last: $last) {{ {} }} }} }}",
+            MESSAGE_FIELDS
+        );
+        let data: Data = self.graphql(&query, json!({ "id": chat_id, "last": last })).await?;
+        Ok(data.chat.map(|chat| chat.messages))
+    }
+
+    /// Posts a message to a chat the signed-in user takes part in. Subscribers of the chat,
+    /// including this client's own [`MessageStream`](crate::MessageStream)s, receive it too.
+    pub async fn send_message(&self, chat_id: i32, content: &str) -> Result<Message> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Data {
+            send_message: Message,
+        }
+        let query = format!(
+            "mutation($chatId: Int!, $content: String!) {{ sendMessage(chatId: $chatId, content: $content) {{ {} }} }}",
+            MESSAGE_FIELDS
+        );
+        let data: Data = self.graphql(&query, json!({ "chatId": chat_id, "content": content })).await?;
+        Ok(data.send_message)
+    }
+}
@@ -0,0 +1,93 @@
+use std::fmt;
+use reqwest::StatusCode;
+use serde_json::Value;
+use tokio_tungstenite::tungstenite;
+
+#[derive(Debug)]
+pub enum Error {
+    /// The server answered with an error status. `message` and `request_id` come from the
+    /// `ErrorResponse` envelope; `body` keeps any further fields, such as the `status`
+    /// and `until` of a suspended account.
+    Api {
+        status: StatusCode,
+        message: String,
+        request_id: Option<String>,
+        body: Value,
+    },
+    /// The request failed before a response arrived, or the response was not the
+    /// expected JSON.
+    Http(reqwest::Error),
+    /// An endpoint that needs a token was called before `login` or `with_token`.
+    NotSignedIn,
+    /// The GraphQL API answered with errors, such as for a chat the user does not take
+    /// part in.
+    GraphQL(Vec<String>),
+    /// A GraphQL result or realtime event was not the expected JSON.
+    Decode(serde_json::Error),
+    /// The realtime WebSocket could not be opened or broke off. An upgrade the server
+    /// refused is an `Api` error instead.
+    WebSocket(Box<tungstenite::Error>),
+    /// The server closed the realtime WebSocket: 4401 once the session is revoked or
+    /// expired, 4403 once the account is restricted, 1012 when the server restarts.
+    Closed { code: u16, reason: String },
+}
+
+impl Error {
+    /// The HTTP status of an `Api` error.
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            Error::Api { status, .. } => Some(*status),
+            Error::Http(err) => err.status(),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn from_body(status: StatusCode, body: Value) -> Self {
+        let message = body["message"]
+            .as_str()
+            .map(str::to_string)
+            .unwrap_or_else(|| status.canonical_reason().unwrap_or("Request failed").to_string());
+        let request_id = body["request_id"].as_str().map(str::to_string);
+        Error::Api { status, message, request_id, body }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Api { status, message, request_id: Some(id), .. } => {
+                write!(f, "{} ({}, request {})", message, status, id)
+            }
+            Error::Api { status, message, .. } => write!(f, "{} ({})", message, status),
+            Error::Http(err) => write!(f, "{}", err),
+            Error::NotSignedIn => f.write_str("Not signed in"),
+            Error::GraphQL(messages) => f.write_str(&messages.join("; ")),
+            Error::Decode(err) => write!(f, "Unexpected response: {}", err),
+            Error::WebSocket(err) => write!(f, "{}", err),
+            Error::Closed { code, reason } => write!(f, "{} (closed with {})", reason, code),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Http(err) => Some(err),
+            Error::Decode(err) => Some(err),
+            Error::WebSocket(err) => Some(err.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(err: reqwest::Error) -> Self {
+        Error::Http(err)
+    }
+}
+
+impl From<tungstenite::Error> for Error {
+    fn from(err: tungstenite::Error) -> Self {
+        Error::WebSocket(Box::new(err))
+    }
+}
@@ -0,0 +1,26 @@
+use crate::models::export_models::{DataExportResponse, DownloadExport, GetAllExportsResponse};
+use crate::{check, Client, Result};
+
+impl Client {
+    /// Queues an export of the signed-in account's data.
+    pub async fn request_export(&self) -> Result<DataExportResponse> {
+        self.authed_json(self.http.post(self.url("/users/exports"))).await
+    }
+
+    pub async fn exports(&self) -> Result<GetAllExportsResponse> {
+        self.authed_json(self.http.get(self.url("/users/exports"))).await
+    }
+
+    /// Its `download_url` is set once the archive is ready.
+    pub async fn export(&self, export_id: i32) -> Result<DataExportResponse> {
+        self.authed_json(self.http.get(self.url(&format!("/users/exports/{}", export_id)))).await
+    }
+
+    /// Fetches the zip archive with the signed token from a `download_url`. The server
+    /// deletes the archive once it has been served.
+    pub async fn download_export(&self, token: &str) -> Result<Vec<u8>> {
+        let query = DownloadExport { token: token.to_string() };
+        let response = self.http.get(self.url("/users/exports/download")).query(&query).send().await?;
+        Ok(check(response).await?.bytes().await?.to_vec())
+    }
+}
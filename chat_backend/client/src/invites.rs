@@ -0,0 +1,17 @@
+use crate::models::invite_models::{CreateInvite, GetAllInvitesResponse, InviteResponse};
+use crate::{Client, Result};
+
+// Admin only.
+impl Client {
+    pub async fn invites(&self) -> Result<GetAllInvitesResponse> {
+        self.authed_json(self.http.get(self.url("/invites"))).await
+    }
+
+    pub async fn create_invite(&self, form: &CreateInvite) -> Result<InviteResponse> {
+        self.authed_json(self.http.post(self.url("/invites")).json(form)).await
+    }
+
+    pub async fn revoke_invite(&self, id: i32) -> Result<InviteResponse> {
+        self.authed_json(self.http.delete(self.url(&format!("/invites/{}", id)))).await
+    }
+}
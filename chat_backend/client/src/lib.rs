@@ -0,0 +1,221 @@
+//! Async client for the BlockChat API, for internal services and bots.
+//!
+//! Accounts, sessions, invites, exports and the audit log go through the REST routes;
+//! chats and messages through GraphQL, with new messages streamed over its WebSocket
+//! (see [`MessageStream`]). Request and response bodies live in [`models`], so the client
+//! builds without the server.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use reqwest::header::WWW_AUTHENTICATE;
+use reqwest::{RequestBuilder, Response, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tokio::sync::Mutex;
+use models::user_models::{LoginResponse, LoginUser};
+
+mod audit;
+mod auth;
+mod chats;
+mod error;
+mod exports;
+mod invites;
+pub mod models;
+mod realtime;
+mod sessions;
+mod users;
+
+pub use error::Error;
+pub use realtime::MessageStream;
+
+/// Prefix of every API route.
+pub const V1: &str = "/api/v1";
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// A connection to one BlockChat server. Cheap to share behind an `Arc`; concurrent
+/// calls that find the session expired sign in again only once between them. Other 401s,
+/// such as a role the route does not allow, are returned as they are.
+pub struct Client {
+    http: reqwest::Client,
+    base_url: String,
+    auth: Mutex<Auth>,
+}
+
+#[derive(Default)]
+struct Auth {
+    token: Option<String>,
+    // Kept after `login` so an expired or revoked session can be replaced.
+    credentials: Option<(String, String)>,
+}
+
+impl Client {
+    /// A client for the server at `base_url`, e.g. `https://chat.example.com`.
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self::with_http_client(reqwest::Client::new(), base_url)
+    }
+
+    /// Like [`Client::new`], sending requests through a preconfigured `reqwest::Client`.
+    pub fn with_http_client(http: reqwest::Client, base_url: impl Into<String>) -> Self {
+        Self {
+            http,
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            auth: Mutex::new(Auth::default()),
+        }
+    }
+
+    /// Uses an already issued token. Without credentials it cannot be renewed, so calls
+    /// fail with a 401 `Error::Api` once it expires or is revoked.
+    pub fn with_token(mut self, token: impl Into<String>) -> Self {
+        self.auth.get_mut().token = Some(token.into());
+        self
+    }
+
+    /// The bearer token currently in use.
+    pub async fn token(&self) -> Option<String> {
+        self.auth.lock().await.token.clone()
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}{}", self.base_url, V1, path)
+    }
+
+    // The current token, renewed first when it has passed its `exp`. Routes picked by role
+    // answer such a token with a 405 rather than a 401, so it is not left to the server.
+    async fn fresh_token(&self) -> Result<String> {
+        let token = self.token().await.ok_or(Error::NotSignedIn)?;
+        if !expired(&token) || !self.refresh(&token).await? {
+            return Ok(token);
+        }
+        self.token().await.ok_or(Error::NotSignedIn)
+    }
+
+    // Sends a request that needs no token.
+    async fn public<T: DeserializeOwned>(&self, request: RequestBuilder) -> Result<T> {
+        json(request.send().await?).await
+    }
+
+    // Sends `request` with the current token. When the server rejects the token itself,
+    // it signs in again with the stored credentials and retries once; without credentials
+    // the 401 is returned as is.
+    async fn authed(&self, request: RequestBuilder) -> Result<Response> {
+        let token = self.fresh_token().await?;
+        let retry = request.try_clone();
+        let response = request.bearer_auth(&token).send().await?;
+        if !token_rejected(response.status(), response.headers()) {
+            return Ok(response);
+        }
+        match retry {
+            Some(retry) if self.refresh(&token).await? => {
+                let token = self.token().await.ok_or(Error::NotSignedIn)?;
+                Ok(retry.bearer_auth(token).send().await?)
+            }
+            _ => Ok(response),
+        }
+    }
+
+    async fn authed_json<T: DeserializeOwned>(&self, request: RequestBuilder) -> Result<T> {
+        json(self.authed(request).await?).await
+    }
+
+    // Runs a GraphQL query or mutation and returns its `data`.
+    async fn graphql<T: DeserializeOwned>(&self, query: &str, variables: Value) -> Result<T> {
+        let request = self
+            .http
+            .post(self.url("/graphql"))
+            .json(&json!({ "query": query, "variables": variables }));
+        let response: GraphQLResponse = self.authed_json(request).await?;
+        response.into_data()
+    }
+
+    // Replaces `stale` with a fresh token; false when there is nothing to sign in with.
+    async fn refresh(&self, stale: &str) -> Result<bool> {
+        let mut auth = self.auth.lock().await;
+        if auth.token.as_deref() != Some(stale) {
+            // Another call renewed it while this one waited for the lock.
+            return Ok(auth.token.is_some());
+        }
+        let Some((username, password)) = auth.credentials.clone() else {
+            return Ok(false);
+        };
+        let response = self.sign_in(&username, &password).await?;
+        auth.token = Some(response.token);
+        Ok(true)
+    }
+
+    async fn sign_in(&self, username: &str, password: &str) -> Result<LoginResponse> {
+        let form = LoginUser {
+            username: username.to_string(),
+            password: password.to_string(),
+        };
+        self.public(self.http.post(self.url("/users/login")).json(&form)).await
+    }
+}
+
+// The result of a query, mutation or subscription event.
+#[derive(Deserialize)]
+struct GraphQLResponse {
+    #[serde(default)]
+    data: Value,
+    #[serde(default)]
+    errors: Vec<GraphQLError>,
+}
+
+#[derive(Deserialize)]
+struct GraphQLError {
+    message: String,
+}
+
+impl GraphQLResponse {
+    fn into_data<T: DeserializeOwned>(self) -> Result<T> {
+        if !self.errors.is_empty() {
+            return Err(Error::GraphQL(self.errors.into_iter().map(|err| err.message).collect()));
+        }
+        serde_json::from_value(self.data).map_err(Error::Decode)
+    }
+}
+
+// Whether a 401 is down to the token itself, expired or revoked, as the server marks it
+// with `WWW-Authenticate: Bearer error="invalid_token"`. Only then is signing in again of
+// any use.
+fn token_rejected(status: StatusCode, headers: &reqwest::header::HeaderMap) -> bool {
+    status == StatusCode::UNAUTHORIZED
+        && headers
+            .get(WWW_AUTHENTICATE)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.contains("invalid_token"))
+}
+
+// Reads the `exp` claim without checking the signature, which only the server can do.
+// Tokens that cannot be read are left to the server to judge.
+fn expired(token: &str) -> bool {
+    #[derive(Deserialize)]
+    struct Expiry {
+        exp: u64,
+    }
+    let Some(expiry) = token
+        .split('.')
+        .nth(1)
+        .and_then(|payload| URL_SAFE_NO_PAD.decode(payload).ok())
+        .and_then(|payload| serde_json::from_slice::<Expiry>(&payload).ok())
+    else {
+        return false;
+    };
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |now| now.as_secs());
+    expiry.exp <= now
+}
+
+// Turns an error status into `Error::Api`, leaving successful responses untouched.
+async fn check(response: Response) -> Result<Response> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    let body = response.json::<Value>().await.unwrap_or(Value::Null);
+    Err(Error::from_body(status, body))
+}
+
+async fn json<T: DeserializeOwned>(response: Response) -> Result<T> {
+    Ok(check(response).await?.json().await?)
+}
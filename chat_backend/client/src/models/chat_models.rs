@@ -0,0 +1,23 @@
+//! Chats and messages, read through the GraphQL API and its `messageAdded` subscription.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Chat {
+    pub id: i32,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Message {
+    pub id: i32,
+    pub chat_id: i32,
+    pub content: String,
+    pub timestamp: Option<DateTime<Utc>>,
+    /// Kept after the author's account is purged.
+    pub author_name: Option<String>,
+}
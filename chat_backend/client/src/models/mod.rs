@@ -0,0 +1,10 @@
+//! Request and response bodies of the BlockChat API, as the client sends and reads them.
+//! They follow the server's `chat_backend::models` field for field; the tests run against
+//! the server, so the two cannot drift apart unnoticed.
+
+pub mod audit_models;
+pub mod chat_models;
+pub mod export_models;
+pub mod invite_models;
+pub mod session_models;
+pub mod user_models;
@@ -0,0 +1,175 @@
+use futures_util::{SinkExt, StreamExt};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::header::{HeaderValue, AUTHORIZATION, SEC_WEBSOCKET_PROTOCOL};
+use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+use tokio_tungstenite::tungstenite::{self, Message as Frame};
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+use crate::chats::MESSAGE_FIELDS;
+use crate::models::chat_models::Message;
+use crate::{token_rejected, Client, Error, GraphQLResponse, Result};
+
+type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+const PROTOCOL: &str = "graphql-transport-ws";
+// Each stream carries a single subscription.
+const SUBSCRIPTION_ID: &str = "1";
+
+/// Messages posted to one chat, delivered by the `messageAdded` GraphQL subscription over
+/// a WebSocket of its own. Dropping the stream drops the connection.
+pub struct MessageStream {
+    socket: Socket,
+    done: bool,
+}
+
+// What the server sends over `graphql-transport-ws`.
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ServerMessage {
+    ConnectionAck,
+    Next { payload: GraphQLResponse },
+    Complete,
+    Ping,
+    #[serde(other)]
+    Other,
+}
+
+impl Client {
+    /// Subscribes to the messages posted to a chat from now on. The token goes with the
+    /// upgrade request, so an expired or revoked session is renewed here as on any other
+    /// call. Once open, the stream ends with [`Error::Closed`] when the session does;
+    /// subscribe again to carry on with a new one.
+    pub async fn subscribe_messages(&self, chat_id: i32) -> Result<MessageStream> {
+        let token = self.fresh_token().await?;
+        let socket = match self.connect(&token).await {
+            Err(tungstenite::Error::Http(response))
+                if token_rejected(response.status(), response.headers()) && self.refresh(&token).await? =>
+            {
+                let token = self.token().await.ok_or(Error::NotSignedIn)?;
+                self.connect(&token).await
+            }
+            other => other,
+        };
+        MessageStream::subscribe(socket.map_err(refused)?, chat_id).await
+    }
+
+    async fn connect(&self, token: &str) -> tungstenite::Result<Socket> {
+        let mut request = self.url("/graphql/ws").replacen("http", "ws", 1).into_client_request()?;
+        let headers = request.headers_mut();
+        headers.insert(SEC_WEBSOCKET_PROTOCOL, HeaderValue::from_static(PROTOCOL));
+        let bearer = HeaderValue::from_str(&format!("Bearer {}", token)).map_err(tungstenite::http::Error::from)?;
+        headers.insert(AUTHORIZATION, bearer);
+        let (socket, _) = tokio_tungstenite::connect_async(request).await?;
+        Ok(socket)
+    }
+}
+
+// An upgrade the server answered with an error status becomes `Error::Api`, as that
+// response would have on any other route.
+fn refused(err: tungstenite::Error) -> Error {
+    match err {
+        tungstenite::Error::Http(response) => {
+            let body = response
+                .body()
+                .as_deref()
+                .and_then(|body| serde_json::from_slice(body).ok())
+                .unwrap_or(Value::Null);
+            Error::from_body(response.status(), body)
+        }
+        err => err.into(),
+    }
+}
+
+impl MessageStream {
+    async fn subscribe(socket: Socket, chat_id: i32) -> Result<Self> {
+        let mut stream = Self { socket, done: false };
+        // The upgrade request already carried the token.
+        stream.send(json!({ "type": "connection_init", "payload": {} })).await?;
+        loop {
+            match stream.event().await? {
+                Some(ServerMessage::ConnectionAck) => break,
+                Some(_) => continue,
+                None => {
+                    return Err(Error::Closed {
+                        code: CloseCode::Normal.into(),
+                        reason: "Closed before the connection was acknowledged".to_string(),
+                    })
+                }
+            }
+        }
+        let query = format!(
+            "subscription($chatId: Int!) {{ messageAdded(chatId: $chatId) {{ {} }} }}",
+            MESSAGE_FIELDS
+        );
+        stream
+            .send(json!({
+                "id": SUBSCRIPTION_ID,
+                "type": "subscribe",
+                "payload": { "query": query, "variables": { "chatId": chat_id } },
+            }))
+            .await?;
+        Ok(stream)
+    }
+
+    /// The next message posted to the chat; `None` once the subscription has ended. A
+    /// chat the user may not read fails here, with `Error::GraphQL`, rather than in
+    /// [`Client::subscribe_messages`]. After an error the stream is over.
+    pub async fn recv(&mut self) -> Option<Result<Message>> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Data {
+            message_added: Message,
+        }
+        if self.done {
+            return None;
+        }
+        let message = loop {
+            match self.event().await {
+                Ok(Some(ServerMessage::Next { payload })) => {
+                    break payload.into_data::<Data>().map(|data| data.message_added)
+                }
+                Ok(Some(ServerMessage::Ping)) => {
+                    if let Err(err) = self.send(json!({ "type": "pong" })).await {
+                        break Err(err);
+                    }
+                }
+                Ok(Some(ServerMessage::Complete)) | Ok(None) => {
+                    self.done = true;
+                    return None;
+                }
+                Ok(Some(_)) => continue,
+                Err(err) => break Err(err),
+            }
+        };
+        self.done = message.is_err();
+        Some(message)
+    }
+
+    /// Ends the subscription and closes the connection.
+    pub async fn close(mut self) -> Result<()> {
+        self.socket.close(None).await?;
+        Ok(())
+    }
+
+    async fn send(&mut self, message: Value) -> Result<()> {
+        self.socket.send(Frame::text(message.to_string())).await?;
+        Ok(())
+    }
+
+    // The next protocol message; `None` once the server closes the connection normally.
+    async fn event(&mut self) -> Result<Option<ServerMessage>> {
+        while let Some(frame) = self.socket.next().await {
+            match frame? {
+                Frame::Text(text) => return serde_json::from_str(&text).map(Some).map_err(Error::Decode),
+                Frame::Close(Some(frame)) if frame.code != CloseCode::Normal => {
+                    return Err(Error::Closed { code: frame.code.into(), reason: frame.reason.to_string() })
+                }
+                Frame::Close(_) => return Ok(None),
+                _ => continue,
+            }
+        }
+        Ok(None)
+    }
+}
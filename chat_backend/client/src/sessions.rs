@@ -0,0 +1,25 @@
+use crate::models::session_models::GetAllSessionsResponse;
+use crate::models::user_models::ResponseMessage;
+use crate::{Client, Result};
+
+impl Client {
+    /// Sessions of the signed-in account; `current` marks the one this client uses.
+    pub async fn sessions(&self) -> Result<GetAllSessionsResponse> {
+        self.authed_json(self.http.get(self.url("/users/sessions"))).await
+    }
+
+    pub async fn revoke_session(&self, session_id: i32) -> Result<ResponseMessage> {
+        self.authed_json(self.http.delete(self.url(&format!("/users/sessions/{}", session_id)))).await
+    }
+
+    /// Admin only.
+    pub async fn user_sessions(&self, user_id: i32) -> Result<GetAllSessionsResponse> {
+        self.authed_json(self.http.get(self.url(&format!("/users/{}/sessions", user_id)))).await
+    }
+
+    /// Admin only.
+    pub async fn revoke_user_session(&self, user_id: i32, session_id: i32) -> Result<ResponseMessage> {
+        let path = format!("/users/{}/sessions/{}", user_id, session_id);
+        self.authed_json(self.http.delete(self.url(&path))).await
+    }
+}
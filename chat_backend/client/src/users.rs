@@ -0,0 +1,68 @@
+use crate::models::user_models::{
+    CreateUser, EraseAccount, GetAllUsersResponse, ModerateUser, ResponseMessage, UpdateUser, UserFilter,
+    UserResponse,
+};
+use crate::{Client, Result};
+
+impl Client {
+    pub async fn users(&self, filter: &UserFilter) -> Result<GetAllUsersResponse> {
+        self.authed_json(self.http.get(self.url("/users/all")).query(filter)).await
+    }
+
+    /// The signed-in user, with the chats they take part in.
+    pub async fn me(&self) -> Result<UserResponse> {
+        self.authed_json(self.http.get(self.url("/users/me"))).await
+    }
+
+    pub async fn user(&self, id: i32) -> Result<UserResponse> {
+        self.authed_json(self.http.get(self.url(&format!("/users/{}", id)))).await
+    }
+
+    /// Admin only.
+    pub async fn create_user(&self, form: &CreateUser) -> Result<ResponseMessage> {
+        self.authed_json(self.http.post(self.url("/users/create")).json(form)).await
+    }
+
+    /// Users may only update their own profile; admins any.
+    pub async fn update_user(&self, id: i32, form: &UpdateUser) -> Result<ResponseMessage> {
+        self.authed_json(self.http.put(self.url(&format!("/users/{}", id))).json(form)).await
+    }
+
+    /// Admin only. The account can be restored until the purge grace period ends.
+    pub async fn delete_user(&self, id: i32) -> Result<ResponseMessage> {
+        self.authed_json(self.http.delete(self.url(&format!("/users/{}", id)))).await
+    }
+
+    /// Admin only.
+    pub async fn restore_user(&self, id: i32) -> Result<ResponseMessage> {
+        self.authed_json(self.http.post(self.url(&format!("/users/{}/restore", id)))).await
+    }
+
+    /// Erases the signed-in account for good and forgets its credentials.
+    pub async fn erase_account(&self, password: &str) -> Result<ResponseMessage> {
+        let form = EraseAccount { password: password.to_string() };
+        let response = self.authed_json(self.http.post(self.url("/users/erase")).json(&form)).await?;
+        self.logout().await;
+        Ok(response)
+    }
+
+    /// Admin only.
+    pub async fn erase_user(&self, id: i32) -> Result<ResponseMessage> {
+        self.authed_json(self.http.post(self.url(&format!("/users/{}/erase", id)))).await
+    }
+
+    /// Admin only.
+    pub async fn suspend_user(&self, id: i32, form: &ModerateUser) -> Result<UserResponse> {
+        self.authed_json(self.http.post(self.url(&format!("/users/{}/suspend", id))).json(form)).await
+    }
+
+    /// Admin only.
+    pub async fn ban_user(&self, id: i32, form: &ModerateUser) -> Result<UserResponse> {
+        self.authed_json(self.http.post(self.url(&format!("/users/{}/ban", id))).json(form)).await
+    }
+
+    /// Admin only.
+    pub async fn reinstate_user(&self, id: i32) -> Result<UserResponse> {
+        self.authed_json(self.http.post(self.url(&format!("/users/{}/reinstate", id)))).await
+    }
+}
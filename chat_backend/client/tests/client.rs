@@ -0,0 +1,185 @@
+// The server's integration test harness, served over HTTP for the client to talk to.
+#[path = "../../tests/support/mod.rs"]
+mod support;
+
+use std::time::Duration;
+use blockchat_client::models::audit_models::AuditEventFilter;
+use blockchat_client::models::user_models::{ModerateUser, UpdateUser, UserFilter};
+use blockchat_client::{Client, Error, MessageStream};
+use reqwest::StatusCode;
+use support::{TestApp, PASSWORD};
+
+fn no_changes() -> UpdateUser {
+    UpdateUser {
+        first_name: None,
+        last_name: None,
+        username: None,
+        email: None,
+        role_id: None,
+        avatar: None,
+        password: None,
+    }
+}
+
+// Subscribes and waits until the server has registered the subscription, so no message
+// sent afterwards is missed.
+async fn subscribe(app: &TestApp, client: &Client, chat_id: i32) -> MessageStream {
+    let subscribers = app.message_hub().subscribers();
+    let stream = client.subscribe_messages(chat_id).await.unwrap();
+    while app.message_hub().subscribers() == subscribers {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    stream
+}
+
+#[actix_web::test]
+async fn signs_in_and_reads_typed_responses() {
+    let app = TestApp::spawn().await;
+    let client = Client::new(app.serve());
+
+    let login = client.login("e2e-admin", PASSWORD).await.unwrap();
+    assert_eq!(client.token().await.as_deref(), Some(login.token.as_str()));
+    assert_eq!(client.me().await.unwrap().username, "e2e-admin");
+
+    let filter = UserFilter {
+        first_name: None,
+        last_name: None,
+        username: Some("e2e-user".to_string()),
+        chat_name: None,
+        author_username: None,
+    };
+    let users = client.users(&filter).await.unwrap().users;
+    assert_eq!(users.len(), 1);
+
+    let user = client.user(users[0].id).await.unwrap();
+    assert_eq!(user.username, "e2e-user");
+
+    app.teardown().await;
+}
+
+#[actix_web::test]
+async fn server_errors_come_back_typed() {
+    let app = TestApp::spawn().await;
+    let client = Client::new(app.serve());
+
+    let err = client.user(1).await.unwrap_err();
+    assert!(matches!(err, Error::NotSignedIn));
+
+    let err = client.login("e2e-user", "wrong-password").await.unwrap_err();
+    match err {
+        Error::Api { status, request_id, .. } => {
+            assert_eq!(status, StatusCode::UNAUTHORIZED);
+            assert!(request_id.is_some());
+        }
+        other => panic!("expected an API error, got {}", other),
+    }
+
+    let err = client.login("e2e-banned", PASSWORD).await.unwrap_err();
+    assert_eq!(err.status(), Some(StatusCode::FORBIDDEN));
+
+    client.login("e2e-user", PASSWORD).await.unwrap();
+    let reason = ModerateUser { reason: "test".to_string(), until: None };
+    let err = client.ban_user(app.user_id("e2e-admin").await, &reason).await.unwrap_err();
+    assert_eq!(err.status(), Some(StatusCode::UNAUTHORIZED));
+
+    app.teardown().await;
+}
+
+#[actix_web::test]
+async fn signs_in_again_when_the_session_is_revoked() {
+    let app = TestApp::spawn().await;
+    let client = Client::new(app.serve());
+    client.login("e2e-user", PASSWORD).await.unwrap();
+    let first = client.token().await.unwrap();
+
+    let sessions = client.sessions().await.unwrap().sessions;
+    let current = sessions.iter().find(|s| s.current).expect("current session");
+    client.revoke_session(current.id).await.unwrap();
+
+    let user_id = app.user_id("e2e-user").await;
+    client.update_user(user_id, &no_changes()).await.unwrap();
+    assert_ne!(client.token().await.unwrap(), first);
+
+    // A bare token cannot be renewed.
+    let bare = Client::new(app.serve()).with_token(first);
+    let err = bare.sessions().await.unwrap_err();
+    assert_eq!(err.status(), Some(StatusCode::UNAUTHORIZED));
+
+    app.teardown().await;
+}
+
+#[actix_web::test]
+async fn role_denials_do_not_sign_in_again() {
+    let app = TestApp::spawn().await;
+    let client = Client::new(app.serve());
+    client.login("e2e-user", PASSWORD).await.unwrap();
+    let token = client.token().await.unwrap();
+
+    let err = client.audit_events(&AuditEventFilter::default()).await.unwrap_err();
+    assert_eq!(err.status(), Some(StatusCode::UNAUTHORIZED));
+    assert_eq!(client.token().await.unwrap(), token);
+
+    app.teardown().await;
+}
+
+#[actix_web::test]
+async fn reads_sends_and_streams_chat_messages() {
+    let app = TestApp::spawn().await;
+    let client = Client::new(app.serve());
+    client.login("e2e-user", PASSWORD).await.unwrap();
+
+    let chats = client.chats().await.unwrap();
+    let chat = chats.iter().find(|chat| chat.name == "e2e-chat").expect("e2e chat");
+    let history = client.messages(chat.id, 20).await.unwrap().expect("readable chat");
+    assert_eq!(history.last().unwrap().content, "First e2e message");
+    assert!(client.messages(chat.id + 1000, 20).await.unwrap().is_none());
+
+    let mut stream = subscribe(&app, &client, chat.id).await;
+    let admin = Client::new(app.serve());
+    admin.login("e2e-admin", PASSWORD).await.unwrap();
+    let sent = admin.send_message(chat.id, "Hello from the client").await.unwrap();
+    let received = stream.recv().await.unwrap().unwrap();
+    assert_eq!(received.id, sent.id);
+    assert_eq!(received.content, "Hello from the client");
+    assert_eq!(received.author_name.as_deref(), Some("e2e-admin"));
+    stream.close().await.unwrap();
+
+    let err = client.send_message(chat.id, " ").await.unwrap_err();
+    assert!(matches!(err, Error::GraphQL(_)), "{}", err);
+
+    app.teardown().await;
+}
+
+#[actix_web::test]
+async fn streams_end_with_the_session_and_resubscribe_with_a_new_one() {
+    let app = TestApp::spawn().await;
+    let client = Client::new(app.serve());
+    client.login("e2e-user", PASSWORD).await.unwrap();
+    let chat_id = client.chats().await.unwrap()[0].id;
+    let mut stream = subscribe(&app, &client, chat_id).await;
+
+    let sessions = client.sessions().await.unwrap().sessions;
+    let current = sessions.iter().find(|s| s.current).expect("current session");
+    client.revoke_session(current.id).await.unwrap();
+    let first = client.token().await.unwrap();
+
+    // The next message delivered makes the server check the session again.
+    let admin = Client::new(app.serve());
+    admin.login("e2e-admin", PASSWORD).await.unwrap();
+    admin.send_message(chat_id, "After the revocation").await.unwrap();
+    match stream.recv().await {
+        Some(Err(Error::Closed { code, .. })) => assert_eq!(code, 4401),
+        other => panic!("expected the stream to close, got {:?}", other.map(|r| r.map(|m| m.content))),
+    }
+    assert!(stream.recv().await.is_none());
+    while app.message_hub().subscribers() > 0 {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    let mut stream = subscribe(&app, &client, chat_id).await;
+    assert_ne!(client.token().await.unwrap(), first);
+    admin.send_message(chat_id, "With the new session").await.unwrap();
+    assert_eq!(stream.recv().await.unwrap().unwrap().content, "With the new session");
+
+    app.teardown().await;
+}
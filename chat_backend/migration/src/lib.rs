@@ -2,6 +2,18 @@ pub use sea_orm_migration::prelude::*;
 
 mod m20241213_220758_init_schema;
 mod m20250313_212955_create_chats_table;
+mod m20250402_181512_create_webauthn_credentials_table;
+mod m20250418_093041_add_email_and_password_resets;
+mod m20250502_154210_add_registration_policy;
+mod m20250516_110327_create_user_sessions_table;
+mod m20250603_142218_add_account_moderation;
+mod m20250619_083512_add_soft_delete;
+mod m20250708_160245_create_data_exports_table;
+mod m20250722_101834_create_audit_events_table;
+mod m20250729_143006_extend_audit_events;
+mod m20250805_091527_add_previous_status;
+mod m20250812_103318_create_webauthn_challenges_table;
+mod sqlite;
 
 pub struct Migrator;
 
@@ -11,6 +23,17 @@ impl MigratorTrait for Migrator {
         vec![
             Box::new(m20241213_220758_init_schema::Migration),
             Box::new(m20250313_212955_create_chats_table::Migration),
+            Box::new(m20250402_181512_create_webauthn_credentials_table::Migration),
+            Box::new(m20250418_093041_add_email_and_password_resets::Migration),
+            Box::new(m20250502_154210_add_registration_policy::Migration),
+            Box::new(m20250516_110327_create_user_sessions_table::Migration),
+            Box::new(m20250603_142218_add_account_moderation::Migration),
+            Box::new(m20250619_083512_add_soft_delete::Migration),
+            Box::new(m20250708_160245_create_data_exports_table::Migration),
+            Box::new(m20250722_101834_create_audit_events_table::Migration),
+            Box::new(m20250729_143006_extend_audit_events::Migration),
+            Box::new(m20250805_091527_add_previous_status::Migration),
+            Box::new(m20250812_103318_create_webauthn_challenges_table::Migration),
         ]
     }
 }
@@ -1,4 +1,5 @@
 use sea_orm_migration::prelude::*;
+use crate::sqlite;
 
 #[derive(DeriveMigrationName)]
 pub struct Migration;
@@ -6,6 +7,10 @@ pub struct Migration;
 #[async_trait::async_trait]
 impl MigrationTrait for Migration {
     async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        if sqlite::is_sqlite(manager) {
+            return up_sqlite(manager).await;
+        }
+
         // 1. Alter Users: Drop the deprecated BlockId column.
         manager
             .alter_table(
@@ -36,79 +41,9 @@ impl MigrationTrait for Migration {
             )
             .await?;
             
-        // 3. Create the Chats table (replacing Blocks).
-        let mut fk_chats_author = {
-            let mut fk = ForeignKey::create();
-            fk.from(Chats::Table, Chats::AuthorId)
-                .to(Users::Table, Users::Id)
-                .on_delete(ForeignKeyAction::Cascade);
-            fk
-        };
-        manager
-            .create_table(
-                Table::create()
-                    .table(Chats::Table)
-                    .if_not_exists()
-                    .col(
-                        ColumnDef::new(Chats::Id)
-                            .integer()
-                            .not_null()
-                            .auto_increment()
-                            .primary_key(),
-                    )
-                    .col(
-                        ColumnDef::new(Chats::Name)
-                            .string()
-                            .not_null()
-                            .unique_key(),
-                    )
-                    .col(ColumnDef::new(Chats::AuthorId).integer().not_null())
-                    .foreign_key(&mut fk_chats_author)
-                    .col(ColumnDef::new(Chats::Image).binary().null())
-                    .col(
-                        ColumnDef::new(Chats::CreatedAt)
-                            .timestamp_with_time_zone()
-                            .default(Expr::current_timestamp()),
-                    )
-                    .to_owned(),
-            )
-            .await?;
-            
-        // 4. Create the ChatParticipants table.
-        let mut fk_cp_chat = {
-            let mut fk = ForeignKey::create();
-            fk.from(ChatParticipants::Table, ChatParticipants::ChatId)
-                .to(Chats::Table, Chats::Id)
-                .on_delete(ForeignKeyAction::Cascade);
-            fk
-        };
-        let mut fk_cp_user = {
-            let mut fk = ForeignKey::create();
-            fk.from(ChatParticipants::Table, ChatParticipants::UserId)
-                .to(Users::Table, Users::Id)
-                .on_delete(ForeignKeyAction::Cascade);
-            fk
-        };
-        manager
-            .create_table(
-                Table::create()
-                    .table(ChatParticipants::Table)
-                    .if_not_exists()
-                    .col(
-                        ColumnDef::new(ChatParticipants::Id)
-                            .integer()
-                            .not_null()
-                            .auto_increment()
-                            .primary_key(),
-                    )
-                    .col(ColumnDef::new(ChatParticipants::ChatId).integer().not_null())
-                    .col(ColumnDef::new(ChatParticipants::UserId).integer().not_null())
-                    .foreign_key(&mut fk_cp_chat)
-                    .foreign_key(&mut fk_cp_user)
-                    .to_owned(),
-            )
-            .await?;
-            
+        // 3-4. Create the Chats and ChatParticipants tables (replacing Blocks).
+        create_chat_tables(manager).await?;
+
         // 5. Alter Messages: Add a new foreign key on ChatId referencing Chats.
         let fk_messages_chat = {
             let mut fk = ForeignKey::create();
@@ -135,6 +70,10 @@ impl MigrationTrait for Migration {
     }
     
     async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        if sqlite::is_sqlite(manager) {
+            return Err(sqlite::irreversible(self.name()));
+        }
+
         // 1. Recreate the Blocks table.
         manager
             .create_table(
@@ -236,11 +175,183 @@ impl MigrationTrait for Migration {
     }
 }
 
+async fn create_chat_tables(manager: &SchemaManager<'_>) -> Result<(), DbErr> {
+    // 3. Create the Chats table (replacing Blocks).
+    let mut fk_chats_author = {
+        let mut fk = ForeignKey::create();
+        fk.from(Chats::Table, Chats::AuthorId)
+            .to(Users::Table, Users::Id)
+            .on_delete(ForeignKeyAction::Cascade);
+        fk
+    };
+    manager
+        .create_table(
+            Table::create()
+                .table(Chats::Table)
+                .if_not_exists()
+                .col(
+                    ColumnDef::new(Chats::Id)
+                        .integer()
+                        .not_null()
+                        .auto_increment()
+                        .primary_key(),
+                )
+                .col(
+                    ColumnDef::new(Chats::Name)
+                        .string()
+                        .not_null()
+                        .unique_key(),
+                )
+                .col(ColumnDef::new(Chats::AuthorId).integer().not_null())
+                .foreign_key(&mut fk_chats_author)
+                .col(ColumnDef::new(Chats::Image).binary().null())
+                .col(
+                    ColumnDef::new(Chats::CreatedAt)
+                        .timestamp_with_time_zone()
+                        .default(Expr::current_timestamp()),
+                )
+                .to_owned(),
+        )
+        .await?;
+
+    // 4. Create the ChatParticipants table.
+    let mut fk_cp_chat = {
+        let mut fk = ForeignKey::create();
+        fk.from(ChatParticipants::Table, ChatParticipants::ChatId)
+            .to(Chats::Table, Chats::Id)
+            .on_delete(ForeignKeyAction::Cascade);
+        fk
+    };
+    let mut fk_cp_user = {
+        let mut fk = ForeignKey::create();
+        fk.from(ChatParticipants::Table, ChatParticipants::UserId)
+            .to(Users::Table, Users::Id)
+            .on_delete(ForeignKeyAction::Cascade);
+        fk
+    };
+    manager
+        .create_table(
+            Table::create()
+                .table(ChatParticipants::Table)
+                .if_not_exists()
+                .col(
+                    ColumnDef::new(ChatParticipants::Id)
+                        .integer()
+                        .not_null()
+                        .auto_increment()
+                        .primary_key(),
+                )
+                .col(ColumnDef::new(ChatParticipants::ChatId).integer().not_null())
+                .col(ColumnDef::new(ChatParticipants::UserId).integer().not_null())
+                .foreign_key(&mut fk_cp_chat)
+                .foreign_key(&mut fk_cp_user)
+                .to_owned(),
+        )
+        .await?;
+
+    Ok(())
+}
+
+// SQLite cannot drop a column that has a foreign key or change a foreign key in place,
+// so Users and Messages are rebuilt in their new shape once Chats exists.
+async fn up_sqlite(manager: &SchemaManager<'_>) -> Result<(), DbErr> {
+    create_chat_tables(manager).await?;
+
+    sqlite::rebuild_table(
+        manager,
+        Users::Table,
+        Table::create()
+            .col(
+                ColumnDef::new(Users::Id)
+                    .integer()
+                    .not_null()
+                    .auto_increment()
+                    .primary_key(),
+            )
+            .col(ColumnDef::new(Users::FirstName).string().not_null())
+            .col(ColumnDef::new(Users::LastName).string().not_null())
+            .col(ColumnDef::new(Users::Username).string().not_null().unique_key())
+            .col(ColumnDef::new(Users::Password).string().not_null())
+            .col(ColumnDef::new(Users::Avatar).binary())
+            .col(ColumnDef::new(Users::RoleId).integer().null())
+            .foreign_key(
+                ForeignKey::create()
+                    .from(Users::Table, Users::RoleId)
+                    .to(Roles::Table, Roles::Id)
+                    .on_delete(ForeignKeyAction::SetNull),
+            )
+            .col(
+                ColumnDef::new(Users::CreatedAt)
+                    .timestamp_with_time_zone()
+                    .default(Expr::current_timestamp()),
+            )
+            .to_owned(),
+        &[
+            ("id", "id"),
+            ("first_name", "first_name"),
+            ("last_name", "last_name"),
+            ("username", "username"),
+            ("password", "password"),
+            ("avatar", "avatar"),
+            ("role_id", "role_id"),
+            ("created_at", "created_at"),
+        ],
+    )
+    .await?;
+
+    sqlite::rebuild_table(
+        manager,
+        Messages::Table,
+        Table::create()
+            .col(
+                ColumnDef::new(Messages::Id)
+                    .integer()
+                    .not_null()
+                    .auto_increment()
+                    .primary_key(),
+            )
+            .col(ColumnDef::new(Messages::UserId).integer().not_null())
+            .foreign_key(
+                ForeignKey::create()
+                    .from(Messages::Table, Messages::UserId)
+                    .to(Users::Table, Users::Id)
+                    .on_delete(ForeignKeyAction::Cascade),
+            )
+            .col(ColumnDef::new(Messages::ChatId).integer().not_null())
+            .foreign_key(
+                ForeignKey::create()
+                    .from(Messages::Table, Messages::ChatId)
+                    .to(Chats::Table, Chats::Id)
+                    .on_delete(ForeignKeyAction::Cascade),
+            )
+            .col(ColumnDef::new(Messages::Content).string().not_null())
+            .col(ColumnDef::new(Messages::Metadata).json_binary())
+            .col(
+                ColumnDef::new(Messages::Timestamp)
+                    .timestamp_with_time_zone()
+                    .default(Expr::current_timestamp()),
+            )
+            .to_owned(),
+        &[
+            ("id", "id"),
+            ("user_id", "user_id"),
+            ("chat_id", "block_id"),
+            ("content", "content"),
+            ("metadata", "metadata"),
+            ("timestamp", "timestamp"),
+        ],
+    )
+    .await?;
+
+    manager
+        .drop_table(Table::drop().table(Blocks::Table).to_owned())
+        .await
+}
+
 #[derive(Iden)]
 enum Roles {
     Table,
     Id,
-    Name,
 }
 
 #[derive(Iden)]
@@ -0,0 +1,106 @@
+use sea_orm_migration::prelude::*;
+use crate::sqlite;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // 1. Alter Users: Why the current status was set, by whom, and until when. One
+        //    column per statement, as SQLite cannot alter several at once.
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Users::Table)
+                    .add_column(ColumnDef::new(Users::StatusReason).text().null())
+                    .to_owned(),
+            )
+            .await?;
+        // SQLite cannot add a foreign key later, only declare one with the column.
+        let status_set_by = if sqlite::is_sqlite(manager) {
+            ColumnDef::new(Users::StatusSetBy)
+                .integer()
+                .null()
+                .extra("REFERENCES \"users\" (\"id\") ON DELETE SET NULL")
+                .to_owned()
+        } else {
+            ColumnDef::new(Users::StatusSetBy).integer().null().to_owned()
+        };
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Users::Table)
+                    .add_column(status_set_by)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Users::Table)
+                    .add_column(
+                        ColumnDef::new(Users::StatusUntil)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // 2. Alter Users: Add a foreign key on StatusSetBy referencing the acting admin.
+        if !sqlite::is_sqlite(manager) {
+            let fk_users_status_set_by = {
+                let mut fk = ForeignKey::create();
+                fk.from(Users::Table, Users::StatusSetBy)
+                    .to(Users::Table, Users::Id)
+                    .on_delete(ForeignKeyAction::SetNull);
+                fk.get_foreign_key().clone()
+            };
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Users::Table)
+                        .add_foreign_key(&fk_users_status_set_by)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        if sqlite::is_sqlite(manager) {
+            return Err(sqlite::irreversible(self.name()));
+        }
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Users::Table)
+                    .drop_foreign_key(Alias::new("users_status_set_by_fkey"))
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Users::Table)
+                    .drop_column(Users::StatusUntil)
+                    .drop_column(Users::StatusSetBy)
+                    .drop_column(Users::StatusReason)
+                    .to_owned(),
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum Users {
+    Table,
+    Id,
+    StatusReason,
+    StatusSetBy,
+    StatusUntil,
+}
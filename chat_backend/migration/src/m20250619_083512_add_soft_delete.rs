@@ -0,0 +1,318 @@
+use sea_orm_migration::prelude::*;
+use crate::sqlite;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // 1. Alter Users: Mark deleted accounts instead of removing the row.
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Users::Table)
+                    .add_column(
+                        ColumnDef::new(Users::DeletedAt)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        if sqlite::is_sqlite(manager) {
+            return up_sqlite(manager).await;
+        }
+
+        // 2a. Alter Chats: Add DeletedAt and let chats outlive their author.
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Chats::Table)
+                    .add_column(
+                        ColumnDef::new(Chats::DeletedAt)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .modify_column(ColumnDef::new(Chats::AuthorId).integer().null())
+                    .drop_foreign_key(Alias::new("chats_author_id_fkey"))
+                    .to_owned(),
+            )
+            .await?;
+
+        // 2b. Alter Chats: Re-add the author foreign key with SET NULL.
+        let fk_chats_author = {
+            let mut fk = ForeignKey::create();
+            fk.name("chats_author_id_fkey")
+                .from(Chats::Table, Chats::AuthorId)
+                .to(Users::Table, Users::Id)
+                .on_delete(ForeignKeyAction::SetNull);
+            fk.get_foreign_key().clone()
+        };
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Chats::Table)
+                    .add_foreign_key(&fk_chats_author)
+                    .to_owned(),
+            )
+            .await?;
+
+        // 3a. Alter Messages: Add DeletedAt, and AuthorName to keep the author's name
+        //     once their account is purged.
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Messages::Table)
+                    .add_column(
+                        ColumnDef::new(Messages::DeletedAt)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .add_column(ColumnDef::new(Messages::AuthorName).string().null())
+                    .modify_column(ColumnDef::new(Messages::UserId).integer().null())
+                    .drop_foreign_key(Alias::new("messages_user_id_fkey"))
+                    .to_owned(),
+            )
+            .await?;
+
+        // 3b. Alter Messages: Re-add the author foreign key with SET NULL.
+        let fk_messages_user = {
+            let mut fk = ForeignKey::create();
+            fk.name("messages_user_id_fkey")
+                .from(Messages::Table, Messages::UserId)
+                .to(Users::Table, Users::Id)
+                .on_delete(ForeignKeyAction::SetNull);
+            fk.get_foreign_key().clone()
+        };
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Messages::Table)
+                    .add_foreign_key(&fk_messages_user)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        if sqlite::is_sqlite(manager) {
+            return Err(sqlite::irreversible(self.name()));
+        }
+
+        // 1. Rows whose author was purged cannot satisfy NOT NULL again.
+        manager
+            .exec_stmt(
+                Query::delete()
+                    .from_table(Messages::Table)
+                    .and_where(Expr::col(Messages::UserId).is_null())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .exec_stmt(
+                Query::delete()
+                    .from_table(Chats::Table)
+                    .and_where(Expr::col(Chats::AuthorId).is_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        // 2. Alter Messages: Restore the cascading author foreign key.
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Messages::Table)
+                    .drop_foreign_key(Alias::new("messages_user_id_fkey"))
+                    .drop_column(Messages::AuthorName)
+                    .drop_column(Messages::DeletedAt)
+                    .modify_column(ColumnDef::new(Messages::UserId).integer().not_null())
+                    .to_owned(),
+            )
+            .await?;
+        let fk_messages_user = {
+            let mut fk = ForeignKey::create();
+            fk.name("messages_user_id_fkey")
+                .from(Messages::Table, Messages::UserId)
+                .to(Users::Table, Users::Id)
+                .on_delete(ForeignKeyAction::Cascade);
+            fk.get_foreign_key().clone()
+        };
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Messages::Table)
+                    .add_foreign_key(&fk_messages_user)
+                    .to_owned(),
+            )
+            .await?;
+
+        // 3. Alter Chats: Restore the cascading author foreign key.
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Chats::Table)
+                    .drop_foreign_key(Alias::new("chats_author_id_fkey"))
+                    .drop_column(Chats::DeletedAt)
+                    .modify_column(ColumnDef::new(Chats::AuthorId).integer().not_null())
+                    .to_owned(),
+            )
+            .await?;
+        let fk_chats_author = {
+            let mut fk = ForeignKey::create();
+            fk.name("chats_author_id_fkey")
+                .from(Chats::Table, Chats::AuthorId)
+                .to(Users::Table, Users::Id)
+                .on_delete(ForeignKeyAction::Cascade);
+            fk.get_foreign_key().clone()
+        };
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Chats::Table)
+                    .add_foreign_key(&fk_chats_author)
+                    .to_owned(),
+            )
+            .await?;
+
+        // 4. Alter Users: Drop DeletedAt.
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Users::Table)
+                    .drop_column(Users::DeletedAt)
+                    .to_owned(),
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+// SQLite cannot relax NOT NULL or replace a foreign key in place, so Messages and then
+// Chats are rebuilt in their new shape.
+async fn up_sqlite(manager: &SchemaManager<'_>) -> Result<(), DbErr> {
+    sqlite::rebuild_table(
+        manager,
+        Messages::Table,
+        Table::create()
+            .col(
+                ColumnDef::new(Messages::Id)
+                    .integer()
+                    .not_null()
+                    .auto_increment()
+                    .primary_key(),
+            )
+            .col(ColumnDef::new(Messages::UserId).integer().null())
+            .foreign_key(
+                ForeignKey::create()
+                    .from(Messages::Table, Messages::UserId)
+                    .to(Users::Table, Users::Id)
+                    .on_delete(ForeignKeyAction::SetNull),
+            )
+            .col(ColumnDef::new(Messages::ChatId).integer().not_null())
+            .foreign_key(
+                ForeignKey::create()
+                    .from(Messages::Table, Messages::ChatId)
+                    .to(Chats::Table, Chats::Id)
+                    .on_delete(ForeignKeyAction::Cascade),
+            )
+            .col(ColumnDef::new(Messages::Content).string().not_null())
+            .col(ColumnDef::new(Messages::Metadata).json_binary())
+            .col(
+                ColumnDef::new(Messages::Timestamp)
+                    .timestamp_with_time_zone()
+                    .default(Expr::current_timestamp()),
+            )
+            .col(
+                ColumnDef::new(Messages::DeletedAt)
+                    .timestamp_with_time_zone()
+                    .null(),
+            )
+            .col(ColumnDef::new(Messages::AuthorName).string().null())
+            .to_owned(),
+        &[
+            ("id", "id"),
+            ("user_id", "user_id"),
+            ("chat_id", "chat_id"),
+            ("content", "content"),
+            ("metadata", "metadata"),
+            ("timestamp", "timestamp"),
+        ],
+    )
+    .await?;
+
+    sqlite::rebuild_table(
+        manager,
+        Chats::Table,
+        Table::create()
+            .col(
+                ColumnDef::new(Chats::Id)
+                    .integer()
+                    .not_null()
+                    .auto_increment()
+                    .primary_key(),
+            )
+            .col(ColumnDef::new(Chats::Name).string().not_null().unique_key())
+            .col(ColumnDef::new(Chats::AuthorId).integer().null())
+            .foreign_key(
+                ForeignKey::create()
+                    .from(Chats::Table, Chats::AuthorId)
+                    .to(Users::Table, Users::Id)
+                    .on_delete(ForeignKeyAction::SetNull),
+            )
+            .col(ColumnDef::new(Chats::Image).binary().null())
+            .col(
+                ColumnDef::new(Chats::CreatedAt)
+                    .timestamp_with_time_zone()
+                    .default(Expr::current_timestamp()),
+            )
+            .col(
+                ColumnDef::new(Chats::DeletedAt)
+                    .timestamp_with_time_zone()
+                    .null(),
+            )
+            .to_owned(),
+        &[
+            ("id", "id"),
+            ("name", "name"),
+            ("author_id", "author_id"),
+            ("image", "image"),
+            ("created_at", "created_at"),
+        ],
+    )
+    .await
+}
+
+#[derive(Iden)]
+enum Users {
+    Table,
+    Id,
+    DeletedAt,
+}
+
+#[derive(Iden)]
+enum Chats {
+    Table,
+    Id,
+    Name,
+    AuthorId,
+    Image,
+    CreatedAt,
+    DeletedAt,
+}
+
+#[derive(Iden)]
+enum Messages {
+    Table,
+    Id,
+    UserId,
+    ChatId,
+    Content,
+    Metadata,
+    Timestamp,
+    AuthorName,
+    DeletedAt,
+}
@@ -0,0 +1,149 @@
+use sea_orm_migration::prelude::*;
+use crate::sqlite;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // 1. Alter AuditEvents: Record where each action came from. One column per
+        //    statement, as SQLite cannot alter several at once.
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AuditEvents::Table)
+                    .add_column(ColumnDef::new(AuditEvents::IpAddress).string().null())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AuditEvents::Table)
+                    .add_column(ColumnDef::new(AuditEvents::RequestId).string().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        // 2. Index the columns the admin endpoint filters and sorts on.
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_audit_events_actor_id")
+                    .table(AuditEvents::Table)
+                    .col(AuditEvents::ActorId)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_audit_events_created_at")
+                    .table(AuditEvents::Table)
+                    .col(AuditEvents::CreatedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        // 3. Make the table append-only.
+        if sqlite::is_sqlite(manager) {
+            for event in ["UPDATE", "DELETE"] {
+                manager
+                    .get_connection()
+                    .execute_unprepared(&format!(
+                        "CREATE TRIGGER audit_events_append_only_{} BEFORE {} ON audit_events \
+                         BEGIN SELECT RAISE(ABORT, 'audit_events is append-only'); END;",
+                        event.to_lowercase(),
+                        event
+                    ))
+                    .await?;
+            }
+            return Ok(());
+        }
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"
+                CREATE OR REPLACE FUNCTION audit_events_append_only() RETURNS trigger AS $$
+                BEGIN
+                    RAISE EXCEPTION 'audit_events is append-only';
+                END;
+                $$ LANGUAGE plpgsql;
+
+                CREATE TRIGGER audit_events_append_only
+                    BEFORE UPDATE OR DELETE ON audit_events
+                    FOR EACH ROW EXECUTE FUNCTION audit_events_append_only();
+                "#,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        if sqlite::is_sqlite(manager) {
+            for event in ["update", "delete"] {
+                manager
+                    .get_connection()
+                    .execute_unprepared(&format!(
+                        "DROP TRIGGER IF EXISTS audit_events_append_only_{}",
+                        event
+                    ))
+                    .await?;
+            }
+        } else {
+            manager
+                .get_connection()
+                .execute_unprepared(
+                    r#"
+                    DROP TRIGGER IF EXISTS audit_events_append_only ON audit_events;
+                    DROP FUNCTION IF EXISTS audit_events_append_only();
+                    "#,
+                )
+                .await?;
+        }
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_audit_events_created_at")
+                    .table(AuditEvents::Table)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_audit_events_actor_id")
+                    .table(AuditEvents::Table)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AuditEvents::Table)
+                    .drop_column(AuditEvents::RequestId)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AuditEvents::Table)
+                    .drop_column(AuditEvents::IpAddress)
+                    .to_owned(),
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum AuditEvents {
+    Table,
+    ActorId,
+    IpAddress,
+    RequestId,
+    CreatedAt,
+}
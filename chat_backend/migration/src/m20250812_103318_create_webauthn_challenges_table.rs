@@ -0,0 +1,71 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Challenges of passkey ceremonies in progress. A row is deleted when its
+        // ceremony finishes, so each challenge is answered at most once.
+        manager
+            .create_table(
+                Table::create()
+                    .table(WebauthnChallenges::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(WebauthnChallenges::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(WebauthnChallenges::Ceremony).string().not_null())
+                    .col(
+                        ColumnDef::new(WebauthnChallenges::Challenge)
+                            .binary()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(ColumnDef::new(WebauthnChallenges::UserId).integer().null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(WebauthnChallenges::Table, WebauthnChallenges::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .col(
+                        ColumnDef::new(WebauthnChallenges::ExpiresAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(WebauthnChallenges::Table).to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum Users {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum WebauthnChallenges {
+    Table,
+    Id,
+    Ceremony,
+    Challenge,
+    UserId,
+    ExpiresAt,
+}
@@ -0,0 +1,85 @@
+//! Helpers for the parts of a migration SQLite cannot express as `ALTER TABLE`.
+//!
+//! SQLite only adds, renames and drops columns in place; a column's nullability and a
+//! table's foreign keys are fixed when the table is created. Migrations written before
+//! SQLite was supported branch on [`is_sqlite`] and rebuild the table instead.
+
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_orm::{DbBackend, Statement};
+
+pub fn is_sqlite(manager: &SchemaManager) -> bool {
+    manager.get_database_backend() == DbBackend::Sqlite
+}
+
+/// The error returned by `down` for migrations that only know how to go forward on SQLite.
+pub fn irreversible(migration: &str) -> DbErr {
+    DbErr::Migration(format!("{} cannot be reverted on SQLite", migration))
+}
+
+/// Replaces `table` with one created by `create` and copies every row across, filling
+/// each new column from an SQL expression over the old row (`(column, expression)`).
+///
+/// Dropping the old table fires the `ON DELETE` actions of foreign keys that point at
+/// it, so this refuses to run while another table holding rows references it. That
+/// never happens for migrations that predate SQLite support, as those only ever run
+/// against a new database.
+pub async fn rebuild_table<T>(
+    manager: &SchemaManager<'_>,
+    table: T,
+    mut create: TableCreateStatement,
+    columns: &[(&str, &str)],
+) -> Result<(), DbErr>
+where
+    T: Iden,
+{
+    let db = manager.get_connection();
+    let name = table.to_string();
+    let staging = format!("{}_rebuild", name);
+
+    manager.create_table(create.table(Alias::new(&staging)).to_owned()).await?;
+    let (targets, sources): (Vec<String>, Vec<&str>) = columns
+        .iter()
+        .map(|(column, expression)| (format!("\"{}\"", column), *expression))
+        .unzip();
+    db.execute_unprepared(&format!(
+        "INSERT INTO \"{}\" ({}) SELECT {} FROM \"{}\"",
+        staging,
+        targets.join(", "),
+        sources.join(", "),
+        name
+    ))
+    .await?;
+
+    let referencing = db
+        .query_all(Statement::from_sql_and_values(
+            DbBackend::Sqlite,
+            "SELECT DISTINCT m.name AS name FROM sqlite_master m \
+             JOIN pragma_foreign_key_list(m.name) f \
+             WHERE m.type = 'table' AND f.\"table\" = $1 AND m.name <> $1",
+            [name.clone().into()],
+        ))
+        .await?;
+    for row in referencing {
+        let child: String = row.try_get("", "name")?;
+        let has_rows = db
+            .query_one(Statement::from_string(
+                DbBackend::Sqlite,
+                format!("SELECT EXISTS (SELECT 1 FROM \"{}\") AS has_rows", child),
+            ))
+            .await?
+            .map(|row| row.try_get::<bool>("", "has_rows"))
+            .transpose()?
+            .unwrap_or(false);
+        if has_rows {
+            return Err(DbErr::Migration(format!(
+                "Cannot rebuild {} while {} has rows that reference it",
+                name, child
+            )));
+        }
+    }
+
+    manager.drop_table(Table::drop().table(Alias::new(&name)).to_owned()).await?;
+    manager
+        .rename_table(Table::rename().table(Alias::new(&staging), Alias::new(&name)).to_owned())
+        .await
+}
@@ -0,0 +1,78 @@
+use actix_cors::Cors;
+use actix_web::body::MessageBody;
+use actix_web::dev::{ServiceFactory, ServiceRequest, ServiceResponse};
+use actix_web::{web, App, Error};
+use sea_orm::DatabaseConnection;
+use crate::config::Config;
+use crate::graphql;
+use crate::mailer::Mailer;
+use crate::middleware::custom_logger::CustomLogger;
+use crate::middleware::deprecation::Deprecated;
+use crate::middleware::metrics::RequestMetrics;
+use crate::middleware::request_id::{AssignRequestId, REQUEST_ID_HEADER};
+use crate::middleware::trace::TraceRequests;
+use crate::routes;
+use crate::shutdown::Shutdown;
+use crate::utils::messages::MessageHub;
+
+// Everything the handlers expect to find in app data.
+#[derive(Clone)]
+pub struct AppState {
+    pub db: DatabaseConnection,
+    pub config: Config,
+    pub mailer: web::Data<dyn Mailer>,
+    pub shutdown: web::Data<Shutdown>,
+    pub messages: MessageHub,
+}
+
+// The API application with its middleware and routes. `main` builds one per worker and
+// the integration tests use the same function, so both exercise identical wiring.
+pub fn build(
+    state: &AppState,
+) -> App<
+    impl ServiceFactory<
+        ServiceRequest,
+        Config = (),
+        Response = ServiceResponse<impl MessageBody>,
+        Error = Error,
+        InitError = (),
+    >,
+> {
+    let cors = Cors::default()
+        .allowed_origin(&state.config.frontend_url)
+        .allowed_methods(vec!["GET", "POST", "PUT", "DELETE", "OPTIONS"])
+        .allowed_headers(vec![
+            actix_web::http::header::AUTHORIZATION,
+            actix_web::http::header::ACCEPT,
+            actix_web::http::header::CONTENT_TYPE,
+            REQUEST_ID_HEADER,
+        ])
+        .expose_headers(vec![REQUEST_ID_HEADER])
+        .max_age(3600);
+    App::new()
+        .wrap(CustomLogger)
+        .wrap(RequestMetrics)
+        .wrap(TraceRequests)
+        .wrap(cors)
+        .wrap(AssignRequestId)
+        .app_data(web::Data::new(state.db.clone()))
+        .app_data(web::Data::new(state.config.clone()))
+        .app_data(state.mailer.clone())
+        .app_data(state.shutdown.clone())
+        .app_data(web::Data::new(graphql::build_schema(state.messages.clone())))
+        // Probes and docs stay unversioned.
+        .configure(routes::health_routes::configure)
+        .configure(routes::docs_routes::configure)
+        .configure(routes::graphql_routes::configure)
+        .service(web::scope(routes::V1).configure(routes::v1))
+        // The original unversioned paths, kept as aliases until the sunset date.
+        .service(
+            web::scope("")
+                .wrap(Deprecated::new(
+                    routes::V1,
+                    state.config.legacy_routes_deprecated,
+                    state.config.legacy_routes_sunset,
+                ))
+                .configure(routes::v1),
+        )
+}
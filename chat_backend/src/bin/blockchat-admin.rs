@@ -0,0 +1,414 @@
+use std::error::Error;
+use std::io::{BufRead, IsTerminal, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use chrono::{DateTime, Utc};
+use clap::{Parser, Subcommand};
+use sea_orm::sea_query::Expr;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, Database, DatabaseConnection, EntityTrait,
+    QueryFilter, QueryOrder, Set, Statement, TransactionTrait,
+};
+use serde_json::Value;
+use chat_backend::config::{Config, MigrationMode};
+use chat_backend::entities::prelude::{Roles, UserSessions, Users};
+use chat_backend::entities::sea_orm_active_enums::AccountStatus;
+use chat_backend::entities::{roles, user_sessions, users};
+use chat_backend::handlers::user_handler::normalize_email;
+use chat_backend::utils::audit::{self, AuditContext, AuditEvent};
+use chat_backend::utils::moderation;
+use chat_backend::utils::password::hash_password;
+use chat_backend::utils::soft_delete::SoftDelete;
+use chat_backend::{logging, migrate, seed};
+
+type CliResult<T = ()> = Result<T, Box<dyn Error>>;
+
+/// Operator tool for a BlockChat database. It reads the same environment (and `.env`)
+/// as the server and works directly against the database.
+#[derive(Parser)]
+#[command(name = "blockchat-admin", version)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Create an administrator account. The password is read from stdin.
+    CreateAdmin {
+        #[arg(long)]
+        username: String,
+        #[arg(long)]
+        first_name: String,
+        #[arg(long)]
+        last_name: String,
+        #[arg(long)]
+        email: Option<String>,
+    },
+    /// Set a new password, read from stdin, and sign the user out everywhere.
+    ResetPassword { username: String },
+    /// Give a user another role, by name (e.g. `admin`, `user`).
+    SetRole { username: String, role: String },
+    /// List accounts.
+    ListUsers {
+        /// Only users with this role.
+        #[arg(long)]
+        role: Option<String>,
+        /// Include soft-deleted accounts.
+        #[arg(long)]
+        include_deleted: bool,
+    },
+    /// Ban an account, indefinitely unless `--until` is given.
+    Ban {
+        username: String,
+        #[arg(long)]
+        reason: String,
+        /// RFC 3339 timestamp, e.g. 2026-01-31T00:00:00Z
+        #[arg(long)]
+        until: Option<DateTime<Utc>>,
+    },
+    /// Lift a suspension or ban.
+    Unban { username: String },
+    /// Apply pending migrations.
+    Migrate {
+        /// Only report whether migrations are pending.
+        #[arg(long)]
+        check: bool,
+    },
+    /// Apply a seed fixture. Defaults to `SEED_FILE` or `SEED_PROFILE`, like the server.
+    Seed {
+        /// Built-in profile: production-minimal, demo or e2e-test.
+        #[arg(long, conflicts_with = "file")]
+        profile: Option<String>,
+        /// TOML or JSON fixture file.
+        #[arg(long)]
+        file: Option<PathBuf>,
+    },
+    /// Look for chat data that is inconsistent; exits non-zero if any is found.
+    VerifyIntegrity,
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    dotenvy::dotenv().ok();
+    logging::init();
+    let cli = Cli::parse();
+
+    match run(cli.command).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run(command: Command) -> CliResult {
+    let database_url = std::env::var("DATABASE_URL").map_err(|_| "DATABASE_URL must be set")?;
+    let config = Config::from_env();
+    let db = Database::connect(&database_url).await?;
+
+    match command {
+        Command::CreateAdmin { username, first_name, last_name, email } => {
+            create_admin(&db, username, first_name, last_name, email).await
+        }
+        Command::ResetPassword { username } => reset_password(&db, &username).await,
+        Command::SetRole { username, role } => set_role(&db, &username, &role).await,
+        Command::ListUsers { role, include_deleted } => {
+            list_users(&db, role.as_deref(), include_deleted).await
+        }
+        Command::Ban { username, reason, until } => ban(&db, &username, reason, until).await,
+        Command::Unban { username } => unban(&db, &username).await,
+        Command::Migrate { check } => {
+            let mode = if check { MigrationMode::Check } else { MigrationMode::Auto };
+            migrate::run(&db, mode).await?;
+            Ok(())
+        }
+        Command::Seed { profile, file } => {
+            let fixture = match (profile, file) {
+                (Some(profile), _) => seed::Fixture::profile(&profile)?,
+                (None, Some(file)) => seed::Fixture::from_file(&file)?,
+                (None, None) => seed::from_config(&config)?,
+            };
+            seed::apply(&db, &fixture).await?;
+            Ok(())
+        }
+        Command::VerifyIntegrity => verify_integrity(&db, &config).await,
+    }
+}
+
+// Reads one line from stdin. Piping it in keeps the password out of shell history and
+// the process list; on a terminal it is prompted for, but not hidden.
+fn read_password() -> CliResult<String> {
+    let stdin = std::io::stdin();
+    if stdin.is_terminal() {
+        eprint!("New password: ");
+        std::io::stderr().flush()?;
+    }
+    let mut line = String::new();
+    stdin.lock().read_line(&mut line)?;
+    let password = line.trim_end_matches(['\r', '\n']).to_string();
+    if password.is_empty() {
+        return Err("Password must not be empty".into());
+    }
+    Ok(password)
+}
+
+async fn role_id(db: &DatabaseConnection, name: &str) -> CliResult<i32> {
+    Roles::find()
+        .filter(roles::Column::Name.eq(name))
+        .one(db)
+        .await?
+        .map(|role| role.id)
+        .ok_or_else(|| format!("No role named {:?}; run `blockchat-admin seed` first", name).into())
+}
+
+async fn live_user(db: &DatabaseConnection, username: &str) -> CliResult<users::Model> {
+    Users::find_live()
+        .filter(users::Column::Username.eq(username))
+        .one(db)
+        .await?
+        .ok_or_else(|| format!("No user named {:?}", username).into())
+}
+
+async fn create_admin(
+    db: &DatabaseConnection,
+    username: String,
+    first_name: String,
+    last_name: String,
+    email: Option<String>,
+) -> CliResult {
+    let admin_role = role_id(db, "admin").await?;
+    if Users::find()
+        .filter(users::Column::Username.eq(username.as_str()))
+        .one(db)
+        .await?
+        .is_some()
+    {
+        return Err(format!("Username {:?} is already taken", username).into());
+    }
+    let email = normalize_email(email);
+    if let Some(email) = &email {
+        if Users::find()
+            .filter(users::Column::Email.eq(email.as_str()))
+            .one(db)
+            .await?
+            .is_some()
+        {
+            return Err(format!("Email {:?} is already in use", email).into());
+        }
+    }
+    let password = hash_password(&read_password()?)
+        .map_err(|e| format!("Failed to hash password: {}", e))?;
+
+    let txn = db.begin().await?;
+    let user = users::ActiveModel {
+        first_name: Set(first_name),
+        last_name: Set(last_name),
+        username: Set(username),
+        password: Set(password),
+        // The operator vouches for the address.
+        email_verified_at: Set(email.as_ref().map(|_| Utc::now().into())),
+        email: Set(email),
+        role_id: Set(Some(admin_role)),
+        status: Set(AccountStatus::Active),
+        ..Default::default()
+    }
+    .insert(&txn)
+    .await?;
+    audit::record(&txn, &AuditContext::operator(), AuditEvent {
+        action: "user.create",
+        target_type: "user",
+        target_id: Some(user.id),
+        changes: audit::diff(&Value::Null, &audit::user_fields(&user)),
+    })
+    .await?;
+    txn.commit().await?;
+
+    println!("Created admin {} (id {})", user.username, user.id);
+    Ok(())
+}
+
+async fn reset_password(db: &DatabaseConnection, username: &str) -> CliResult {
+    let user = live_user(db, username).await?;
+    let password = hash_password(&read_password()?)
+        .map_err(|e| format!("Failed to hash password: {}", e))?;
+
+    let txn = db.begin().await?;
+    let mut user_model: users::ActiveModel = user.clone().into();
+    user_model.password = Set(password);
+    user_model.update(&txn).await?;
+    let revoked = UserSessions::update_many()
+        .col_expr(user_sessions::Column::RevokedAt, Expr::value(Utc::now()))
+        .filter(user_sessions::Column::UserId.eq(user.id))
+        .filter(user_sessions::Column::RevokedAt.is_null())
+        .exec(&txn)
+        .await?;
+    audit::record(&txn, &AuditContext::operator(), AuditEvent {
+        action: "user.reset_password",
+        target_type: "user",
+        target_id: Some(user.id),
+        changes: None,
+    })
+    .await?;
+    txn.commit().await?;
+
+    println!(
+        "Password reset for {}; {} session(s) revoked",
+        user.username, revoked.rows_affected
+    );
+    Ok(())
+}
+
+async fn set_role(db: &DatabaseConnection, username: &str, role: &str) -> CliResult {
+    let role_id = role_id(db, role).await?;
+    let user = live_user(db, username).await?;
+    if user.role_id == Some(role_id) {
+        println!("{} already has role {}", user.username, role);
+        return Ok(());
+    }
+    let mut user_model: users::ActiveModel = user.clone().into();
+    user_model.role_id = Set(Some(role_id));
+    audit::update_user(db, &AuditContext::operator(), "user.update", &user, user_model).await?;
+
+    println!("{} now has role {}", user.username, role);
+    Ok(())
+}
+
+async fn list_users(db: &DatabaseConnection, role: Option<&str>, include_deleted: bool) -> CliResult {
+    let role_names: std::collections::HashMap<i32, String> = Roles::find()
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|role| (role.id, role.name))
+        .collect();
+
+    let mut query = if include_deleted { Users::find() } else { Users::find_live() };
+    if let Some(role) = role {
+        query = query.filter(users::Column::RoleId.eq(role_id(db, role).await?));
+    }
+    let users = query.order_by_asc(users::Column::Id).all(db).await?;
+
+    println!(
+        "{:>6}  {:<24} {:<32} {:<10} {:<10} DELETED",
+        "ID", "USERNAME", "EMAIL", "ROLE", "STATUS"
+    );
+    for user in &users {
+        let role = user
+            .role_id
+            .and_then(|id| role_names.get(&id))
+            .map(String::as_str)
+            .unwrap_or("-");
+        let status = serde_json::to_value(user.status)?;
+        println!(
+            "{:>6}  {:<24} {:<32} {:<10} {:<10} {}",
+            user.id,
+            user.username,
+            user.email.as_deref().unwrap_or("-"),
+            role,
+            status.as_str().unwrap_or("-"),
+            user.deleted_at.map(|dt| dt.to_rfc3339()).unwrap_or_else(|| "-".to_string()),
+        );
+    }
+    println!("{} user(s)", users.len());
+    Ok(())
+}
+
+async fn ban(
+    db: &DatabaseConnection,
+    username: &str,
+    reason: String,
+    until: Option<DateTime<Utc>>,
+) -> CliResult {
+    if reason.trim().is_empty() {
+        return Err("A reason is required".into());
+    }
+    if until.is_some_and(|until| until <= Utc::now()) {
+        return Err("--until must be in the future".into());
+    }
+    let user = live_user(db, username).await?;
+    let mut user_model: users::ActiveModel = user.clone().into();
+    moderation::restrict(&user, &mut user_model, AccountStatus::Banned, &reason, None, until);
+    audit::update_user(db, &AuditContext::operator(), "user.ban", &user, user_model).await?;
+
+    match until {
+        Some(until) => println!("Banned {} until {}", user.username, until.to_rfc3339()),
+        None => println!("Banned {}", user.username),
+    }
+    Ok(())
+}
+
+async fn unban(db: &DatabaseConnection, username: &str) -> CliResult {
+    let user = live_user(db, username).await?;
+    if !matches!(user.status, AccountStatus::Suspended | AccountStatus::Banned) {
+        println!("{} is not suspended or banned", user.username);
+        return Ok(());
+    }
+    let mut user_model: users::ActiveModel = user.clone().into();
+    moderation::lift(&user, &mut user_model);
+    audit::update_user(db, &AuditContext::operator(), "user.reinstate", &user, user_model).await?;
+
+    println!("Reinstated {}", user.username);
+    Ok(())
+}
+
+// Each query counts rows that the application should never produce.
+const INTEGRITY_CHECKS: &[(&str, &str)] = &[
+    (
+        "messages whose author is not a participant of the chat",
+        "SELECT COUNT(*) FROM messages m WHERE m.user_id IS NOT NULL AND NOT EXISTS \
+         (SELECT 1 FROM chat_participants p WHERE p.chat_id = m.chat_id AND p.user_id = m.user_id)",
+    ),
+    (
+        "messages with neither an author nor an author name",
+        "SELECT COUNT(*) FROM messages WHERE user_id IS NULL AND author_name IS NULL",
+    ),
+    (
+        "live messages in deleted chats",
+        "SELECT COUNT(*) FROM messages m JOIN chats c ON c.id = m.chat_id \
+         WHERE m.deleted_at IS NULL AND c.deleted_at IS NOT NULL",
+    ),
+    (
+        "live chats without participants",
+        "SELECT COUNT(*) FROM chats c WHERE c.deleted_at IS NULL AND NOT EXISTS \
+         (SELECT 1 FROM chat_participants p WHERE p.chat_id = c.id)",
+    ),
+    (
+        "duplicate chat memberships",
+        "SELECT COUNT(*) FROM (SELECT chat_id, user_id FROM chat_participants \
+         GROUP BY chat_id, user_id HAVING COUNT(*) > 1) d",
+    ),
+    (
+        "deleted rows the purge job has missed for over a day",
+        "SELECT (SELECT COUNT(*) FROM users WHERE deleted_at < $1) \
+         + (SELECT COUNT(*) FROM chats WHERE deleted_at < $1) \
+         + (SELECT COUNT(*) FROM messages WHERE deleted_at < $1)",
+    ),
+];
+
+async fn verify_integrity(db: &DatabaseConnection, config: &Config) -> CliResult {
+    // The purge job runs hourly, so only rows well past the grace period count.
+    let purge_cutoff = Utc::now() - chrono::Duration::days(config.purge_grace_days + 1);
+    let mut problems = 0;
+    for (description, sql) in INTEGRITY_CHECKS {
+        let statement = if sql.contains("$1") {
+            Statement::from_sql_and_values(db.get_database_backend(), *sql, [purge_cutoff.into()])
+        } else {
+            Statement::from_string(db.get_database_backend(), *sql)
+        };
+        let count: i64 = match db.query_one(statement).await? {
+            Some(row) => row.try_get_by_index(0)?,
+            None => 0,
+        };
+        let marker = if count == 0 { "ok  " } else { "FAIL" };
+        println!("[{}] {}: {}", marker, description, count);
+        if count > 0 {
+            problems += 1;
+        }
+    }
+    if problems > 0 {
+        return Err(format!("{} integrity check(s) failed", problems).into());
+    }
+    println!("All integrity checks passed");
+    Ok(())
+}
@@ -0,0 +1,166 @@
+use std::env;
+use chrono::{DateTime, TimeZone, Utc};
+
+// Runtime settings read once from the environment at startup.
+#[derive(Clone)]
+pub struct Config {
+    pub frontend_url: String,
+    // Where this API is reachable from outside, for links that point back at it.
+    pub public_url: String,
+    pub webauthn_rp_id: String,
+    pub password_reset_ttl_minutes: i64,
+    pub email_verification_ttl_hours: i64,
+    pub registration_mode: RegistrationMode,
+    pub migration_mode: MigrationMode,
+    // Built-in seed profile applied at startup, unless `seed_file` names a fixture file.
+    pub seed_profile: String,
+    pub seed_file: Option<String>,
+    // How long deleted users, chats and messages can still be restored before they are purged.
+    pub purge_grace_days: i64,
+    // Directory data export archives are written to.
+    pub export_dir: String,
+    pub export_link_ttl_hours: i64,
+    // Admin listener serving `/metrics`; keep it off the public interface.
+    pub metrics_addr: String,
+    // Listener of the gRPC API, next to the HTTP server.
+    pub grpc_addr: String,
+    // How long a shutdown waits for in-flight requests, and then for background jobs.
+    pub shutdown_timeout_secs: u64,
+    // When the unversioned aliases of the `/api/v1` routes were deprecated and when they
+    // are removed; sent in their `Deprecation` and `Sunset` headers.
+    pub legacy_routes_deprecated: DateTime<Utc>,
+    pub legacy_routes_sunset: DateTime<Utc>,
+    pub mail: MailConfig,
+    pub tracing: TracingConfig,
+}
+
+// Who may use `/users/register`, selected by `REGISTRATION_MODE`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RegistrationMode {
+    Open,
+    // Accounts stay limited until the email address is confirmed.
+    EmailVerified,
+    InviteCode,
+    Closed,
+}
+
+impl std::str::FromStr for RegistrationMode {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_lowercase().replace('-', "_").as_str() {
+            "open" => Ok(Self::Open),
+            "email_verified" => Ok(Self::EmailVerified),
+            "invite_code" => Ok(Self::InviteCode),
+            "closed" => Ok(Self::Closed),
+            other => Err(format!("Unknown registration mode: {}", other)),
+        }
+    }
+}
+
+// What startup does about schema migrations, selected by `MIGRATION_MODE`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MigrationMode {
+    // Apply pending migrations before serving.
+    Auto,
+    // Refuse to start while migrations are pending, for rollouts that migrate separately.
+    Check,
+    Off,
+}
+
+impl std::str::FromStr for MigrationMode {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_lowercase().as_str() {
+            "auto" => Ok(Self::Auto),
+            "check" => Ok(Self::Check),
+            "off" => Ok(Self::Off),
+            other => Err(format!("Unknown migration mode: {}", other)),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct MailConfig {
+    // "smtp" or "file"
+    pub transport: String,
+    pub from: String,
+    pub smtp_host: String,
+    pub smtp_port: u16,
+    pub smtp_username: Option<String>,
+    pub smtp_password: Option<String>,
+    // Directory the file transport writes `.eml` files to; only logged when unset.
+    pub outbox_dir: Option<String>,
+}
+
+#[derive(Clone)]
+pub struct TracingConfig {
+    // "none", "otlp" or "file"
+    pub exporter: String,
+    // Where the file exporter appends spans, one JSON object per line.
+    pub file: String,
+    // `EnvFilter` directives for which spans are exported; SeaORM only emits its
+    // per-statement spans at `trace`.
+    pub filter: String,
+}
+
+fn var_or(key: &str, default: &str) -> String {
+    env::var(key).unwrap_or_else(|_| default.to_string())
+}
+
+fn parsed_or<T: std::str::FromStr>(key: &str, default: T) -> T {
+    env::var(key)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(default)
+}
+
+impl Config {
+    pub fn from_env() -> Self {
+        Self {
+            frontend_url: var_or("FRONTEND_URL", "http://localhost:3000"),
+            public_url: var_or("PUBLIC_URL", "http://127.0.0.1:8080"),
+            webauthn_rp_id: var_or("WEBAUTHN_RP_ID", "localhost"),
+            password_reset_ttl_minutes: parsed_or("PASSWORD_RESET_TTL_MINUTES", 30),
+            email_verification_ttl_hours: parsed_or("EMAIL_VERIFICATION_TTL_HOURS", 24),
+            // A typo here must not silently open registration, so unknown modes are fatal.
+            registration_mode: env::var("REGISTRATION_MODE")
+                .map(|mode| mode.parse().unwrap_or_else(|e: String| panic!("{}", e)))
+                .unwrap_or(RegistrationMode::Open),
+            migration_mode: env::var("MIGRATION_MODE")
+                .map(|mode| mode.parse().unwrap_or_else(|e: String| panic!("{}", e)))
+                .unwrap_or(MigrationMode::Auto),
+            seed_profile: var_or("SEED_PROFILE", "production-minimal"),
+            seed_file: env::var("SEED_FILE").ok(),
+            purge_grace_days: parsed_or("PURGE_GRACE_DAYS", 30),
+            export_dir: var_or("EXPORT_DIR", "./exports"),
+            export_link_ttl_hours: parsed_or("EXPORT_LINK_TTL_HOURS", 24),
+            metrics_addr: var_or("METRICS_ADDR", "127.0.0.1:9091"),
+            grpc_addr: var_or("GRPC_ADDR", "127.0.0.1:50051"),
+            shutdown_timeout_secs: parsed_or("SHUTDOWN_TIMEOUT_SECS", 30),
+            legacy_routes_deprecated: parsed_or(
+                "LEGACY_ROUTES_DEPRECATED",
+                Utc.with_ymd_and_hms(2026, 10, 19, 0, 0, 0).unwrap(),
+            ),
+            legacy_routes_sunset: parsed_or(
+                "LEGACY_ROUTES_SUNSET",
+                Utc.with_ymd_and_hms(2027, 4, 30, 0, 0, 0).unwrap(),
+            ),
+            mail: MailConfig {
+                transport: var_or("MAIL_TRANSPORT", "file"),
+                from: var_or("MAIL_FROM", "BlockChat <no-reply@localhost>"),
+                smtp_host: var_or("SMTP_HOST", "localhost"),
+                smtp_port: parsed_or("SMTP_PORT", 587),
+                smtp_username: env::var("SMTP_USERNAME").ok(),
+                smtp_password: env::var("SMTP_PASSWORD").ok(),
+                outbox_dir: env::var("MAIL_OUTBOX_DIR").ok(),
+            },
+            tracing: TracingConfig {
+                exporter: var_or("OTEL_TRACES_EXPORTER", "none"),
+                file: var_or("OTEL_TRACES_FILE", "./traces.jsonl"),
+                filter: var_or("OTEL_TRACES_FILTER", "chat_backend=info,sea_orm=trace"),
+            },
+        }
+    }
+}
@@ -7,9 +7,10 @@ pub struct Model {
     pub id: i32,
     #[sea_orm(unique)]
     pub name: String,
-    pub author_id: i32, 
+    pub author_id: Option<i32>,
     pub image: Option<Vec<u8>>,
     pub created_at: DateTimeWithTimeZone,
+    pub deleted_at: Option<DateTimeWithTimeZone>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -19,7 +20,7 @@ pub enum Relation {
         from = "Column::AuthorId",
         to = "super::users::Column::Id",
         on_update = "NoAction",
-        on_delete = "Cascade"
+        on_delete = "SetNull"
     )]
     Author,
     #[sea_orm(has_many = "super::messages::Entity")]
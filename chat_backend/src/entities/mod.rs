@@ -2,8 +2,16 @@
 
 pub mod prelude;
 
+pub mod audit_events;
 pub mod chats;
 pub mod chat_participants;
+pub mod data_exports;
+pub mod invite_codes;
 pub mod messages;
+pub mod password_reset_tokens;
 pub mod roles;
+pub mod sea_orm_active_enums;
+pub mod user_sessions;
 pub mod users;
+pub mod webauthn_challenges;
+pub mod webauthn_credentials;
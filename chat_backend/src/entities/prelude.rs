@@ -1,7 +1,14 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.2
 
+pub use super::audit_events::Entity as AuditEvents;
 pub use super::chats::Entity as Chats;
 pub use super::chat_participants::Entity as ChatParticipants;
+pub use super::data_exports::Entity as DataExports;
+pub use super::invite_codes::Entity as InviteCodes;
 pub use super::messages::Entity as Messages;
+pub use super::password_reset_tokens::Entity as PasswordResetTokens;
 pub use super::roles::Entity as Roles;
+pub use super::user_sessions::Entity as UserSessions;
 pub use super::users::Entity as Users;
+pub use super::webauthn_challenges::Entity as WebauthnChallenges;
+pub use super::webauthn_credentials::Entity as WebauthnCredentials;
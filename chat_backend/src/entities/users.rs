@@ -1,6 +1,7 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.2
 
 use sea_orm::entity::prelude::*;
+use super::sea_orm_active_enums::AccountStatus;
 
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
 #[sea_orm(table_name = "users")]
@@ -12,16 +13,30 @@ pub struct Model {
     #[sea_orm(unique)]
     pub username: String,
     pub password: String,
+    #[sea_orm(unique)]
+    pub email: Option<String>,
     #[sea_orm(column_type = "VarBinary(StringLen::None)", nullable)]
     pub avatar: Option<Vec<u8>>,
     pub role_id: Option<i32>,
     pub created_at: Option<DateTimeWithTimeZone>,
+    pub status: AccountStatus,
+    pub email_verified_at: Option<DateTimeWithTimeZone>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub status_reason: Option<String>,
+    pub status_set_by: Option<i32>,
+    pub status_until: Option<DateTimeWithTimeZone>,
+    pub deleted_at: Option<DateTimeWithTimeZone>,
+    pub previous_status: Option<AccountStatus>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(has_many = "super::data_exports::Entity")]
+    DataExports,
     #[sea_orm(has_many = "super::messages::Entity")]
     Messages,
+    #[sea_orm(has_many = "super::password_reset_tokens::Entity")]
+    PasswordResetTokens,
     #[sea_orm(
         belongs_to = "super::roles::Entity",
         from = "Column::RoleId",
@@ -30,6 +45,18 @@ pub enum Relation {
         on_delete = "SetNull"
     )]
     Roles,
+    #[sea_orm(has_many = "super::user_sessions::Entity")]
+    UserSessions,
+    #[sea_orm(has_many = "super::webauthn_challenges::Entity")]
+    WebauthnChallenges,
+    #[sea_orm(has_many = "super::webauthn_credentials::Entity")]
+    WebauthnCredentials,
+}
+
+impl Related<super::data_exports::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::DataExports.def()
+    }
 }
 
 impl Related<super::messages::Entity> for Entity {
@@ -38,10 +65,34 @@ impl Related<super::messages::Entity> for Entity {
     }
 }
 
+impl Related<super::password_reset_tokens::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::PasswordResetTokens.def()
+    }
+}
+
 impl Related<super::roles::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Roles.def()
     }
 }
 
+impl Related<super::user_sessions::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::UserSessions.def()
+    }
+}
+
+impl Related<super::webauthn_challenges::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::WebauthnChallenges.def()
+    }
+}
+
+impl Related<super::webauthn_credentials::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::WebauthnCredentials.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
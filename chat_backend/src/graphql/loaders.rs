@@ -0,0 +1,105 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use async_graphql::dataloader::{DataLoader, Loader};
+use sea_orm::{ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter};
+use crate::entities::prelude::{ChatParticipants, Chats, Users};
+use crate::entities::{chat_participants, chats, messages, users};
+use crate::utils::messages::recent_messages;
+use crate::utils::soft_delete::SoftDelete;
+
+pub fn loader<T: Send + Sync + 'static>(loader: T) -> DataLoader<T> {
+    DataLoader::new(loader, tokio::spawn)
+}
+
+/// Live users by id.
+pub struct UserLoader {
+    pub db: DatabaseConnection,
+}
+
+impl Loader<i32> for UserLoader {
+    type Value = users::Model;
+    type Error = Arc<DbErr>;
+
+    async fn load(&self, keys: &[i32]) -> Result<HashMap<i32, Self::Value>, Self::Error> {
+        let users = Users::find_live()
+            .filter(users::Column::Id.is_in(keys.iter().copied()))
+            .all(&self.db)
+            .await?;
+        Ok(users.into_iter().map(|user| (user.id, user)).collect())
+    }
+}
+
+/// The live chats each user takes part in, by user id.
+pub struct ChatsOfUserLoader {
+    pub db: DatabaseConnection,
+}
+
+impl Loader<i32> for ChatsOfUserLoader {
+    type Value = Vec<chats::Model>;
+    type Error = Arc<DbErr>;
+
+    async fn load(&self, keys: &[i32]) -> Result<HashMap<i32, Self::Value>, Self::Error> {
+        let rows = ChatParticipants::find()
+            .filter(chat_participants::Column::UserId.is_in(keys.iter().copied()))
+            .find_also_related(Chats)
+            .all(&self.db)
+            .await?;
+        let mut chats: HashMap<i32, Vec<chats::Model>> = keys.iter().map(|key| (*key, Vec::new())).collect();
+        for (participant, chat) in rows {
+            if let Some(chat) = chat.filter(|chat| chat.deleted_at.is_none()) {
+                chats.entry(participant.user_id).or_default().push(chat);
+            }
+        }
+        for list in chats.values_mut() {
+            list.sort_by_key(|chat| chat.id);
+        }
+        Ok(chats)
+    }
+}
+
+/// The latest live messages of each chat, keyed by `(chat id, how many)`, oldest first.
+/// One statement per distinct count.
+pub struct RecentMessagesLoader {
+    pub db: DatabaseConnection,
+}
+
+impl Loader<(i32, u64)> for RecentMessagesLoader {
+    type Value = Vec<messages::Model>;
+    type Error = Arc<DbErr>;
+
+    async fn load(&self, keys: &[(i32, u64)]) -> Result<HashMap<(i32, u64), Self::Value>, Self::Error> {
+        let mut by_count: HashMap<u64, Vec<i32>> = HashMap::new();
+        for (chat_id, count) in keys {
+            by_count.entry(*count).or_default().push(*chat_id);
+        }
+
+        let mut recent = HashMap::new();
+        for (count, chat_ids) in by_count {
+            for (chat_id, messages) in recent_messages(&self.db, &chat_ids, count).await? {
+                recent.insert((chat_id, count), messages);
+            }
+        }
+        Ok(recent)
+    }
+}
+
+/// The chats, by id, that one account takes part in. A chat it is not in is missing
+/// from the result.
+pub struct MembershipLoader {
+    pub db: DatabaseConnection,
+    pub user_id: i32,
+}
+
+impl Loader<i32> for MembershipLoader {
+    type Value = ();
+    type Error = Arc<DbErr>;
+
+    async fn load(&self, keys: &[i32]) -> Result<HashMap<i32, Self::Value>, Self::Error> {
+        let rows = ChatParticipants::find()
+            .filter(chat_participants::Column::UserId.eq(self.user_id))
+            .filter(chat_participants::Column::ChatId.is_in(keys.iter().copied()))
+            .all(&self.db)
+            .await?;
+        Ok(rows.into_iter().map(|row| (row.chat_id, ())).collect())
+    }
+}
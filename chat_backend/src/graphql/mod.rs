@@ -0,0 +1,94 @@
+//! The GraphQL API at `/api/v1/graphql`, resolved from the SeaORM entities.
+//!
+//! Related rows (a user's chats, a chat's author and recent messages, ...) are fetched
+//! through the dataloaders in [`loaders`], so a nested query costs one statement per
+//! level rather than one per row.
+
+mod loaders;
+mod mutation;
+mod query;
+mod subscription;
+mod types;
+
+use async_graphql::dataloader::DataLoader;
+use async_graphql::{Context, Data, Guard, Schema};
+use sea_orm::DatabaseConnection;
+use crate::models::token_model::Claims;
+use crate::utils::messages::{self, MessageHub};
+
+pub use mutation::MutationRoot;
+pub use query::QueryRoot;
+pub use subscription::SubscriptionRoot;
+
+pub type BlockChatSchema = Schema<QueryRoot, MutationRoot, SubscriptionRoot>;
+
+pub fn build_schema(hub: MessageHub) -> BlockChatSchema {
+    Schema::build(QueryRoot, MutationRoot, SubscriptionRoot)
+        .data(hub)
+        // Deep enough for user > chats > messages > author > chats and then some.
+        .limit_depth(10)
+        .finish()
+}
+
+/// The account a GraphQL operation runs as. It has passed the same session and
+/// restriction checks as a REST request.
+pub struct Viewer {
+    pub id: i32,
+    pub username: String,
+    pub role: String,
+}
+
+impl Viewer {
+    pub fn new(claims: Claims, user_id: i32) -> Self {
+        Self { id: user_id, username: claims.sub, role: claims.role }
+    }
+
+    pub fn is_admin(&self) -> bool {
+        self.role == "admin"
+    }
+}
+
+/// Adds what every operation needs to its context: the viewer, the database and a fresh
+/// set of dataloaders. Done per request, or once per WebSocket connection.
+pub fn add_operation_data(data: &mut Data, db: DatabaseConnection, viewer: Viewer) {
+    data.insert(loaders::loader(loaders::UserLoader { db: db.clone() }));
+    data.insert(loaders::loader(loaders::ChatsOfUserLoader { db: db.clone() }));
+    data.insert(loaders::loader(loaders::RecentMessagesLoader { db: db.clone() }));
+    data.insert(loaders::loader(loaders::MembershipLoader { db: db.clone(), user_id: viewer.id }));
+    data.insert(viewer);
+    data.insert(db);
+}
+
+/// Field guard with the semantics of `RoleGuard`: the viewer's role must be listed.
+pub struct RequireRole(pub &'static [&'static str]);
+
+impl Guard for RequireRole {
+    async fn check(&self, ctx: &Context<'_>) -> async_graphql::Result<()> {
+        if self.0.contains(&viewer(ctx).role.as_str()) {
+            Ok(())
+        } else {
+            Err("Unauthorized".into())
+        }
+    }
+}
+
+fn viewer<'a>(ctx: &Context<'a>) -> &'a Viewer {
+    ctx.data_unchecked::<Viewer>()
+}
+
+fn db<'a>(ctx: &Context<'a>) -> &'a DatabaseConnection {
+    ctx.data_unchecked::<DatabaseConnection>()
+}
+
+fn loader<'a, T: Send + Sync + 'static>(ctx: &Context<'a>) -> &'a DataLoader<T> {
+    ctx.data_unchecked::<DataLoader<T>>()
+}
+
+/// [`messages::can_read_chat`], with membership batched through the dataloader.
+async fn can_read_chat(ctx: &Context<'_>, chat_id: i32) -> async_graphql::Result<bool> {
+    messages::can_read_chat(viewer(ctx).is_admin(), is_member(ctx, chat_id)).await
+}
+
+async fn is_member(ctx: &Context<'_>, chat_id: i32) -> async_graphql::Result<bool> {
+    Ok(loader::<loaders::MembershipLoader>(ctx).load_one(chat_id).await?.is_some())
+}
@@ -0,0 +1,16 @@
+use async_graphql::{Context, Object, Result};
+use crate::utils::messages::{post_message, MessageHub};
+use super::types::Message;
+use super::{db, viewer};
+
+pub struct MutationRoot;
+
+#[Object]
+impl MutationRoot {
+    /// Posts a message to a chat the signed-in user takes part in and delivers it to the
+    /// chat's `messageAdded` subscribers.
+    async fn send_message(&self, ctx: &Context<'_>, chat_id: i32, content: String) -> Result<Message> {
+        let hub = ctx.data_unchecked::<MessageHub>();
+        Ok(Message(post_message(db(ctx), hub, viewer(ctx).id, chat_id, &content).await?))
+    }
+}
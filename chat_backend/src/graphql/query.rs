@@ -0,0 +1,40 @@
+use async_graphql::{Context, Object, Result};
+use sea_orm::{ColumnTrait, QueryFilter, QueryOrder};
+use crate::entities::prelude::{Chats, Users};
+use crate::entities::users;
+use crate::utils::soft_delete::SoftDelete;
+use super::loaders::UserLoader;
+use super::types::{Chat, User};
+use super::{can_read_chat, db, loader, viewer, RequireRole};
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    /// The signed-in user.
+    async fn me(&self, ctx: &Context<'_>) -> Result<Option<User>> {
+        Ok(loader::<UserLoader>(ctx).load_one(viewer(ctx).id).await?.map(User))
+    }
+
+    async fn user(&self, ctx: &Context<'_>, id: i32) -> Result<Option<User>> {
+        Ok(loader::<UserLoader>(ctx).load_one(id).await?.map(User))
+    }
+
+    /// All live users, optionally only those whose username starts with `username`.
+    #[graphql(guard = "RequireRole(&[\"admin\"])")]
+    async fn users(&self, ctx: &Context<'_>, username: Option<String>) -> Result<Vec<User>> {
+        let mut query = Users::find_live().order_by_asc(users::Column::Id);
+        if let Some(prefix) = username.filter(|prefix| !prefix.trim().is_empty()) {
+            query = query.filter(users::Column::Username.starts_with(prefix.trim()));
+        }
+        Ok(query.all(db(ctx)).await?.into_iter().map(User).collect())
+    }
+
+    /// A chat the signed-in user takes part in; admins can look up any chat.
+    async fn chat(&self, ctx: &Context<'_>, id: i32) -> Result<Option<Chat>> {
+        if !can_read_chat(ctx, id).await? {
+            return Ok(None);
+        }
+        Ok(Chats::find_live_by_id(id).one(db(ctx)).await?.map(Chat))
+    }
+}
@@ -0,0 +1,31 @@
+use async_graphql::{Context, Result, Subscription};
+use futures::Stream;
+use tokio::sync::broadcast;
+use crate::utils::messages::MessageHub;
+use super::types::Message;
+use super::{can_read_chat, Viewer};
+
+pub struct SubscriptionRoot;
+
+#[Subscription]
+impl SubscriptionRoot {
+    /// Messages posted to a chat from now on. Only participants and admins may subscribe.
+    async fn message_added(&self, ctx: &Context<'_>, chat_id: i32) -> Result<impl Stream<Item = Message>> {
+        let hub = ctx.data_unchecked::<MessageHub>();
+        let (receiver, can_read) = hub.subscribe_checked(can_read_chat(ctx, chat_id)).await?;
+        if !can_read {
+            return Err("You are not a participant of this chat".into());
+        }
+        log::debug!("User {} subscribed to chat {}", ctx.data_unchecked::<Viewer>().id, chat_id);
+
+        Ok(futures::stream::unfold(receiver, move |mut receiver| async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(message) if message.chat_id == chat_id => return Some((Message(message), receiver)),
+                    Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => return None,
+                }
+            }
+        }))
+    }
+}
@@ -0,0 +1,120 @@
+use async_graphql::{Context, Object, Result};
+use chrono::{DateTime, Utc};
+use crate::entities::sea_orm_active_enums::AccountStatus;
+use crate::entities::{chats, messages, users};
+use crate::utils::messages::MAX_RECENT_MESSAGES;
+use super::loaders::{ChatsOfUserLoader, RecentMessagesLoader, UserLoader};
+use super::{can_read_chat, loader, viewer};
+
+pub struct User(pub users::Model);
+
+#[Object]
+impl User {
+    async fn id(&self) -> i32 {
+        self.0.id
+    }
+
+    async fn username(&self) -> &str {
+        &self.0.username
+    }
+
+    async fn first_name(&self) -> &str {
+        &self.0.first_name
+    }
+
+    async fn last_name(&self) -> &str {
+        &self.0.last_name
+    }
+
+    /// Only shown to the account itself and to admins.
+    async fn email(&self, ctx: &Context<'_>) -> Option<&str> {
+        let viewer = viewer(ctx);
+        (viewer.id == self.0.id || viewer.is_admin()).then_some(self.0.email.as_deref()).flatten()
+    }
+
+    async fn status(&self) -> AccountStatus {
+        self.0.status
+    }
+
+    async fn created_at(&self) -> Option<DateTime<Utc>> {
+        self.0.created_at.map(|at| at.with_timezone(&Utc))
+    }
+
+    /// The chats this user takes part in.
+    async fn chats(&self, ctx: &Context<'_>) -> Result<Vec<Chat>> {
+        let chats = loader::<ChatsOfUserLoader>(ctx).load_one(self.0.id).await?;
+        Ok(chats.unwrap_or_default().into_iter().map(Chat).collect())
+    }
+}
+
+pub struct Chat(pub chats::Model);
+
+#[Object]
+impl Chat {
+    async fn id(&self) -> i32 {
+        self.0.id
+    }
+
+    async fn name(&self) -> &str {
+        &self.0.name
+    }
+
+    async fn created_at(&self) -> DateTime<Utc> {
+        self.0.created_at.with_timezone(&Utc)
+    }
+
+    /// The user who created the chat; null once their account is gone.
+    async fn author(&self, ctx: &Context<'_>) -> Result<Option<User>> {
+        let Some(author_id) = self.0.author_id else {
+            return Ok(None);
+        };
+        Ok(loader::<UserLoader>(ctx).load_one(author_id).await?.map(User))
+    }
+
+    /// The latest `last` messages, oldest first. Only participants and admins may read
+    /// them.
+    async fn messages(&self, ctx: &Context<'_>, #[graphql(default = 20)] last: u64) -> Result<Vec<Message>> {
+        if !can_read_chat(ctx, self.0.id).await? {
+            return Err("You are not a participant of this chat".into());
+        }
+        let last = last.min(MAX_RECENT_MESSAGES);
+        let messages = loader::<RecentMessagesLoader>(ctx).load_one((self.0.id, last)).await?;
+        Ok(messages.unwrap_or_default().into_iter().map(Message).collect())
+    }
+}
+
+pub struct Message(pub messages::Model);
+
+#[Object]
+impl Message {
+    async fn id(&self) -> i32 {
+        self.0.id
+    }
+
+    async fn chat_id(&self) -> i32 {
+        self.0.chat_id
+    }
+
+    async fn content(&self) -> &str {
+        &self.0.content
+    }
+
+    async fn timestamp(&self) -> Option<DateTime<Utc>> {
+        self.0.timestamp.map(|at| at.with_timezone(&Utc))
+    }
+
+    /// The author's username, kept after their account is purged.
+    async fn author_name(&self, ctx: &Context<'_>) -> Result<Option<String>> {
+        if let Some(name) = &self.0.author_name {
+            return Ok(Some(name.clone()));
+        }
+        Ok(self.author(ctx).await?.map(|author| author.0.username))
+    }
+
+    async fn author(&self, ctx: &Context<'_>) -> Result<Option<User>> {
+        let Some(user_id) = self.0.user_id else {
+            return Ok(None);
+        };
+        Ok(loader::<UserLoader>(ctx).load_one(user_id).await?.map(User))
+    }
+}
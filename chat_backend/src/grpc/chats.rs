@@ -0,0 +1,151 @@
+use std::collections::HashSet;
+use std::pin::Pin;
+use futures::Stream;
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder};
+use tokio::sync::broadcast;
+use tokio_util::sync::CancellationToken;
+use tonic::{Request, Response, Status};
+use crate::entities::prelude::{ChatParticipants, Chats};
+use crate::entities::{chat_participants, chats};
+use crate::utils::messages::{
+    can_read_chat, is_participant, post_message, recent_messages, MessageHub, PostMessageError, MAX_RECENT_MESSAGES,
+};
+use crate::utils::soft_delete::SoftDelete;
+use super::pb::chat_event::Event;
+use super::pb::chats_server::Chats as ChatsApi;
+use super::pb::{
+    Chat, ChatEvent, GetChatRequest, ListChatsRequest, ListChatsResponse, ListMessagesRequest, ListMessagesResponse,
+    Message, SendMessageRequest, StreamChatEventsRequest,
+};
+use super::{authenticate, db_error, message_list, Caller};
+
+/// How many messages `ListMessages` returns when the request does not say.
+const DEFAULT_RECENT_MESSAGES: u32 = 20;
+
+pub struct ChatsService {
+    pub db: DatabaseConnection,
+    pub hub: MessageHub,
+    pub stopping: CancellationToken,
+}
+
+impl ChatsService {
+    async fn check_can_read(&self, caller: &Caller, chat_id: i32) -> Result<(), Status> {
+        let is_member = is_participant(&self.db, chat_id, caller.id);
+        if can_read_chat(caller.is_admin(), is_member).await.map_err(db_error)? {
+            Ok(())
+        } else {
+            Err(Status::permission_denied("You are not a participant of this chat"))
+        }
+    }
+}
+
+type ChatEventStream = Pin<Box<dyn Stream<Item = Result<ChatEvent, Status>> + Send>>;
+
+#[tonic::async_trait]
+impl ChatsApi for ChatsService {
+    async fn list_chats(&self, request: Request<ListChatsRequest>) -> Result<Response<ListChatsResponse>, Status> {
+        let caller = authenticate(&self.db, &request).await?;
+        let chats = ChatParticipants::find()
+            .filter(chat_participants::Column::UserId.eq(caller.id))
+            .find_also_related(Chats)
+            .filter(chats::Column::DeletedAt.is_null())
+            .order_by_asc(chats::Column::Id)
+            .all(&self.db)
+            .await
+            .map_err(db_error)?;
+        Ok(Response::new(ListChatsResponse {
+            chats: chats.into_iter().filter_map(|(_, chat)| chat).map(Chat::from).collect(),
+        }))
+    }
+
+    async fn get_chat(&self, request: Request<GetChatRequest>) -> Result<Response<Chat>, Status> {
+        let caller = authenticate(&self.db, &request).await?;
+        let id = request.into_inner().id;
+        self.check_can_read(&caller, id).await?;
+        match Chats::find_live_by_id(id).one(&self.db).await.map_err(db_error)? {
+            Some(chat) => Ok(Response::new(chat.into())),
+            None => Err(Status::not_found("Chat not found")),
+        }
+    }
+
+    async fn list_messages(&self, request: Request<ListMessagesRequest>) -> Result<Response<ListMessagesResponse>, Status> {
+        let caller = authenticate(&self.db, &request).await?;
+        let ListMessagesRequest { chat_id, last } = request.into_inner();
+        self.check_can_read(&caller, chat_id).await?;
+        let last = u64::from(last.unwrap_or(DEFAULT_RECENT_MESSAGES)).min(MAX_RECENT_MESSAGES);
+        let mut recent = recent_messages(&self.db, &[chat_id], last).await.map_err(db_error)?;
+        let messages = message_list(&self.db, recent.remove(&chat_id).unwrap_or_default())
+            .await
+            .map_err(db_error)?;
+        Ok(Response::new(ListMessagesResponse { messages }))
+    }
+
+    async fn send_message(&self, request: Request<SendMessageRequest>) -> Result<Response<Message>, Status> {
+        let caller = authenticate(&self.db, &request).await?;
+        let SendMessageRequest { chat_id, content } = request.into_inner();
+        let message = post_message(&self.db, &self.hub, caller.id, chat_id, &content)
+            .await
+            .map_err(|err| match err {
+                PostMessageError::Empty | PostMessageError::TooLong => Status::invalid_argument(err.to_string()),
+                PostMessageError::NotParticipant => Status::permission_denied(err.to_string()),
+                PostMessageError::Database(err) => db_error(err),
+            })?;
+        let mut messages = message_list(&self.db, vec![message]).await.map_err(db_error)?;
+        Ok(Response::new(messages.remove(0)))
+    }
+
+    type StreamChatEventsStream = ChatEventStream;
+
+    async fn stream_chat_events(
+        &self,
+        request: Request<StreamChatEventsRequest>,
+    ) -> Result<Response<Self::StreamChatEventsStream>, Status> {
+        let caller = authenticate(&self.db, &request).await?;
+        let requested = request.into_inner().chat_ids;
+        let (receiver, chat_ids) = self
+            .hub
+            .subscribe_checked(async {
+                if requested.is_empty() {
+                    let participants = ChatParticipants::find()
+                        .filter(chat_participants::Column::UserId.eq(caller.id))
+                        .all(&self.db)
+                        .await
+                        .map_err(db_error)?;
+                    return Ok(participants.into_iter().map(|participant| participant.chat_id).collect());
+                }
+                for chat_id in &requested {
+                    self.check_can_read(&caller, *chat_id).await?;
+                }
+                Ok::<HashSet<i32>, Status>(requested.iter().copied().collect())
+            })
+            .await?;
+
+        let db = self.db.clone();
+        let stopping = self.stopping.clone();
+        let events = futures::stream::unfold(receiver, move |mut receiver| {
+            let db = db.clone();
+            let stopping = stopping.clone();
+            let chat_ids = chat_ids.clone();
+            async move {
+                loop {
+                    let received = tokio::select! {
+                        received = receiver.recv() => received,
+                        _ = stopping.cancelled() => return None,
+                    };
+                    match received {
+                        Ok(message) if chat_ids.contains(&message.chat_id) => {
+                            let event = match message_list(&db, vec![message]).await {
+                                Ok(mut messages) => Ok(ChatEvent { event: Some(Event::MessageAdded(messages.remove(0))) }),
+                                Err(err) => Err(db_error(err)),
+                            };
+                            return Some((event, receiver));
+                        }
+                        Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                        Err(broadcast::error::RecvError::Closed) => return None,
+                    }
+                }
+            }
+        });
+        Ok(Response::new(Box::pin(events)))
+    }
+}
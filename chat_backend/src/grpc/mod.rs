@@ -0,0 +1,158 @@
+//! The gRPC API, served by tonic on its own listener next to the actix HTTP server.
+//!
+//! The services are generated from `proto/blockchat/v1/blockchat.proto` by `build.rs`.
+//! They read the same tables as the HTTP and GraphQL APIs, and messages posted through
+//! any of them reach the streams of the others through the shared [`MessageHub`].
+
+mod chats;
+mod users;
+
+use std::collections::HashMap;
+use actix_web::http::header::{HeaderMap, HeaderValue, AUTHORIZATION};
+use actix_web::http::StatusCode;
+use sea_orm::{ColumnTrait, DatabaseConnection, DbErr, QueryFilter};
+use tokio::net::TcpListener;
+use tokio_util::sync::CancellationToken;
+use tonic::transport::server::TcpIncoming;
+use tonic::transport::Server;
+use tonic::{Request, Status};
+use crate::entities::prelude::Users;
+use crate::entities::sea_orm_active_enums::AccountStatus;
+use crate::entities::{chats as chat, messages, users as user};
+use crate::utils::check_auth_user::AuthenticatedUser;
+use crate::utils::messages::{MessageHub, CHAT_API_ROLES};
+use crate::utils::soft_delete::SoftDelete;
+
+pub mod pb {
+    tonic::include_proto!("blockchat.v1");
+}
+
+/// Serves the gRPC API on `listener` until `stopping` is cancelled. Open event streams
+/// end then too, so the drain does not wait on them.
+pub async fn serve(
+    listener: TcpListener,
+    db: DatabaseConnection,
+    hub: MessageHub,
+    stopping: CancellationToken,
+) -> Result<(), tonic::transport::Error> {
+    let users = users::UsersService { db: db.clone() };
+    let chats = chats::ChatsService { db, hub, stopping: stopping.clone() };
+    Server::builder()
+        .add_service(pb::users_server::UsersServer::new(users))
+        .add_service(pb::chats_server::ChatsServer::new(chats))
+        .serve_with_incoming_shutdown(TcpIncoming::from(listener), stopping.cancelled_owned())
+        .await
+}
+
+/// The account behind a call.
+struct Caller {
+    id: i32,
+    role: String,
+}
+
+impl Caller {
+    fn is_admin(&self) -> bool {
+        self.role == "admin"
+    }
+}
+
+/// Checks the bearer token in the `authorization` metadata with the same session and
+/// restriction checks as the HTTP API.
+async fn authenticate<T>(db: &DatabaseConnection, request: &Request<T>) -> Result<Caller, Status> {
+    let mut headers = HeaderMap::new();
+    if let Some(value) = request.metadata().get("authorization") {
+        if let Ok(value) = HeaderValue::from_bytes(value.as_bytes()) {
+            headers.insert(AUTHORIZATION, value);
+        }
+    }
+    let (auth_user, user) = AuthenticatedUser::verify(&headers, db).await.map_err(|err| {
+        let message = err.to_string();
+        match err.as_response_error().status_code() {
+            StatusCode::UNAUTHORIZED => Status::unauthenticated(message),
+            StatusCode::FORBIDDEN => Status::permission_denied(message),
+            _ => Status::internal(message),
+        }
+    })?;
+    if !CHAT_API_ROLES.contains(&auth_user.0.role.as_str()) {
+        return Err(Status::permission_denied("Unauthorized"));
+    }
+    Ok(Caller { id: user.id, role: auth_user.0.role })
+}
+
+fn db_error(err: DbErr) -> Status {
+    log::error!("gRPC call failed: {:?}", err);
+    Status::internal("Database error")
+}
+
+fn timestamp(at: chrono::DateTime<chrono::FixedOffset>) -> prost_types::Timestamp {
+    prost_types::Timestamp { seconds: at.timestamp(), nanos: at.timestamp_subsec_nanos() as i32 }
+}
+
+impl From<AccountStatus> for pb::AccountStatus {
+    fn from(status: AccountStatus) -> Self {
+        match status {
+            AccountStatus::Active => pb::AccountStatus::Active,
+            AccountStatus::Unverified => pb::AccountStatus::Unverified,
+            AccountStatus::Suspended => pb::AccountStatus::Suspended,
+            AccountStatus::Banned => pb::AccountStatus::Banned,
+        }
+    }
+}
+
+fn user_message(user: user::Model, caller: &Caller) -> pb::User {
+    let show_email = caller.id == user.id || caller.is_admin();
+    pb::User {
+        id: user.id,
+        username: user.username,
+        first_name: user.first_name,
+        last_name: user.last_name,
+        email: user.email.filter(|_| show_email),
+        status: pb::AccountStatus::from(user.status).into(),
+        created_at: user.created_at.map(timestamp),
+    }
+}
+
+impl From<chat::Model> for pb::Chat {
+    fn from(chat: chat::Model) -> Self {
+        pb::Chat {
+            id: chat.id,
+            name: chat.name,
+            author_id: chat.author_id,
+            created_at: Some(timestamp(chat.created_at)),
+        }
+    }
+}
+
+/// Converts messages, filling `author_name` from the live authors with one query.
+async fn message_list(db: &DatabaseConnection, messages: Vec<messages::Model>) -> Result<Vec<pb::Message>, DbErr> {
+    let author_ids: Vec<i32> = messages
+        .iter()
+        .filter(|message| message.author_name.is_none())
+        .filter_map(|message| message.user_id)
+        .collect();
+    let authors: HashMap<i32, String> = if author_ids.is_empty() {
+        HashMap::new()
+    } else {
+        Users::find_live()
+            .filter(user::Column::Id.is_in(author_ids))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|user| (user.id, user.username))
+            .collect()
+    };
+
+    Ok(messages
+        .into_iter()
+        .map(|message| pb::Message {
+            id: message.id,
+            chat_id: message.chat_id,
+            user_id: message.user_id,
+            content: message.content,
+            timestamp: message.timestamp.map(timestamp),
+            author_name: message
+                .author_name
+                .or_else(|| message.user_id.and_then(|id| authors.get(&id).cloned())),
+        })
+        .collect())
+}
@@ -0,0 +1,47 @@
+use sea_orm::{ColumnTrait, DatabaseConnection, QueryFilter, QueryOrder};
+use tonic::{Request, Response, Status};
+use crate::entities::prelude::Users;
+use crate::entities::users;
+use crate::utils::soft_delete::SoftDelete;
+use super::pb::users_server::Users as UsersApi;
+use super::pb::{GetMeRequest, GetUserRequest, ListUsersRequest, ListUsersResponse, User};
+use super::{authenticate, db_error, user_message};
+
+pub struct UsersService {
+    pub db: DatabaseConnection,
+}
+
+#[tonic::async_trait]
+impl UsersApi for UsersService {
+    async fn get_me(&self, request: Request<GetMeRequest>) -> Result<Response<User>, Status> {
+        let caller = authenticate(&self.db, &request).await?;
+        match Users::find_live_by_id(caller.id).one(&self.db).await.map_err(db_error)? {
+            Some(user) => Ok(Response::new(user_message(user, &caller))),
+            None => Err(Status::not_found("User not found")),
+        }
+    }
+
+    async fn get_user(&self, request: Request<GetUserRequest>) -> Result<Response<User>, Status> {
+        let caller = authenticate(&self.db, &request).await?;
+        let id = request.into_inner().id;
+        match Users::find_live_by_id(id).one(&self.db).await.map_err(db_error)? {
+            Some(user) => Ok(Response::new(user_message(user, &caller))),
+            None => Err(Status::not_found("User not found")),
+        }
+    }
+
+    async fn list_users(&self, request: Request<ListUsersRequest>) -> Result<Response<ListUsersResponse>, Status> {
+        let caller = authenticate(&self.db, &request).await?;
+        if !caller.is_admin() {
+            return Err(Status::permission_denied("Unauthorized"));
+        }
+        let mut query = Users::find_live().order_by_asc(users::Column::Id);
+        if let Some(prefix) = request.into_inner().username_prefix.filter(|prefix| !prefix.trim().is_empty()) {
+            query = query.filter(users::Column::Username.starts_with(prefix.trim()));
+        }
+        let users = query.all(&self.db).await.map_err(db_error)?;
+        Ok(Response::new(ListUsersResponse {
+            users: users.into_iter().map(|user| user_message(user, &caller)).collect(),
+        }))
+    }
+}
@@ -0,0 +1,75 @@
+use actix_web::{web, HttpResponse};
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, PaginatorTrait, QueryFilter, QueryOrder};
+use crate::entities::audit_events;
+use crate::entities::prelude::AuditEvents;
+use crate::models::audit_models::*;
+use crate::models::user_models::ErrorResponse;
+
+const DEFAULT_PER_PAGE: u64 = 50;
+const MAX_PER_PAGE: u64 = 200;
+
+// Get Audit Events Handler
+#[utoipa::path(
+    get,
+    path = "/audit-events",
+    tag = "audit",
+    params(AuditEventFilter),
+    responses(
+        (status = 200, description = "A page of audit events", body = GetAuditEventsResponse),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 500, description = "Unexpected server error", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn get_audit_events(
+    db: web::Data<DatabaseConnection>,
+    filter: web::Query<AuditEventFilter>,
+) -> HttpResponse {
+    let mut query = AuditEvents::find();
+    if let Some(actor_id) = filter.actor_id {
+        query = query.filter(audit_events::Column::ActorId.eq(actor_id));
+    }
+    if let Some(action) = filter.action.as_ref().filter(|s| !s.trim().is_empty()) {
+        // "user." matches every user action
+        query = match action.strip_suffix('.') {
+            Some(prefix) => query.filter(audit_events::Column::Action.starts_with(format!("{}.", prefix))),
+            None => query.filter(audit_events::Column::Action.eq(action.as_str())),
+        };
+    }
+    if let Some(target_type) = filter.target_type.as_ref().filter(|s| !s.trim().is_empty()) {
+        query = query.filter(audit_events::Column::TargetType.eq(target_type.as_str()));
+    }
+    if let Some(target_id) = filter.target_id {
+        query = query.filter(audit_events::Column::TargetId.eq(target_id));
+    }
+    if let Some(request_id) = filter.request_id.as_ref().filter(|s| !s.trim().is_empty()) {
+        query = query.filter(audit_events::Column::RequestId.eq(request_id.as_str()));
+    }
+    if let Some(from) = filter.from {
+        query = query.filter(audit_events::Column::CreatedAt.gte(from));
+    }
+    if let Some(to) = filter.to {
+        query = query.filter(audit_events::Column::CreatedAt.lt(to));
+    }
+
+    let page = filter.page.unwrap_or(1).max(1);
+    let per_page = filter.per_page.unwrap_or(DEFAULT_PER_PAGE).clamp(1, MAX_PER_PAGE);
+    let paginator = query
+        .order_by_desc(audit_events::Column::Id)
+        .paginate(db.get_ref(), per_page);
+
+    let totals = match paginator.num_items_and_pages().await {
+        Ok(totals) => totals,
+        Err(err) => return HttpResponse::InternalServerError().json(format!("Error: {:?}", err)),
+    };
+    match paginator.fetch_page(page - 1).await {
+        Ok(events) => HttpResponse::Ok().json(GetAuditEventsResponse {
+            events: events.into_iter().map(AuditEventResponse::from).collect(),
+            page,
+            per_page,
+            total: totals.number_of_items,
+            total_pages: totals.number_of_pages,
+        }),
+        Err(err) => HttpResponse::InternalServerError().json(format!("Error: {:?}", err)),
+    }
+}
@@ -0,0 +1,229 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::Utc;
+use sea_orm::sea_query::Expr;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, Set,
+    TransactionTrait,
+};
+use crate::config::Config;
+use crate::entities::prelude::*;
+use crate::entities::{
+    chat_participants, chats, data_exports, invite_codes, messages, password_reset_tokens,
+    user_sessions, users, webauthn_credentials,
+};
+use crate::handlers::user_handler::role_name_from_id;
+use crate::jobs::data_exports::remove_archive;
+use crate::models::user_models::{EraseAccount, ErrorResponse, ResponseMessage};
+use crate::utils::audit::{self, AuditContext, AuditEvent};
+use crate::utils::check_auth_user::AuthenticatedUser;
+use crate::utils::password::{hash_password, verify_password};
+use crate::utils::soft_delete::SoftDelete;
+
+// Shown as the author of messages whose author erased their account.
+pub const TOMBSTONE_AUTHOR: &str = "deleted user";
+
+// Scrubs the account and detaches it from everything other users can still see, in one
+// transaction together with its audit record. Returns the export archives left to remove.
+async fn erase(db: &DatabaseConnection, context: &AuditContext, user: users::Model) -> Result<Vec<String>, DbErr> {
+    let user_id = user.id;
+    let txn = db.begin().await?;
+
+    // 1. Messages stay readable for the rest of the chat, attributed to the tombstone.
+    Messages::update_many()
+        .col_expr(messages::Column::UserId, Expr::value(Option::<i32>::None))
+        .col_expr(messages::Column::AuthorName, Expr::value(TOMBSTONE_AUTHOR))
+        .filter(messages::Column::UserId.eq(user_id))
+        .exec(&txn)
+        .await?;
+
+    // 2. Chats the user created keep existing without an author.
+    Chats::update_many()
+        .col_expr(chats::Column::AuthorId, Expr::value(Option::<i32>::None))
+        .filter(chats::Column::AuthorId.eq(user_id))
+        .exec(&txn)
+        .await?;
+    InviteCodes::update_many()
+        .col_expr(invite_codes::Column::CreatedBy, Expr::value(Option::<i32>::None))
+        .filter(invite_codes::Column::CreatedBy.eq(user_id))
+        .exec(&txn)
+        .await?;
+
+    // 3. Drop memberships, credentials, sessions and pending tokens outright.
+    ChatParticipants::delete_many()
+        .filter(chat_participants::Column::UserId.eq(user_id))
+        .exec(&txn)
+        .await?;
+    WebauthnCredentials::delete_many()
+        .filter(webauthn_credentials::Column::UserId.eq(user_id))
+        .exec(&txn)
+        .await?;
+    UserSessions::delete_many()
+        .filter(user_sessions::Column::UserId.eq(user_id))
+        .exec(&txn)
+        .await?;
+    PasswordResetTokens::delete_many()
+        .filter(password_reset_tokens::Column::UserId.eq(user_id))
+        .exec(&txn)
+        .await?;
+    let archives = DataExports::find()
+        .filter(data_exports::Column::UserId.eq(user_id))
+        .all(&txn)
+        .await?
+        .into_iter()
+        .filter_map(|export| export.file_name)
+        .collect();
+    DataExports::delete_many()
+        .filter(data_exports::Column::UserId.eq(user_id))
+        .exec(&txn)
+        .await?;
+
+    // 4. Scrub the profile. Nobody knows the new password, and the row is left
+    //    soft-deleted for the purge job.
+    let mut secret = [0u8; 32];
+    OsRng.fill_bytes(&mut secret);
+    let password = hash_password(&URL_SAFE_NO_PAD.encode(secret))
+        .map_err(|e| DbErr::Custom(format!("Password hashing error: {:?}", e)))?;
+    let mut user_model: users::ActiveModel = user.into();
+    user_model.first_name = Set("Deleted".to_string());
+    user_model.last_name = Set("User".to_string());
+    user_model.username = Set(format!("deleted-user-{}", user_id));
+    user_model.password = Set(password);
+    user_model.email = Set(None);
+    user_model.email_verified_at = Set(None);
+    user_model.avatar = Set(None);
+    user_model.status_reason = Set(None);
+    user_model.deleted_at = Set(Some(Utc::now().into()));
+    user_model.update(&txn).await?;
+
+    // 5. The audit record names no personal data, only who erased which account id.
+    audit::record(&txn, context, AuditEvent {
+        action: "user.erase",
+        target_type: "user",
+        target_id: Some(user_id),
+        changes: None,
+    })
+    .await?;
+
+    txn.commit().await?;
+    Ok(archives)
+}
+
+async fn erased_response(config: &Config, result: Result<Vec<String>, DbErr>) -> HttpResponse {
+    match result {
+        Ok(archives) => {
+            for file_name in archives {
+                remove_archive(&config.export_dir, &file_name).await;
+            }
+            HttpResponse::Ok().json(ResponseMessage {
+                message: "Account erased successfully".to_string(),
+            })
+        }
+        Err(err) => HttpResponse::InternalServerError().json(format!("Error: {:?}", err)),
+    }
+}
+
+// Erase Own Account Handler
+#[utoipa::path(
+    post,
+    path = "/users/erase",
+    tag = "users",
+    request_body = EraseAccount,
+    responses(
+        (status = 200, description = "Account erased", body = ResponseMessage),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 404, description = "User not found", body = ErrorResponse),
+        (status = 500, description = "Unexpected server error", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn erase_my_account(
+    auth_user: AuthenticatedUser,
+    req: HttpRequest,
+    db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
+    form: web::Json<EraseAccount>,
+) -> HttpResponse {
+    let user = match Users::find_live()
+        .filter(users::Column::Username.eq(&auth_user.0.sub))
+        .one(db.get_ref())
+        .await
+    {
+        Ok(Some(user)) => user,
+        Ok(None) => return HttpResponse::NotFound().json(ResponseMessage {
+            message: "User not found".to_string(),
+        }),
+        Err(err) => return HttpResponse::InternalServerError().json(format!("Error: {:?}", err)),
+    };
+
+    if !verify_password(&form.password, &user.password) {
+        return HttpResponse::Unauthorized().json(ResponseMessage {
+            message: "Invalid password".to_string(),
+        });
+    }
+
+    let context = AuditContext::new(&req, Some(user.id));
+    erased_response(config.get_ref(), erase(db.get_ref(), &context, user).await).await
+}
+
+// Erase User Handler (admin)
+#[utoipa::path(
+    post,
+    path = "/users/{id}/erase",
+    tag = "users",
+    params(("id" = i32, Path, description = "User id")),
+    responses(
+        (status = 200, description = "Account erased", body = ResponseMessage),
+        (status = 400, description = "The caller's own account", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 403, description = "The account of an admin", body = ErrorResponse),
+        (status = 404, description = "User not found", body = ErrorResponse),
+        (status = 500, description = "Unexpected server error", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn erase_user(
+    auth_user: AuthenticatedUser,
+    req: HttpRequest,
+    db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
+    user_id: web::Path<i32>,
+) -> HttpResponse {
+    let admin = match Users::find_live()
+        .filter(users::Column::Username.eq(&auth_user.0.sub))
+        .one(db.get_ref())
+        .await
+    {
+        Ok(Some(admin)) => admin,
+        Ok(None) => return HttpResponse::Unauthorized().json(ResponseMessage {
+            message: "Unauthorized".to_string(),
+        }),
+        Err(err) => return HttpResponse::InternalServerError().json(format!("Error: {:?}", err)),
+    };
+
+    // Accounts already waiting to be purged can still be erased.
+    let user = match Users::find_by_id(user_id.into_inner()).one(db.get_ref()).await {
+        Ok(Some(user)) => user,
+        Ok(None) => return HttpResponse::NotFound().json(ResponseMessage {
+            message: "User not found".to_string(),
+        }),
+        Err(err) => return HttpResponse::InternalServerError().json(format!("Error: {:?}", err)),
+    };
+    // Own accounts go through /users/erase, which asks for the password. Admins have to
+    // be demoted first, so one admin token cannot wipe out the others.
+    if user.id == admin.id {
+        return HttpResponse::BadRequest().json(ResponseMessage {
+            message: "Use /users/erase to erase your own account".to_string(),
+        });
+    }
+    if role_name_from_id(user.role_id) == "admin" {
+        return HttpResponse::Forbidden().json(ResponseMessage {
+            message: "Admin accounts cannot be erased; demote the account first".to_string(),
+        });
+    }
+
+    let context = AuditContext::new(&req, Some(admin.id));
+    erased_response(config.get_ref(), erase(db.get_ref(), &context, user).await).await
+}
@@ -0,0 +1,282 @@
+use std::path::Path;
+use actix_web::http::header::{ContentDisposition, DispositionParam, DispositionType};
+use actix_web::{web, HttpResponse};
+use chrono::Utc;
+use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use sea_orm::sea_query::Expr;
+use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set};
+use crate::config::Config;
+use crate::entities::prelude::{DataExports, Users};
+use crate::entities::sea_orm_active_enums::ExportStatus;
+use crate::entities::{data_exports, users};
+use crate::handlers::user_handler::get_secret;
+use crate::jobs;
+use crate::mailer::Mailer;
+use crate::models::export_models::*;
+use crate::models::token_model::ExportDownloadClaims;
+use crate::models::user_models::{ErrorResponse, ResponseMessage};
+use crate::routes;
+use crate::shutdown::Shutdown;
+use crate::utils::check_auth_user::AuthenticatedUser;
+use crate::utils::soft_delete::SoftDelete;
+
+// Signed link to an export's archive, valid until the export expires.
+pub fn download_url(config: &Config, export: &data_exports::Model) -> Option<String> {
+    if export.status != ExportStatus::Ready {
+        return None;
+    }
+    let claims = ExportDownloadClaims {
+        export_id: export.id,
+        exp: export.expires_at?.timestamp() as usize,
+    };
+    let token = encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(get_secret().as_bytes()),
+    )
+    .ok()?;
+    Some(format!(
+        "{}{}/users/exports/download?token={}",
+        config.public_url.trim_end_matches('/'),
+        routes::V1,
+        token
+    ))
+}
+
+fn export_response(config: &Config, export: data_exports::Model) -> DataExportResponse {
+    let download_url = download_url(config, &export);
+    DataExportResponse {
+        download_url,
+        ..DataExportResponse::from(export)
+    }
+}
+
+async fn caller(db: &DatabaseConnection, auth_user: &AuthenticatedUser) -> Result<Option<users::Model>, sea_orm::DbErr> {
+    Users::find_live()
+        .filter(users::Column::Username.eq(&auth_user.0.sub))
+        .one(db)
+        .await
+}
+
+// Request Data Export Handler
+#[utoipa::path(
+    post,
+    path = "/users/exports",
+    tag = "exports",
+    responses(
+        (status = 202, description = "Export queued", body = DataExportResponse),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 409, description = "An export is already in progress", body = ErrorResponse),
+        (status = 500, description = "Unexpected server error", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn request_export(
+    auth_user: AuthenticatedUser,
+    db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
+    mailer: web::Data<dyn Mailer>,
+    shutdown: web::Data<Shutdown>,
+) -> HttpResponse {
+    let user = match caller(db.get_ref(), &auth_user).await {
+        Ok(Some(user)) => user,
+        Ok(None) => return HttpResponse::NotFound().json(ResponseMessage {
+            message: "User not found".to_string(),
+        }),
+        Err(err) => return HttpResponse::InternalServerError().json(format!("Error: {:?}", err)),
+    };
+
+    match DataExports::find()
+        .filter(data_exports::Column::UserId.eq(user.id))
+        .filter(data_exports::Column::Status.eq(ExportStatus::Pending))
+        .one(db.get_ref())
+        .await
+    {
+        Ok(Some(_)) => return HttpResponse::Conflict().json(ResponseMessage {
+            message: "An export is already being prepared".to_string(),
+        }),
+        Ok(None) => {}
+        Err(err) => return HttpResponse::InternalServerError().json(format!("Error: {:?}", err)),
+    }
+
+    let export = data_exports::ActiveModel {
+        user_id: Set(user.id),
+        status: Set(ExportStatus::Pending),
+        ..Default::default()
+    };
+    match export.insert(db.get_ref()).await {
+        Ok(export) => {
+            // Tracked so a shutdown waits for the archive instead of leaving it pending.
+            shutdown.spawn(jobs::data_exports::build(
+                db.get_ref().clone(),
+                config.get_ref().clone(),
+                mailer.into_inner(),
+                export.clone(),
+            ));
+            HttpResponse::Accepted().json(DataExportResponse::from(export))
+        }
+        Err(err) => HttpResponse::InternalServerError().json(format!("Error: {:?}", err)),
+    }
+}
+
+// Get My Data Exports Handler
+#[utoipa::path(
+    get,
+    path = "/users/exports",
+    tag = "exports",
+    responses(
+        (status = 200, description = "Exports of the caller", body = GetAllExportsResponse),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 500, description = "Unexpected server error", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn get_my_exports(
+    auth_user: AuthenticatedUser,
+    db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
+) -> HttpResponse {
+    let user = match caller(db.get_ref(), &auth_user).await {
+        Ok(Some(user)) => user,
+        Ok(None) => return HttpResponse::NotFound().json(ResponseMessage {
+            message: "User not found".to_string(),
+        }),
+        Err(err) => return HttpResponse::InternalServerError().json(format!("Error: {:?}", err)),
+    };
+
+    match DataExports::find()
+        .filter(data_exports::Column::UserId.eq(user.id))
+        .order_by_desc(data_exports::Column::CreatedAt)
+        .all(db.get_ref())
+        .await
+    {
+        Ok(exports) => HttpResponse::Ok().json(GetAllExportsResponse {
+            exports: exports
+                .into_iter()
+                .map(|export| export_response(config.get_ref(), export))
+                .collect(),
+        }),
+        Err(err) => HttpResponse::InternalServerError().json(format!("Error: {:?}", err)),
+    }
+}
+
+// Get Single Data Export Handler
+#[utoipa::path(
+    get,
+    path = "/users/exports/{export_id}",
+    tag = "exports",
+    params(("export_id" = i32, Path, description = "Export id")),
+    responses(
+        (status = 200, description = "The export", body = DataExportResponse),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 404, description = "Export not found", body = ErrorResponse),
+        (status = 500, description = "Unexpected server error", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn get_my_export(
+    auth_user: AuthenticatedUser,
+    db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
+    export_id: web::Path<i32>,
+) -> HttpResponse {
+    let user = match caller(db.get_ref(), &auth_user).await {
+        Ok(Some(user)) => user,
+        Ok(None) => return HttpResponse::NotFound().json(ResponseMessage {
+            message: "User not found".to_string(),
+        }),
+        Err(err) => return HttpResponse::InternalServerError().json(format!("Error: {:?}", err)),
+    };
+
+    match DataExports::find_by_id(export_id.into_inner())
+        .filter(data_exports::Column::UserId.eq(user.id))
+        .one(db.get_ref())
+        .await
+    {
+        Ok(Some(export)) => HttpResponse::Ok().json(export_response(config.get_ref(), export)),
+        Ok(None) => HttpResponse::NotFound().json(ResponseMessage {
+            message: "Export not found".to_string(),
+        }),
+        Err(err) => HttpResponse::InternalServerError().json(format!("Error: {:?}", err)),
+    }
+}
+
+// Download Data Export Handler
+// The signed token is the only credential, and the archive is deleted once it is served.
+#[utoipa::path(
+    get,
+    path = "/users/exports/download",
+    tag = "exports",
+    params(DownloadExport),
+    responses(
+        (status = 200, description = "The archive", body = [u8], content_type = "application/zip"),
+        (status = 410, description = "Link expired or already used", body = ErrorResponse),
+        (status = 500, description = "Unexpected server error", body = ErrorResponse),
+    )
+)]
+pub async fn download_export(
+    db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
+    query: web::Query<DownloadExport>,
+) -> HttpResponse {
+    let gone = || {
+        HttpResponse::Gone().json(ResponseMessage {
+            message: "This download link is invalid, expired or already used".to_string(),
+        })
+    };
+
+    let claims = match decode::<ExportDownloadClaims>(
+        query.token.trim(),
+        &DecodingKey::from_secret(get_secret().as_bytes()),
+        &Validation::default(),
+    ) {
+        Ok(data) => data.claims,
+        Err(_) => return gone(),
+    };
+
+    let export = match DataExports::find_by_id(claims.export_id).one(db.get_ref()).await {
+        Ok(Some(export)) => export,
+        Ok(None) => return gone(),
+        Err(err) => return HttpResponse::InternalServerError().json(format!("Error: {:?}", err)),
+    };
+    let file_name = match &export.file_name {
+        Some(file_name) if export.status == ExportStatus::Ready => file_name.clone(),
+        _ => return gone(),
+    };
+
+    // Read before claiming, so a missing archive does not use up the link.
+    let archive = match tokio::fs::read(Path::new(&config.export_dir).join(&file_name)).await {
+        Ok(archive) => archive,
+        Err(err) => {
+            log::error!("Failed to read data export archive {}: {}", file_name, err);
+            return HttpResponse::InternalServerError().json(ResponseMessage {
+                message: "Export archive is unavailable".to_string(),
+            });
+        }
+    };
+
+    // Claiming the download is conditional on it still being ready, so the link works once.
+    let claimed = DataExports::update_many()
+        .col_expr(data_exports::Column::Status, Expr::value(ExportStatus::Downloaded))
+        .col_expr(data_exports::Column::DownloadedAt, Expr::value(Utc::now()))
+        .filter(data_exports::Column::Id.eq(export.id))
+        .filter(data_exports::Column::Status.eq(ExportStatus::Ready))
+        .filter(data_exports::Column::ExpiresAt.gt(Utc::now()))
+        .exec(db.get_ref())
+        .await;
+    match claimed {
+        Ok(result) if result.rows_affected == 1 => {}
+        Ok(_) => return gone(),
+        Err(err) => return HttpResponse::InternalServerError().json(format!("Error: {:?}", err)),
+    }
+
+    jobs::data_exports::remove_archive(&config.export_dir, &file_name).await;
+
+    HttpResponse::Ok()
+        .content_type("application/zip")
+        .insert_header(ContentDisposition {
+            disposition: DispositionType::Attachment,
+            parameters: vec![DispositionParam::Filename(format!("blockchat-export-{}.zip", export.id))],
+        })
+        .body(archive)
+}
@@ -0,0 +1,202 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use actix_web::http::header::{self, HeaderMap, HeaderValue};
+use actix_web::http::StatusCode;
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+use actix_ws::{CloseCode, CloseReason, Message as WsFrame};
+use async_graphql::Data;
+use async_graphql::http::{GraphiQLSource, WebSocket, WebSocketProtocols as Protocols, WsMessage};
+use futures::{future, StreamExt};
+use sea_orm::DatabaseConnection;
+use crate::graphql::{self, BlockChatSchema, Viewer};
+use crate::metrics;
+use crate::routes;
+use crate::shutdown::Shutdown;
+use crate::utils::check_auth_user::{AuthenticatedUser, AuthenticatedUserId};
+use crate::utils::messages::CHAT_API_ROLES;
+
+// How often an idle subscription connection checks that its session is still valid.
+const REVALIDATE_INTERVAL: Duration = Duration::from_secs(30);
+
+// GraphQL Handler
+pub async fn graphql(
+    req: HttpRequest,
+    auth_user: AuthenticatedUser,
+    schema: web::Data<BlockChatSchema>,
+    db: web::Data<DatabaseConnection>,
+    request: web::Json<async_graphql::Request>,
+) -> HttpResponse {
+    let Some(AuthenticatedUserId(user_id)) = req.extensions().get::<AuthenticatedUserId>().copied() else {
+        return HttpResponse::Unauthorized().json("Unauthorized");
+    };
+    let mut request = request.into_inner();
+    graphql::add_operation_data(&mut request.data, db.get_ref().clone(), Viewer::new(auth_user.0, user_id));
+    HttpResponse::Ok().json(schema.execute(request).await)
+}
+
+// GraphQL Subscriptions Handler
+//
+// Speaks both `graphql-transport-ws` and the older `graphql-ws` protocol. Browsers cannot
+// set headers on a WebSocket, so the token may come either with the upgrade request or
+// as `{"Authorization": "Bearer <token>"}` in the `connection_init` payload; it passes the
+// same checks as on the REST routes either way. The session is checked again before every
+// message sent and every `REVALIDATE_INTERVAL`, and the connection is closed as soon as it
+// is revoked or the account restricted.
+pub async fn graphql_ws(
+    req: HttpRequest,
+    body: web::Payload,
+    schema: web::Data<BlockChatSchema>,
+    db: web::Data<DatabaseConnection>,
+    shutdown: web::Data<Shutdown>,
+) -> actix_web::Result<HttpResponse> {
+    let protocol = req
+        .headers()
+        .get(header::SEC_WEBSOCKET_PROTOCOL)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(',').find_map(|name| name.trim().parse::<Protocols>().ok()));
+
+    let db = db.get_ref().clone();
+    // Set once `connection_init` is accepted.
+    let session_jti = Arc::new(Mutex::new(None::<String>));
+    let upgrade_viewer = if req.headers().contains_key(header::AUTHORIZATION) {
+        Some(ws_viewer(req.headers(), &db).await?)
+    } else {
+        None
+    };
+
+    let (mut response, mut session, frames) = actix_ws::handle(&req, body)?;
+    let connection = metrics::WebSocketConnection::open();
+    if let Some(protocol) = protocol {
+        response.headers_mut().insert(
+            header::SEC_WEBSOCKET_PROTOCOL,
+            HeaderValue::from_static(protocol.sec_websocket_protocol()),
+        );
+    }
+
+    let pong = session.clone();
+    let incoming = frames
+        .take_while(|frame| future::ready(!matches!(frame, Ok(WsFrame::Close(_)) | Err(_))))
+        .filter_map(move |frame| {
+            let mut pong = pong.clone();
+            async move {
+                match frame {
+                    Ok(WsFrame::Text(text)) => Some(text.as_bytes().to_vec()),
+                    Ok(WsFrame::Binary(bytes)) => Some(bytes.to_vec()),
+                    Ok(WsFrame::Ping(bytes)) => {
+                        pong.pong(&bytes).await.ok();
+                        None
+                    }
+                    _ => None,
+                }
+            }
+        });
+
+    let schema = schema.get_ref().clone();
+    let init_db = db.clone();
+    let init_jti = session_jti.clone();
+    let mut stream = WebSocket::new(schema, Box::pin(incoming), protocol.unwrap_or(Protocols::SubscriptionsTransportWS))
+        .on_connection_init(move |payload| async move {
+            let db = init_db;
+            let (viewer, jti) = match upgrade_viewer {
+                Some(viewer) => viewer,
+                None => {
+                    let token = payload
+                        .get("Authorization")
+                        .or_else(|| payload.get("authorization"))
+                        .and_then(|value| value.as_str())
+                        .unwrap_or_default();
+                    let mut headers = HeaderMap::new();
+                    if let Ok(value) = HeaderValue::from_str(token) {
+                        headers.insert(header::AUTHORIZATION, value);
+                    }
+                    ws_viewer(&headers, &db)
+                        .await
+                        .map_err(|err| async_graphql::Error::new(err.to_string()))?
+                }
+            };
+            *init_jti.lock().unwrap() = Some(jti);
+            let mut data = Data::default();
+            graphql::add_operation_data(&mut data, db, viewer);
+            Ok(data)
+        });
+
+    let stopping = shutdown.token();
+    actix_web::rt::spawn(async move {
+        let _connection = connection;
+        let mut revalidate = tokio::time::interval(REVALIDATE_INTERVAL);
+        loop {
+            let message = tokio::select! {
+                message = stream.next() => message,
+                _ = revalidate.tick() => {
+                    if let Err(reason) = check_session(&db, &session_jti).await {
+                        session.close(Some(reason)).await.ok();
+                        return;
+                    }
+                    continue;
+                }
+                // Clients reconnect to another instance on 1012.
+                _ = stopping.cancelled() => {
+                    let reason = CloseReason { code: CloseCode::Restart, description: Some("Server restarting".to_string()) };
+                    session.close(Some(reason)).await.ok();
+                    return;
+                }
+            };
+            match message {
+                Some(WsMessage::Text(text)) => {
+                    if let Err(reason) = check_session(&db, &session_jti).await {
+                        session.close(Some(reason)).await.ok();
+                        return;
+                    }
+                    if session.text(text).await.is_err() {
+                        return;
+                    }
+                }
+                Some(WsMessage::Close(code, reason)) => {
+                    let reason = CloseReason { code: CloseCode::from(code), description: Some(reason) };
+                    session.close(Some(reason)).await.ok();
+                    return;
+                }
+                None => break,
+            }
+        }
+        session.close(None).await.ok();
+    });
+    Ok(response)
+}
+
+// The checks `RoleGuard` does for the POST route, which a WebSocket has to make itself.
+// Returns the session id along with the viewer, to check the session again later.
+async fn ws_viewer(headers: &HeaderMap, db: &DatabaseConnection) -> actix_web::Result<(Viewer, String)> {
+    let (auth_user, user) = AuthenticatedUser::verify(headers, db).await?;
+    if !CHAT_API_ROLES.contains(&auth_user.0.role.as_str()) {
+        return Err(actix_web::error::ErrorUnauthorized("Unauthorized"));
+    }
+    let jti = auth_user.0.jti.clone();
+    Ok((Viewer::new(auth_user.0, user.id), jti))
+}
+
+// Nothing to check before `connection_init`. Otherwise a failed check closes the
+// connection with the graphql-transport-ws code for it.
+async fn check_session(db: &DatabaseConnection, session_jti: &Mutex<Option<String>>) -> Result<(), CloseReason> {
+    let Some(jti) = session_jti.lock().unwrap().clone() else {
+        return Ok(());
+    };
+    AuthenticatedUser::check_session(db, &jti).await.map(|_| ()).map_err(|err| {
+        let code = match err.as_response_error().status_code() {
+            StatusCode::UNAUTHORIZED => 4401,
+            StatusCode::FORBIDDEN => 4403,
+            _ => 4500,
+        };
+        CloseReason { code: CloseCode::Other(code), description: Some(err.to_string()) }
+    })
+}
+
+// GraphiQL Handler
+pub async fn graphiql() -> HttpResponse {
+    let endpoint = format!("{}/graphql", routes::V1);
+    let page = GraphiQLSource::build()
+        .endpoint(&endpoint)
+        .subscription_endpoint(&format!("{}/ws", endpoint))
+        .finish();
+    HttpResponse::Ok().content_type("text/html; charset=utf-8").body(page)
+}
@@ -0,0 +1,119 @@
+use std::future::Future;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use actix_web::{web, HttpResponse};
+use migration::{Migrator, MigratorTrait};
+use sea_orm::DatabaseConnection;
+use crate::config::Config;
+use crate::models::health_models::{
+    CheckResult, HealthResponse, MigrationCheck, ReadinessChecks, ReadinessResponse,
+};
+
+// A dependency that does not answer within this long counts as down.
+const CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+async fn timed<T, F>(check: F) -> (Result<T, String>, u128)
+where
+    F: Future<Output = Result<T, String>>,
+{
+    let start = Instant::now();
+    let result = match tokio::time::timeout(CHECK_TIMEOUT, check).await {
+        Ok(result) => result,
+        Err(_) => Err(format!("Timed out after {}s", CHECK_TIMEOUT.as_secs())),
+    };
+    (result, start.elapsed().as_millis())
+}
+
+fn check_result<T>(result: &Result<T, String>, latency_ms: u128) -> CheckResult {
+    CheckResult {
+        ok: result.is_ok(),
+        latency_ms,
+        error: result.as_ref().err().cloned(),
+    }
+}
+
+// Export archives are the only files the server keeps outside the database, so the
+// export directory must exist and accept writes.
+async fn probe_blob_store(dir: &str) -> Result<(), String> {
+    tokio::fs::create_dir_all(dir)
+        .await
+        .map_err(|e| format!("Cannot create {}: {}", dir, e))?;
+    let probe = Path::new(dir).join(format!(".readyz-{}", uuid::Uuid::new_v4()));
+    tokio::fs::write(&probe, b"ok")
+        .await
+        .map_err(|e| format!("Cannot write to {}: {}", dir, e))?;
+    tokio::fs::remove_file(&probe)
+        .await
+        .map_err(|e| format!("Cannot remove {}: {}", probe.display(), e))
+}
+
+// Liveness Handler
+#[utoipa::path(
+    get,
+    path = "/healthz",
+    tag = "health",
+    responses(
+        (status = 200, description = "The process is up", body = HealthResponse),
+    )
+)]
+pub async fn healthz() -> HttpResponse {
+    HttpResponse::Ok().json(HealthResponse { status: "ok" })
+}
+
+// Readiness Handler
+#[utoipa::path(
+    get,
+    path = "/readyz",
+    tag = "health",
+    responses(
+        (status = 200, description = "Ready to serve traffic", body = ReadinessResponse),
+        (status = 503, description = "A dependency is unavailable", body = ReadinessResponse),
+    )
+)]
+pub async fn readyz(
+    db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
+) -> HttpResponse {
+    let (database, database_ms) = timed(async {
+        db.ping().await.map_err(|e| e.to_string())
+    })
+    .await;
+
+    let (pending, migrations_ms) = timed(async {
+        Migrator::get_pending_migrations(db.get_ref())
+            .await
+            .map(|pending| pending.iter().map(|m| m.name().to_string()).collect::<Vec<_>>())
+            .map_err(|e| e.to_string())
+    })
+    .await;
+    let migrations = MigrationCheck {
+        result: CheckResult {
+            ok: matches!(&pending, Ok(names) if names.is_empty()),
+            latency_ms: migrations_ms,
+            error: match &pending {
+                Ok(names) if !names.is_empty() => Some(format!("{} migration(s) pending", names.len())),
+                Ok(_) => None,
+                Err(e) => Some(e.clone()),
+            },
+        },
+        pending: pending.unwrap_or_default(),
+    };
+
+    let (blob_store, blob_store_ms) = timed(probe_blob_store(&config.export_dir)).await;
+
+    let checks = ReadinessChecks {
+        database: check_result(&database, database_ms),
+        migrations,
+        blob_store: check_result(&blob_store, blob_store_ms),
+    };
+    let ready = checks.database.ok && checks.migrations.result.ok && checks.blob_store.ok;
+    let body = ReadinessResponse {
+        status: if ready { "ready" } else { "not_ready" },
+        checks,
+    };
+    if ready {
+        HttpResponse::Ok().json(body)
+    } else {
+        HttpResponse::ServiceUnavailable().json(body)
+    }
+}
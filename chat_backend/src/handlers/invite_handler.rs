@@ -0,0 +1,187 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+use chrono::Utc;
+use rand::distr::Alphanumeric;
+use rand::Rng;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, QueryOrder,
+    Set, TransactionTrait,
+};
+use serde_json::json;
+use crate::entities::prelude::InviteCodes;
+use crate::entities::invite_codes;
+use crate::models::invite_models::*;
+use crate::models::user_models::{ErrorResponse, ResponseMessage};
+use crate::utils::audit::{self, AuditContext, AuditEvent};
+use crate::utils::check_auth_user::AuthenticatedUser;
+
+fn generate_code() -> String {
+    rand::rng()
+        .sample_iter(&Alphanumeric)
+        .take(12)
+        .map(char::from)
+        .collect::<String>()
+        .to_uppercase()
+}
+
+// Create Invite Handler
+#[utoipa::path(
+    post,
+    path = "/invites",
+    tag = "invites",
+    request_body = CreateInvite,
+    responses(
+        (status = 200, description = "Invite created", body = InviteResponse),
+        (status = 400, description = "Invalid invite", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 409, description = "Invite code already exists", body = ErrorResponse),
+        (status = 500, description = "Unexpected server error", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn create_invite(
+    auth_user: AuthenticatedUser,
+    req: HttpRequest,
+    db: web::Data<DatabaseConnection>,
+    form: web::Json<CreateInvite>,
+) -> HttpResponse {
+    if form.max_uses.is_some_and(|max| max < 1) {
+        return HttpResponse::BadRequest().json(ResponseMessage {
+            message: "max_uses must be at least 1".to_string(),
+        });
+    }
+    if form.expires_at.is_some_and(|at| at <= Utc::now()) {
+        return HttpResponse::BadRequest().json(ResponseMessage {
+            message: "expires_at must be in the future".to_string(),
+        });
+    }
+
+    let code = form
+        .code
+        .as_ref()
+        .map(|c| c.trim().to_string())
+        .filter(|c| !c.is_empty())
+        .unwrap_or_else(generate_code);
+    if InviteCodes::find()
+        .filter(invite_codes::Column::Code.eq(code.as_str()))
+        .one(db.get_ref())
+        .await
+        .unwrap()
+        .is_some()
+    {
+        return HttpResponse::Conflict().json(ResponseMessage {
+            message: "Invite code already exists".to_string(),
+        });
+    }
+
+    let creator_id = auth_user.user_id(db.get_ref()).await.ok().flatten();
+    let context = AuditContext::new(&req, creator_id);
+
+    let invite = invite_codes::ActiveModel {
+        code: Set(code),
+        created_by: Set(creator_id),
+        max_uses: Set(form.max_uses),
+        expires_at: Set(form.expires_at.map(Into::into)),
+        ..Default::default()
+    };
+    let created = async {
+        let txn = db.begin().await?;
+        let invite = invite.insert(&txn).await?;
+        audit::record(&txn, &context, AuditEvent {
+            action: "invite.create",
+            target_type: "invite",
+            target_id: Some(invite.id),
+            changes: Some(json!({
+                "max_uses": { "before": null, "after": invite.max_uses },
+                "expires_at": { "before": null, "after": invite.expires_at.map(|dt| dt.to_rfc3339()) },
+            })),
+        })
+        .await?;
+        txn.commit().await?;
+        Ok::<_, DbErr>(invite)
+    };
+    match created.await {
+        Ok(invite) => HttpResponse::Ok().json(InviteResponse::from(invite)),
+        Err(err) => HttpResponse::InternalServerError().json(format!("Error: {:?}", err)),
+    }
+}
+
+// Get All Invites Handler
+#[utoipa::path(
+    get,
+    path = "/invites",
+    tag = "invites",
+    responses(
+        (status = 200, description = "All invites", body = GetAllInvitesResponse),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 500, description = "Unexpected server error", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn get_invites(db: web::Data<DatabaseConnection>) -> HttpResponse {
+    match InviteCodes::find()
+        .order_by_desc(invite_codes::Column::CreatedAt)
+        .all(db.get_ref())
+        .await
+    {
+        Ok(invites) => HttpResponse::Ok().json(GetAllInvitesResponse {
+            invites: invites.into_iter().map(InviteResponse::from).collect(),
+        }),
+        Err(err) => HttpResponse::InternalServerError().json(format!("Error: {:?}", err)),
+    }
+}
+
+// Revoke Invite Handler
+#[utoipa::path(
+    delete,
+    path = "/invites/{id}",
+    tag = "invites",
+    params(("id" = i32, Path, description = "Invite id")),
+    responses(
+        (status = 200, description = "Invite revoked", body = InviteResponse),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 404, description = "Invite not found", body = ErrorResponse),
+        (status = 500, description = "Unexpected server error", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn revoke_invite(
+    auth_user: AuthenticatedUser,
+    req: HttpRequest,
+    db: web::Data<DatabaseConnection>,
+    invite_id: web::Path<i32>,
+) -> HttpResponse {
+    let invite = match InviteCodes::find_by_id(invite_id.into_inner()).one(db.get_ref()).await {
+        Ok(Some(invite)) => invite,
+        Ok(None) => return HttpResponse::NotFound().json(ResponseMessage {
+            message: "Invite not found".to_string(),
+        }),
+        Err(err) => return HttpResponse::InternalServerError().json(format!("Error: {:?}", err)),
+    };
+    if invite.revoked_at.is_some() {
+        return HttpResponse::Ok().json(InviteResponse::from(invite));
+    }
+
+    let context = match auth_user.user_id(db.get_ref()).await {
+        Ok(actor_id) => AuditContext::new(&req, actor_id),
+        Err(err) => return HttpResponse::InternalServerError().json(format!("Error: {:?}", err)),
+    };
+    let mut invite_model: invite_codes::ActiveModel = invite.into();
+    invite_model.revoked_at = Set(Some(Utc::now().into()));
+    let revoked = async {
+        let txn = db.begin().await?;
+        let invite = invite_model.update(&txn).await?;
+        audit::record(&txn, &context, AuditEvent {
+            action: "invite.revoke",
+            target_type: "invite",
+            target_id: Some(invite.id),
+            changes: None,
+        })
+        .await?;
+        txn.commit().await?;
+        Ok::<_, DbErr>(invite)
+    };
+    match revoked.await {
+        Ok(invite) => HttpResponse::Ok().json(InviteResponse::from(invite)),
+        Err(err) => HttpResponse::InternalServerError().json(format!("Error: {:?}", err)),
+    }
+}
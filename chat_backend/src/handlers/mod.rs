@@ -1,4 +1,15 @@
 pub mod user_handler;
+pub mod passkey_handler;
+pub mod password_handler;
+pub mod invite_handler;
+pub mod verification_handler;
+pub mod session_handler;
+pub mod export_handler;
+pub mod erasure_handler;
+pub mod moderation_handler;
+pub mod audit_handler;
+pub mod health_handler;
+pub mod graphql_handler;
 
 #[macro_export]
 macro_rules! merge_update {
@@ -0,0 +1,163 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+use chrono::Utc;
+use sea_orm::DatabaseConnection;
+use crate::entities::prelude::Users;
+use crate::entities::sea_orm_active_enums::AccountStatus;
+use crate::entities::users;
+use crate::models::user_models::{ErrorResponse, ModerateUser, ResponseMessage, UserResponse};
+use crate::utils::audit::{self, AuditContext};
+use crate::utils::check_auth_user::AuthenticatedUser;
+use crate::utils::moderation;
+use crate::utils::soft_delete::SoftDelete;
+
+async fn set_restriction(
+    auth_user: &AuthenticatedUser,
+    req: &HttpRequest,
+    db: &DatabaseConnection,
+    user_id: i32,
+    status: AccountStatus,
+    form: &ModerateUser,
+) -> HttpResponse {
+    if form.reason.trim().is_empty() {
+        return HttpResponse::BadRequest().json(ResponseMessage {
+            message: "A reason is required".to_string(),
+        });
+    }
+    if form.until.is_some_and(|until| until <= Utc::now()) {
+        return HttpResponse::BadRequest().json(ResponseMessage {
+            message: "until must be in the future".to_string(),
+        });
+    }
+
+    let admin_id = match auth_user.user_id(db).await {
+        Ok(Some(admin_id)) => admin_id,
+        Ok(None) => return HttpResponse::Unauthorized().json(ResponseMessage {
+            message: "Unauthorized".to_string(),
+        }),
+        Err(err) => return HttpResponse::InternalServerError().json(format!("Error: {:?}", err)),
+    };
+    if admin_id == user_id {
+        return HttpResponse::BadRequest().json(ResponseMessage {
+            message: "You cannot restrict your own account".to_string(),
+        });
+    }
+
+    let user = match Users::find_live_by_id(user_id).one(db).await {
+        Ok(Some(user)) => user,
+        Ok(None) => return HttpResponse::NotFound().json(ResponseMessage {
+            message: "User not found".to_string(),
+        }),
+        Err(err) => return HttpResponse::InternalServerError().json(format!("Error: {:?}", err)),
+    };
+
+    let action = match status {
+        AccountStatus::Banned => "user.ban",
+        _ => "user.suspend",
+    };
+    let mut user_model: users::ActiveModel = user.clone().into();
+    moderation::restrict(&user, &mut user_model, status, &form.reason, Some(admin_id), form.until);
+    let context = AuditContext::new(req, Some(admin_id));
+    match audit::update_user(db, &context, action, &user, user_model).await {
+        Ok(user) => HttpResponse::Ok().json(UserResponse::from(user)),
+        Err(err) => HttpResponse::InternalServerError().json(format!("Error: {:?}", err)),
+    }
+}
+
+// Suspend User Handler
+#[utoipa::path(
+    post,
+    path = "/users/{id}/suspend",
+    tag = "moderation",
+    request_body = ModerateUser,
+    params(("id" = i32, Path, description = "User id")),
+    responses(
+        (status = 200, description = "User suspended", body = UserResponse),
+        (status = 400, description = "Invalid moderation request", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 404, description = "User not found", body = ErrorResponse),
+        (status = 500, description = "Unexpected server error", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn suspend_user(
+    auth_user: AuthenticatedUser,
+    req: HttpRequest,
+    db: web::Data<DatabaseConnection>,
+    user_id: web::Path<i32>,
+    form: web::Json<ModerateUser>,
+) -> HttpResponse {
+    set_restriction(&auth_user, &req, db.get_ref(), user_id.into_inner(), AccountStatus::Suspended, &form).await
+}
+
+// Ban User Handler
+#[utoipa::path(
+    post,
+    path = "/users/{id}/ban",
+    tag = "moderation",
+    request_body = ModerateUser,
+    params(("id" = i32, Path, description = "User id")),
+    responses(
+        (status = 200, description = "User banned", body = UserResponse),
+        (status = 400, description = "Invalid moderation request", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 404, description = "User not found", body = ErrorResponse),
+        (status = 500, description = "Unexpected server error", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn ban_user(
+    auth_user: AuthenticatedUser,
+    req: HttpRequest,
+    db: web::Data<DatabaseConnection>,
+    user_id: web::Path<i32>,
+    form: web::Json<ModerateUser>,
+) -> HttpResponse {
+    set_restriction(&auth_user, &req, db.get_ref(), user_id.into_inner(), AccountStatus::Banned, &form).await
+}
+
+// Lift Suspension/Ban Handler
+#[utoipa::path(
+    post,
+    path = "/users/{id}/reinstate",
+    tag = "moderation",
+    params(("id" = i32, Path, description = "User id")),
+    responses(
+        (status = 200, description = "User reinstated", body = UserResponse),
+        (status = 400, description = "User is not suspended or banned", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 404, description = "User not found", body = ErrorResponse),
+        (status = 500, description = "Unexpected server error", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn reinstate_user(
+    auth_user: AuthenticatedUser,
+    req: HttpRequest,
+    db: web::Data<DatabaseConnection>,
+    user_id: web::Path<i32>,
+) -> HttpResponse {
+    let user = match Users::find_live_by_id(user_id.into_inner()).one(db.get_ref()).await {
+        Ok(Some(user)) => user,
+        Ok(None) => return HttpResponse::NotFound().json(ResponseMessage {
+            message: "User not found".to_string(),
+        }),
+        Err(err) => return HttpResponse::InternalServerError().json(format!("Error: {:?}", err)),
+    };
+    if !matches!(user.status, AccountStatus::Suspended | AccountStatus::Banned) {
+        return HttpResponse::BadRequest().json(ResponseMessage {
+            message: "User is not suspended or banned".to_string(),
+        });
+    }
+    let admin_id = match auth_user.user_id(db.get_ref()).await {
+        Ok(admin_id) => admin_id,
+        Err(err) => return HttpResponse::InternalServerError().json(format!("Error: {:?}", err)),
+    };
+
+    let mut user_model: users::ActiveModel = user.clone().into();
+    moderation::lift(&user, &mut user_model);
+    let context = AuditContext::new(&req, admin_id);
+    match audit::update_user(db.get_ref(), &context, "user.reinstate", &user, user_model).await {
+        Ok(user) => HttpResponse::Ok().json(UserResponse::from(user)),
+        Err(err) => HttpResponse::InternalServerError().json(format!("Error: {:?}", err)),
+    }
+}
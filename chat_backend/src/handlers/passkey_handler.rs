@@ -0,0 +1,379 @@
+use actix_web::http::StatusCode;
+use actix_web::{web, HttpRequest, HttpResponse};
+use chrono::{Duration, Utc};
+use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, Set};
+use crate::config::Config;
+use crate::entities::prelude::{Users, WebauthnChallenges, WebauthnCredentials};
+use crate::entities::{users, webauthn_challenges, webauthn_credentials};
+use crate::handlers::user_handler::login_response;
+use crate::metrics;
+use crate::models::passkey_models::*;
+use crate::models::user_models::{AccountRestrictedResponse, ErrorResponse, LoginResponse, ResponseMessage};
+use crate::utils::check_auth_user::AuthenticatedUser;
+use crate::utils::soft_delete::SoftDelete;
+use crate::utils::webauthn::{self, RelyingParty, COSE_ALG_ES256};
+
+const REGISTRATION: &str = "registration";
+const AUTHENTICATION: &str = "authentication";
+const CEREMONY_TIMEOUT_SECS: i64 = 300;
+
+fn error_response(status: StatusCode, message: impl ToString) -> HttpResponse {
+    HttpResponse::build(status).json(ResponseMessage {
+        message: message.to_string(),
+    })
+}
+
+// Records the challenge of a ceremony that is starting. The client sends it back as
+// `state` to finish the ceremony.
+async fn start_ceremony(db: &DatabaseConnection, ceremony: &str, user_id: Option<i32>) -> Result<Vec<u8>, DbErr> {
+    // Ceremonies that were started and never finished.
+    WebauthnChallenges::delete_many()
+        .filter(webauthn_challenges::Column::ExpiresAt.lte(Utc::now()))
+        .exec(db)
+        .await?;
+
+    let challenge = webauthn::new_challenge();
+    let pending = webauthn_challenges::ActiveModel {
+        ceremony: Set(ceremony.to_string()),
+        challenge: Set(challenge.clone()),
+        user_id: Set(user_id),
+        expires_at: Set((Utc::now() + Duration::seconds(CEREMONY_TIMEOUT_SECS)).into()),
+        ..Default::default()
+    };
+    WebauthnChallenges::insert(pending).exec(db).await?;
+    Ok(challenge)
+}
+
+// Takes the ceremony named by `state` out of the table and returns its challenge and
+// user. Deleting the row is what claims it, so each challenge is answered at most once,
+// even by concurrent requests, and a captured response cannot be replayed.
+async fn finish_ceremony(
+    db: &DatabaseConnection,
+    state: &str,
+    ceremony: &str,
+) -> Result<Option<(Vec<u8>, Option<i32>)>, DbErr> {
+    let Ok(challenge) = webauthn::decode(state) else {
+        return Ok(None);
+    };
+    let Some(pending) = WebauthnChallenges::find()
+        .filter(webauthn_challenges::Column::Challenge.eq(challenge))
+        .filter(webauthn_challenges::Column::Ceremony.eq(ceremony))
+        .filter(webauthn_challenges::Column::ExpiresAt.gt(Utc::now()))
+        .one(db)
+        .await?
+    else {
+        return Ok(None);
+    };
+    let claimed = WebauthnChallenges::delete_by_id(pending.id).exec(db).await?;
+    if claimed.rows_affected != 1 {
+        return Ok(None);
+    }
+    Ok(Some((pending.challenge, pending.user_id)))
+}
+
+fn descriptor(credential: &webauthn_credentials::Model) -> CredentialDescriptor {
+    CredentialDescriptor {
+        kind: "public-key".to_string(),
+        id: webauthn::encode(&credential.credential_id),
+    }
+}
+
+async fn find_user_by_username(db: &DatabaseConnection, username: &str) -> Option<users::Model> {
+    Users::find_live()
+        .filter(users::Column::Username.eq(username))
+        .one(db)
+        .await
+        .ok()
+        .flatten()
+}
+
+async fn credentials_of(db: &DatabaseConnection, user_id: i32) -> Vec<webauthn_credentials::Model> {
+    WebauthnCredentials::find()
+        .filter(webauthn_credentials::Column::UserId.eq(user_id))
+        .all(db)
+        .await
+        .unwrap_or_default()
+}
+
+// Begin Passkey Registration Handler
+#[utoipa::path(
+    post,
+    path = "/users/passkeys/register/start",
+    tag = "passkeys",
+    responses(
+        (status = 200, description = "Options for navigator.credentials.create", body = PasskeyChallenge<CreationOptions>),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 500, description = "Unexpected server error", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn register_start(
+    auth_user: AuthenticatedUser,
+    db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
+) -> HttpResponse {
+    let user = match find_user_by_username(db.get_ref(), &auth_user.0.sub).await {
+        Some(user) => user,
+        None => return error_response(StatusCode::NOT_FOUND, "User not found"),
+    };
+
+    let rp = RelyingParty::from_config(&config);
+    let challenge = match start_ceremony(db.get_ref(), REGISTRATION, Some(user.id)).await {
+        Ok(challenge) => challenge,
+        Err(err) => return HttpResponse::InternalServerError().json(format!("Error: {:?}", err)),
+    };
+    let existing = credentials_of(db.get_ref(), user.id).await;
+
+    HttpResponse::Ok().json(PasskeyChallenge {
+        state: webauthn::encode(&challenge),
+        public_key: CreationOptions {
+            rp: RelyingPartyInfo {
+                id: rp.id,
+                name: rp.name,
+            },
+            user: PasskeyUserInfo {
+                id: webauthn::encode(&user.id.to_be_bytes()),
+                name: user.username.clone(),
+                display_name: format!("{} {}", user.first_name, user.last_name),
+            },
+            challenge: webauthn::encode(&challenge),
+            pub_key_cred_params: vec![CredentialParameter {
+                kind: "public-key".to_string(),
+                alg: COSE_ALG_ES256,
+            }],
+            timeout: (CEREMONY_TIMEOUT_SECS * 1000) as u32,
+            attestation: "none".to_string(),
+            exclude_credentials: existing.iter().map(descriptor).collect(),
+            authenticator_selection: AuthenticatorSelection {
+                resident_key: "preferred".to_string(),
+                user_verification: "required".to_string(),
+            },
+        },
+    })
+}
+
+// Finish Passkey Registration Handler
+#[utoipa::path(
+    post,
+    path = "/users/passkeys/register/finish",
+    tag = "passkeys",
+    request_body = FinishPasskeyRegistration,
+    responses(
+        (status = 200, description = "Passkey registered", body = ResponseMessage),
+        (status = 400, description = "Invalid or expired ceremony", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 500, description = "Unexpected server error", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn register_finish(
+    auth_user: AuthenticatedUser,
+    db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
+    form: web::Json<FinishPasskeyRegistration>,
+) -> HttpResponse {
+    let user = match find_user_by_username(db.get_ref(), &auth_user.0.sub).await {
+        Some(user) => user,
+        None => return error_response(StatusCode::NOT_FOUND, "User not found"),
+    };
+    let challenge = match finish_ceremony(db.get_ref(), &form.state, REGISTRATION).await {
+        Ok(Some((challenge, Some(user_id)))) if user_id == user.id => challenge,
+        Ok(_) => return error_response(StatusCode::BAD_REQUEST, "Invalid or expired passkey challenge"),
+        Err(err) => return HttpResponse::InternalServerError().json(format!("Error: {:?}", err)),
+    };
+
+    let response = &form.credential.response;
+    let (client_data, attestation) = match (
+        webauthn::decode(&response.client_data_json),
+        webauthn::decode(&response.attestation_object),
+    ) {
+        (Ok(client_data), Ok(attestation)) => (client_data, attestation),
+        _ => return error_response(StatusCode::BAD_REQUEST, "Invalid base64url data"),
+    };
+
+    let credential = match webauthn::verify_registration(
+        &RelyingParty::from_config(&config),
+        &challenge,
+        &client_data,
+        &attestation,
+    ) {
+        Ok(credential) => credential,
+        Err(err) => return error_response(StatusCode::BAD_REQUEST, err),
+    };
+    if webauthn::decode(&form.credential.raw_id).ok().as_deref() != Some(&credential.credential_id[..]) {
+        return error_response(StatusCode::BAD_REQUEST, "Credential id mismatch");
+    }
+
+    if WebauthnCredentials::find()
+        .filter(webauthn_credentials::Column::CredentialId.eq(credential.credential_id.clone()))
+        .one(db.get_ref())
+        .await
+        .unwrap()
+        .is_some()
+    {
+        return error_response(StatusCode::CONFLICT, "Passkey already registered");
+    }
+
+    let new_credential = webauthn_credentials::ActiveModel {
+        user_id: Set(user.id),
+        credential_id: Set(credential.credential_id),
+        public_key: Set(credential.public_key),
+        sign_count: Set(credential.sign_count as i64),
+        name: Set(form.name.clone().filter(|name| !name.trim().is_empty())),
+        ..Default::default()
+    };
+
+    match WebauthnCredentials::insert(new_credential).exec(db.get_ref()).await {
+        Ok(_) => HttpResponse::Ok().json(ResponseMessage {
+            message: "Passkey registered successfully".to_string(),
+        }),
+        Err(err) => HttpResponse::InternalServerError().body(format!("Error: {:?}", err)),
+    }
+}
+
+// Begin Passkey Login Handler
+#[utoipa::path(
+    post,
+    path = "/users/passkeys/login/start",
+    tag = "passkeys",
+    request_body = StartPasskeyLogin,
+    responses(
+        (status = 200, description = "Options for navigator.credentials.get", body = PasskeyChallenge<RequestOptions>),
+        (status = 500, description = "Unexpected server error", body = ErrorResponse),
+    )
+)]
+pub async fn login_start(
+    db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
+    form: web::Json<StartPasskeyLogin>,
+) -> HttpResponse {
+    // Without a username the browser offers its discoverable credentials for this site.
+    let (user_id, allow_credentials) = match form.username.as_ref().filter(|s| !s.trim().is_empty()) {
+        Some(username) => match find_user_by_username(db.get_ref(), username).await {
+            Some(user) => {
+                let credentials = credentials_of(db.get_ref(), user.id).await;
+                (Some(user.id), credentials.iter().map(descriptor).collect())
+            }
+            None => (None, Vec::new()),
+        },
+        None => (None, Vec::new()),
+    };
+
+    let rp = RelyingParty::from_config(&config);
+    let challenge = match start_ceremony(db.get_ref(), AUTHENTICATION, user_id).await {
+        Ok(challenge) => challenge,
+        Err(err) => return HttpResponse::InternalServerError().json(format!("Error: {:?}", err)),
+    };
+
+    HttpResponse::Ok().json(PasskeyChallenge {
+        state: webauthn::encode(&challenge),
+        public_key: RequestOptions {
+            challenge: webauthn::encode(&challenge),
+            rp_id: rp.id,
+            timeout: (CEREMONY_TIMEOUT_SECS * 1000) as u32,
+            allow_credentials,
+            user_verification: "required".to_string(),
+        },
+    })
+}
+
+// Finish Passkey Login Handler
+#[utoipa::path(
+    post,
+    path = "/users/passkeys/login/finish",
+    tag = "passkeys",
+    request_body = FinishPasskeyLogin,
+    responses(
+        (status = 200, description = "Signed in", body = LoginResponse),
+        (status = 401, description = "Passkey not recognised or assertion invalid", body = ErrorResponse),
+        (status = 403, description = "Account suspended or banned", body = AccountRestrictedResponse),
+        (status = 500, description = "Unexpected server error", body = ErrorResponse),
+    )
+)]
+pub async fn login_finish(
+    req: HttpRequest,
+    db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
+    form: web::Json<FinishPasskeyLogin>,
+) -> HttpResponse {
+    let response = finish_passkey_login(req, db, config, form).await;
+    metrics::record_login("passkey", response.status().is_success());
+    response
+}
+
+async fn finish_passkey_login(
+    req: HttpRequest,
+    db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
+    form: web::Json<FinishPasskeyLogin>,
+) -> HttpResponse {
+    let invalid = || error_response(StatusCode::UNAUTHORIZED, "Invalid passkey");
+
+    let (challenge, expected_user) = match finish_ceremony(db.get_ref(), &form.state, AUTHENTICATION).await {
+        Ok(Some(state)) => state,
+        Ok(None) => return error_response(StatusCode::BAD_REQUEST, "Invalid or expired passkey challenge"),
+        Err(err) => return HttpResponse::InternalServerError().json(format!("Error: {:?}", err)),
+    };
+
+    let credential = &form.credential;
+    let decoded = (
+        webauthn::decode(&credential.raw_id),
+        webauthn::decode(&credential.response.client_data_json),
+        webauthn::decode(&credential.response.authenticator_data),
+        webauthn::decode(&credential.response.signature),
+    );
+    let (raw_id, client_data, auth_data, signature) = match decoded {
+        (Ok(raw_id), Ok(client_data), Ok(auth_data), Ok(signature)) => {
+            (raw_id, client_data, auth_data, signature)
+        }
+        _ => return error_response(StatusCode::BAD_REQUEST, "Invalid base64url data"),
+    };
+
+    let stored = match WebauthnCredentials::find()
+        .filter(webauthn_credentials::Column::CredentialId.eq(raw_id))
+        .one(db.get_ref())
+        .await
+    {
+        Ok(Some(stored)) => stored,
+        Ok(None) => return invalid(),
+        Err(err) => return HttpResponse::InternalServerError().json(format!("Error: {:?}", err)),
+    };
+    if expected_user.is_some_and(|user_id| user_id != stored.user_id) {
+        return invalid();
+    }
+    if let Some(handle) = &credential.response.user_handle {
+        if webauthn::decode(handle).ok() != Some(stored.user_id.to_be_bytes().to_vec()) {
+            return invalid();
+        }
+    }
+
+    let sign_count = match webauthn::verify_authentication(
+        &RelyingParty::from_config(&config),
+        &challenge,
+        &stored.public_key,
+        stored.sign_count as u32,
+        &client_data,
+        &auth_data,
+        &signature,
+    ) {
+        Ok(sign_count) => sign_count,
+        Err(err) => {
+            log::debug!("Passkey assertion rejected: {}", err);
+            return invalid();
+        }
+    };
+
+    let user_id = stored.user_id;
+    let mut credential_model: webauthn_credentials::ActiveModel = stored.into();
+    credential_model.sign_count = Set(sign_count as i64);
+    credential_model.last_used_at = Set(Some(Utc::now().into()));
+    if let Err(err) = credential_model.update(db.get_ref()).await {
+        return HttpResponse::InternalServerError().json(format!("Error: {:?}", err));
+    }
+
+    match Users::find_live_by_id(user_id).one(db.get_ref()).await {
+        Ok(Some(user)) => login_response(db.get_ref(), &req, &user).await,
+        Ok(None) => invalid(),
+        Err(err) => HttpResponse::InternalServerError().json(format!("Error: {:?}", err)),
+    }
+}
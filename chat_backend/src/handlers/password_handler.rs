@@ -0,0 +1,202 @@
+use actix_web::{web, HttpResponse};
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::{Duration, Utc};
+use sea_orm::sea_query::Expr;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, Set,
+    TransactionTrait,
+};
+use sha2::{Digest, Sha256};
+use crate::config::Config;
+use crate::entities::prelude::{PasswordResetTokens, UserSessions, Users};
+use crate::entities::{password_reset_tokens, user_sessions, users};
+use crate::handlers::user_handler::normalize_email;
+use crate::mailer::{Email, Mailer};
+use crate::models::user_models::{ErrorResponse, ForgotPassword, ResetPassword, ResponseMessage};
+use crate::utils::password::hash_password;
+use crate::utils::soft_delete::SoftDelete;
+
+fn hash_token(token: &str) -> String {
+    Sha256::digest(token.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+// Forgot Password Handler
+#[utoipa::path(
+    post,
+    path = "/users/password/forgot",
+    tag = "auth",
+    request_body = ForgotPassword,
+    responses(
+        (status = 200, description = "A reset link is mailed if the account exists", body = ResponseMessage),
+        (status = 500, description = "Unexpected server error", body = ErrorResponse),
+    )
+)]
+pub async fn forgot_password(
+    db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
+    mailer: web::Data<dyn Mailer>,
+    form: web::Json<ForgotPassword>,
+) -> HttpResponse {
+    // The response never reveals whether an account uses this address.
+    let accepted = HttpResponse::Ok().json(ResponseMessage {
+        message: "If an account uses that email, a reset link has been sent".to_string(),
+    });
+
+    let email = match normalize_email(Some(form.email.clone())) {
+        Some(email) => email,
+        None => return accepted,
+    };
+    let user = match Users::find_live()
+        .filter(users::Column::Email.eq(email.as_str()))
+        .one(db.get_ref())
+        .await
+    {
+        Ok(Some(user)) => user,
+        Ok(None) => return accepted,
+        Err(err) => return HttpResponse::InternalServerError().json(format!("Error: {:?}", err)),
+    };
+
+    let mut raw = [0u8; 32];
+    OsRng.fill_bytes(&mut raw);
+    let token = URL_SAFE_NO_PAD.encode(raw);
+
+    // Only the newest link stays valid.
+    if let Err(err) = PasswordResetTokens::delete_many()
+        .filter(password_reset_tokens::Column::UserId.eq(user.id))
+        .filter(password_reset_tokens::Column::UsedAt.is_null())
+        .exec(db.get_ref())
+        .await
+    {
+        return HttpResponse::InternalServerError().json(format!("Error: {:?}", err));
+    }
+
+    let reset_token = password_reset_tokens::ActiveModel {
+        user_id: Set(user.id),
+        token_hash: Set(hash_token(&token)),
+        expires_at: Set((Utc::now() + Duration::minutes(config.password_reset_ttl_minutes)).into()),
+        ..Default::default()
+    };
+    if let Err(err) = reset_token.insert(db.get_ref()).await {
+        return HttpResponse::InternalServerError().json(format!("Error: {:?}", err));
+    }
+
+    let link = format!(
+        "{}/reset-password?token={}",
+        config.frontend_url.trim_end_matches('/'),
+        token
+    );
+    let message = Email {
+        to: email,
+        subject: "Reset your BlockChat password".to_string(),
+        body: format!(
+            "Hi {},\n\nUse the link below to choose a new password. It expires in {} minutes and works once.\n\n{}\n\nIf you did not ask for this, you can ignore this email.\n",
+            user.first_name, config.password_reset_ttl_minutes, link
+        ),
+    };
+    if let Err(err) = mailer.send(message).await {
+        log::error!("Failed to send password reset email to user {}: {}", user.id, err);
+    }
+
+    accepted
+}
+
+// Reset Password Handler
+#[utoipa::path(
+    post,
+    path = "/users/password/reset",
+    tag = "auth",
+    request_body = ResetPassword,
+    responses(
+        (status = 200, description = "Password changed", body = ResponseMessage),
+        (status = 400, description = "Invalid or expired token", body = ErrorResponse),
+        (status = 500, description = "Unexpected server error", body = ErrorResponse),
+    )
+)]
+pub async fn reset_password(
+    db: web::Data<DatabaseConnection>,
+    form: web::Json<ResetPassword>,
+) -> HttpResponse {
+    let invalid = || {
+        HttpResponse::BadRequest().json(ResponseMessage {
+            message: "Invalid or expired reset token".to_string(),
+        })
+    };
+
+    if form.password.is_empty() {
+        return HttpResponse::BadRequest().json(ResponseMessage {
+            message: "Missing required fields".to_string(),
+        });
+    }
+
+    let reset_token = match PasswordResetTokens::find()
+        .filter(password_reset_tokens::Column::TokenHash.eq(hash_token(form.token.trim())))
+        .filter(password_reset_tokens::Column::UsedAt.is_null())
+        .filter(password_reset_tokens::Column::ExpiresAt.gt(Utc::now()))
+        .one(db.get_ref())
+        .await
+    {
+        Ok(Some(token)) => token,
+        Ok(None) => return invalid(),
+        Err(err) => return HttpResponse::InternalServerError().json(format!("Error: {:?}", err)),
+    };
+
+    let hashed_password = match hash_password(&form.password) {
+        Ok(hash) => hash,
+        Err(err) => {
+            return HttpResponse::InternalServerError().json(ResponseMessage {
+                message: format!("Password hashing error: {:?}", err),
+            })
+        }
+    };
+
+    let txn = match db.begin().await {
+        Ok(txn) => txn,
+        Err(err) => return HttpResponse::InternalServerError().json(format!("Error: {:?}", err)),
+    };
+
+    // Claiming the token is conditional on it still being unused, so two concurrent
+    // requests cannot both spend it.
+    let claimed = PasswordResetTokens::update_many()
+        .col_expr(password_reset_tokens::Column::UsedAt, Expr::value(Utc::now()))
+        .filter(password_reset_tokens::Column::Id.eq(reset_token.id))
+        .filter(password_reset_tokens::Column::UsedAt.is_null())
+        .exec(&txn)
+        .await;
+    match claimed {
+        Ok(result) if result.rows_affected == 1 => {}
+        Ok(_) => return invalid(),
+        Err(err) => return HttpResponse::InternalServerError().json(format!("Error: {:?}", err)),
+    }
+
+    let updated = Users::update_many()
+        .col_expr(users::Column::Password, Expr::value(hashed_password))
+        .filter(users::Column::Id.eq(reset_token.user_id))
+        .exec(&txn)
+        .await;
+    if let Err(err) = updated {
+        return HttpResponse::InternalServerError().json(format!("Error: {:?}", err));
+    }
+
+    // Whoever held the old password should not keep their sessions.
+    let revoked = UserSessions::update_many()
+        .col_expr(user_sessions::Column::RevokedAt, Expr::value(Utc::now()))
+        .filter(user_sessions::Column::UserId.eq(reset_token.user_id))
+        .filter(user_sessions::Column::RevokedAt.is_null())
+        .exec(&txn)
+        .await;
+    if let Err(err) = revoked {
+        return HttpResponse::InternalServerError().json(format!("Error: {:?}", err));
+    }
+
+    match txn.commit().await {
+        Ok(_) => HttpResponse::Ok().json(ResponseMessage {
+            message: "Password reset successfully".to_string(),
+        }),
+        Err(err) => HttpResponse::InternalServerError().json(format!("Error: {:?}", err)),
+    }
+}
@@ -0,0 +1,186 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+use chrono::Utc;
+use sea_orm::sea_query::Expr;
+use sea_orm::{ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, QueryOrder, TransactionTrait};
+use serde_json::json;
+use crate::entities::prelude::{UserSessions, Users};
+use crate::entities::{user_sessions, users};
+use crate::models::session_models::*;
+use crate::models::user_models::{ErrorResponse, ResponseMessage};
+use crate::utils::audit::{self, AuditContext, AuditEvent};
+use crate::utils::check_auth_user::AuthenticatedUser;
+use crate::utils::soft_delete::SoftDelete;
+
+async fn caller_id(db: &DatabaseConnection, auth_user: &AuthenticatedUser) -> Option<i32> {
+    Users::find_live()
+        .filter(users::Column::Username.eq(&auth_user.0.sub))
+        .one(db)
+        .await
+        .ok()
+        .flatten()
+        .map(|user| user.id)
+}
+
+async fn list_sessions(db: &DatabaseConnection, user_id: i32, current_jti: &str) -> HttpResponse {
+    match UserSessions::find()
+        .filter(user_sessions::Column::UserId.eq(user_id))
+        .filter(user_sessions::Column::RevokedAt.is_null())
+        .filter(user_sessions::Column::ExpiresAt.gt(Utc::now()))
+        .order_by_desc(user_sessions::Column::LastSeenAt)
+        .all(db)
+        .await
+    {
+        Ok(sessions) => HttpResponse::Ok().json(GetAllSessionsResponse {
+            sessions: sessions
+                .into_iter()
+                .map(|session| SessionResponse::new(session, current_jti))
+                .collect(),
+        }),
+        Err(err) => HttpResponse::InternalServerError().json(format!("Error: {:?}", err)),
+    }
+}
+
+async fn revoke_session(db: &DatabaseConnection, user_id: i32, session_id: i32) -> HttpResponse {
+    match UserSessions::update_many()
+        .col_expr(user_sessions::Column::RevokedAt, Expr::value(Utc::now()))
+        .filter(user_sessions::Column::Id.eq(session_id))
+        .filter(user_sessions::Column::UserId.eq(user_id))
+        .filter(user_sessions::Column::RevokedAt.is_null())
+        .exec(db)
+        .await
+    {
+        Ok(result) if result.rows_affected > 0 => HttpResponse::Ok().json(ResponseMessage {
+            message: "Session revoked successfully".to_string(),
+        }),
+        Ok(_) => HttpResponse::NotFound().json(ResponseMessage {
+            message: "Session not found".to_string(),
+        }),
+        Err(err) => HttpResponse::InternalServerError().json(format!("Error: {:?}", err)),
+    }
+}
+
+// Get Own Sessions Handler
+#[utoipa::path(
+    get,
+    path = "/users/sessions",
+    tag = "sessions",
+    responses(
+        (status = 200, description = "Active sessions of the caller", body = GetAllSessionsResponse),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 500, description = "Unexpected server error", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn get_my_sessions(
+    auth_user: AuthenticatedUser,
+    db: web::Data<DatabaseConnection>,
+) -> HttpResponse {
+    match caller_id(db.get_ref(), &auth_user).await {
+        Some(user_id) => list_sessions(db.get_ref(), user_id, &auth_user.0.jti).await,
+        None => HttpResponse::NotFound().json(ResponseMessage {
+            message: "User not found".to_string(),
+        }),
+    }
+}
+
+// Revoke Own Session Handler
+#[utoipa::path(
+    delete,
+    path = "/users/sessions/{session_id}",
+    tag = "sessions",
+    params(("session_id" = i32, Path, description = "Session id")),
+    responses(
+        (status = 200, description = "Session revoked", body = ResponseMessage),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 404, description = "Session not found", body = ErrorResponse),
+        (status = 500, description = "Unexpected server error", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn revoke_my_session(
+    auth_user: AuthenticatedUser,
+    db: web::Data<DatabaseConnection>,
+    session_id: web::Path<i32>,
+) -> HttpResponse {
+    match caller_id(db.get_ref(), &auth_user).await {
+        Some(user_id) => revoke_session(db.get_ref(), user_id, session_id.into_inner()).await,
+        None => HttpResponse::NotFound().json(ResponseMessage {
+            message: "User not found".to_string(),
+        }),
+    }
+}
+
+// Get User Sessions Handler (admin)
+#[utoipa::path(
+    get,
+    path = "/users/{id}/sessions",
+    tag = "sessions",
+    params(("id" = i32, Path, description = "User id")),
+    responses(
+        (status = 200, description = "Active sessions of the user", body = GetAllSessionsResponse),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 500, description = "Unexpected server error", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn get_user_sessions(
+    auth_user: AuthenticatedUser,
+    db: web::Data<DatabaseConnection>,
+    user_id: web::Path<i32>,
+) -> HttpResponse {
+    list_sessions(db.get_ref(), user_id.into_inner(), &auth_user.0.jti).await
+}
+
+// Revoke User Session Handler (admin)
+#[utoipa::path(
+    delete,
+    path = "/users/{id}/sessions/{session_id}",
+    tag = "sessions",
+    params(("id" = i32, Path, description = "User id"), ("session_id" = i32, Path, description = "Session id")),
+    responses(
+        (status = 200, description = "Session revoked", body = ResponseMessage),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 404, description = "Session not found", body = ErrorResponse),
+        (status = 500, description = "Unexpected server error", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn revoke_user_session(
+    auth_user: AuthenticatedUser,
+    req: HttpRequest,
+    db: web::Data<DatabaseConnection>,
+    path: web::Path<(i32, i32)>,
+) -> HttpResponse {
+    let (user_id, session_id) = path.into_inner();
+    let context = AuditContext::new(&req, caller_id(db.get_ref(), &auth_user).await);
+    let revoked = async {
+        let txn = db.begin().await?;
+        let result = UserSessions::update_many()
+            .col_expr(user_sessions::Column::RevokedAt, Expr::value(Utc::now()))
+            .filter(user_sessions::Column::Id.eq(session_id))
+            .filter(user_sessions::Column::UserId.eq(user_id))
+            .filter(user_sessions::Column::RevokedAt.is_null())
+            .exec(&txn)
+            .await?;
+        if result.rows_affected > 0 {
+            audit::record(&txn, &context, AuditEvent {
+                action: "session.revoke",
+                target_type: "user",
+                target_id: Some(user_id),
+                changes: Some(json!({ "session_id": session_id })),
+            })
+            .await?;
+        }
+        txn.commit().await?;
+        Ok::<_, DbErr>(result.rows_affected)
+    };
+    match revoked.await {
+        Ok(rows) if rows > 0 => HttpResponse::Ok().json(ResponseMessage {
+            message: "Session revoked successfully".to_string(),
+        }),
+        Ok(_) => HttpResponse::NotFound().json(ResponseMessage {
+            message: "Session not found".to_string(),
+        }),
+        Err(err) => HttpResponse::InternalServerError().json(format!("Error: {:?}", err)),
+    }
+}
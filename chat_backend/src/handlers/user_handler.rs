@@ -6,24 +6,35 @@ use base64::Engine;
 use futures::{StreamExt, TryStreamExt};
 use sea_orm::prelude::Expr;
 use sea_orm::sea_query::Func;
-use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, Set};
-use crate::entities::prelude::{ChatParticipants, Chats};
-use crate::entities::{users, prelude::Users};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, Condition, DatabaseConnection, EntityTrait, QueryFilter, Set,
+    TransactionTrait,
+};
+use crate::config::{Config, RegistrationMode};
+use crate::entities::prelude::{ChatParticipants, Chats, InviteCodes, UserSessions};
+use crate::entities::sea_orm_active_enums::AccountStatus;
+use crate::entities::{invite_codes, user_sessions, users, prelude::Users};
+use crate::handlers::verification_handler::send_verification_email;
+use crate::mailer::Mailer;
 use crate::models::token_model::Claims;
+use crate::utils::audit::{self, AuditContext, AuditEvent};
 use crate::utils::check_auth_user::AuthenticatedUser;
+use crate::metrics;
+use crate::utils::moderation::{active_restriction, current_status};
+use crate::utils::password::{hash_password, verify_password};
+use crate::utils::sessions::record_session;
+use crate::utils::soft_delete::SoftDelete;
 use crate::{merge_update, merge_update_optional};
-use argon2::{Argon2, PasswordHash, PasswordHasher, PasswordVerifier};
-use argon2::password_hash::rand_core::OsRng;
-use argon2::password_hash::SaltString;
 use jsonwebtoken::{encode, Header, EncodingKey};
 use chrono::{Utc, Duration};
 use crate::models::user_models::*;
 
 // Load JWT secret key at runtime
-fn get_secret() -> String {
+pub fn get_secret() -> String {
     std::env::var("JWT_SECRET").expect("JWT_SECRET must be set")
 }
 
+#[tracing::instrument(skip(db))]
 async fn get_user_chat_info(db: &DatabaseConnection, user_id: i32) -> Vec<ChatInfo> {
     use crate::entities::chat_participants::Column as CpColumn;
     let mut infos = Vec::new();
@@ -36,31 +47,71 @@ async fn get_user_chat_info(db: &DatabaseConnection, user_id: i32) -> Vec<ChatIn
         // For each participation record, load the chat along with its author.
         for cp in chat_parts {
             // We use find_by_id on Chats and then find_also_related on Users (the chat's author).
-            if let Ok(Some((chat, maybe_author))) = Chats::find_by_id(cp.chat_id)
+            // A chat keeps its place in the list after its author's account is purged.
+            if let Ok(Some((chat, maybe_author))) = Chats::find_live_by_id(cp.chat_id)
                 .find_also_related(users::Entity)
                 .one(db)
                 .await
             {
-                if let Some(author) = maybe_author {
-                    infos.push(ChatInfo {
-                        chat_name: chat.name,
-                        author_id: chat.author_id,
-                        author_username: author.username,
-                    });
-                }
+                infos.push(ChatInfo {
+                    chat_name: chat.name,
+                    author_id: chat.author_id,
+                    author_username: maybe_author.map(|author| author.username),
+                });
             }
         }
     }
     infos
 }
 
+// Trims and lowercases an email address; blank values count as absent.
+pub fn normalize_email(email: Option<String>) -> Option<String> {
+    email
+        .map(|e| e.trim().to_lowercase())
+        .filter(|e| !e.is_empty())
+}
+
+async fn email_taken(db: &DatabaseConnection, email: &Option<String>) -> bool {
+    match email {
+        Some(email) => Users::find()
+            .filter(users::Column::Email.eq(email.as_str()))
+            .one(db)
+            .await
+            .unwrap()
+            .is_some(),
+        None => false,
+    }
+}
+
 // Registration Handler
+#[utoipa::path(
+    post,
+    path = "/users/register",
+    tag = "auth",
+    request_body = RegisterUser,
+    responses(
+        (status = 200, description = "Account created", body = ResponseMessage),
+        (status = 400, description = "Invalid email address", body = ErrorResponse),
+        (status = 403, description = "A valid invite code is required", body = ErrorResponse),
+        (status = 409, description = "Username or email already taken", body = ErrorResponse),
+        (status = 500, description = "Unexpected server error", body = ErrorResponse),
+    )
+)]
 pub async fn register(
     db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
+    mailer: web::Data<dyn Mailer>,
     form: web::Json<RegisterUser>,
 ) -> HttpResponse {
     use sea_orm::ColumnTrait;
 
+    let mode = config.registration_mode;
+    if mode == RegistrationMode::Closed {
+        return HttpResponse::Forbidden().json(ResponseMessage {
+            message: "Registration is closed".to_string(),
+        });
+    }
+
     // Check if username already exists
     if Users::find()
         .filter(users::Column::Username.eq(&form.username))
@@ -74,14 +125,20 @@ pub async fn register(
         });
     }
 
+    let email = normalize_email(form.email.clone());
+    if mode == RegistrationMode::EmailVerified && email.is_none() {
+        return HttpResponse::BadRequest().json(ResponseMessage {
+            message: "An email address is required".to_string(),
+        });
+    }
+    if email_taken(db.get_ref(), &email).await {
+        return HttpResponse::Conflict().json(ResponseMessage {
+            message: "Email already in use".to_string(),
+        });
+    }
+
     // Hash the password with a secure salt
-    let password = form.password.as_bytes();
-    let mut rng = OsRng;
-    let salt = SaltString::generate(&mut rng);
-    let hashed_password = Argon2::default()
-        .hash_password(password, &salt)
-        .expect("Failed to hash password")
-        .to_string();
+    let hashed_password = hash_password(&form.password).expect("Failed to hash password");
 
     // Create new user
     let new_user = users::ActiveModel {
@@ -89,19 +146,84 @@ pub async fn register(
         last_name: Set(form.last_name.clone()),
         username: Set(form.username.clone()),
         password: Set(hashed_password),
+        email: Set(email),
         role_id: Set(Some(3)), // Assuming "user" role has id 3
+        status: Set(if mode == RegistrationMode::EmailVerified {
+            AccountStatus::Unverified
+        } else {
+            AccountStatus::Active
+        }),
         ..Default::default()
     };
 
-    match users::Entity::insert(new_user).exec(db.get_ref()).await {
-        Ok(_) => HttpResponse::Ok().json(ResponseMessage {
-            message: "User registered successfully".to_string(),
-        }),
-        Err(err) => HttpResponse::InternalServerError().body(format!("Error: {:?}", err)),
+    let txn = match db.begin().await {
+        Ok(txn) => txn,
+        Err(err) => return HttpResponse::InternalServerError().body(format!("Error: {:?}", err)),
+    };
+
+    // Spend one use of the invite in the same transaction that creates the account.
+    if mode == RegistrationMode::InviteCode {
+        let code = form.invite_code.as_deref().map(str::trim).unwrap_or("");
+        let now = Utc::now();
+        let redeemed = InviteCodes::update_many()
+            .col_expr(
+                invite_codes::Column::Uses,
+                Expr::col(invite_codes::Column::Uses).add(1),
+            )
+            .filter(invite_codes::Column::Code.eq(code))
+            .filter(invite_codes::Column::RevokedAt.is_null())
+            .filter(
+                Condition::any()
+                    .add(invite_codes::Column::ExpiresAt.is_null())
+                    .add(invite_codes::Column::ExpiresAt.gt(now)),
+            )
+            .filter(
+                Condition::any()
+                    .add(invite_codes::Column::MaxUses.is_null())
+                    .add(Expr::col(invite_codes::Column::Uses).lt(Expr::col(invite_codes::Column::MaxUses))),
+            )
+            .exec(&txn)
+            .await;
+        match redeemed {
+            Ok(result) if result.rows_affected == 1 => {}
+            Ok(_) => {
+                return HttpResponse::Forbidden().json(ResponseMessage {
+                    message: "Invalid or expired invite code".to_string(),
+                })
+            }
+            Err(err) => return HttpResponse::InternalServerError().body(format!("Error: {:?}", err)),
+        }
+    }
+
+    let user = match new_user.insert(&txn).await {
+        Ok(user) => user,
+        Err(err) => return HttpResponse::InternalServerError().body(format!("Error: {:?}", err)),
+    };
+    if let Err(err) = txn.commit().await {
+        return HttpResponse::InternalServerError().body(format!("Error: {:?}", err));
+    }
+
+    if mode == RegistrationMode::EmailVerified {
+        send_verification_email(config.get_ref(), mailer.get_ref(), &user).await;
+        return HttpResponse::Ok().json(ResponseMessage {
+            message: "User registered successfully, check your email to verify your account".to_string(),
+        });
+    }
+
+    HttpResponse::Ok().json(ResponseMessage {
+        message: "User registered successfully".to_string(),
+    })
+}
+
+// Accounts awaiting email verification get a token that only unlocks the resend endpoint.
+fn token_role(user: &users::Model) -> String {
+    if current_status(user) == AccountStatus::Unverified {
+        return "unverified".to_string();
     }
+    role_name_from_id(user.role_id)
 }
 
-fn role_name_from_id(role_id: Option<i32>) -> String {
+pub(crate) fn role_name_from_id(role_id: Option<i32>) -> String {
     match role_id {
         Some(2) => "admin".to_string(),
         Some(3) => "user".to_string(),
@@ -110,14 +232,37 @@ fn role_name_from_id(role_id: Option<i32>) -> String {
 }
 
 // Login Handler
+#[utoipa::path(
+    post,
+    path = "/users/login",
+    tag = "auth",
+    request_body = LoginUser,
+    responses(
+        (status = 200, description = "Signed in", body = LoginResponse),
+        (status = 401, description = "Invalid credentials", body = ErrorResponse),
+        (status = 403, description = "Account suspended or banned", body = AccountRestrictedResponse),
+        (status = 500, description = "Unexpected server error", body = ErrorResponse),
+    )
+)]
 pub async fn login(
+    req: HttpRequest,
+    db: web::Data<DatabaseConnection>,
+    form: web::Json<LoginUser>,
+) -> HttpResponse {
+    let response = password_login(req, db, form).await;
+    metrics::record_login("password", response.status().is_success());
+    response
+}
+
+async fn password_login(
+    req: HttpRequest,
     db: web::Data<DatabaseConnection>,
     form: web::Json<LoginUser>,
 ) -> HttpResponse {
     use sea_orm::ColumnTrait;
 
     // Find the user by username
-    let user = match Users::find()
+    let user = match Users::find_live()
         .filter(users::Column::Username.eq(&form.username))
         .one(db.get_ref())
         .await
@@ -131,18 +276,38 @@ pub async fn login(
     };
 
     // Verify the password
-    let parsed_hash = PasswordHash::new(&user.password).expect("Invalid password hash");
-    if !Argon2::default().verify_password(form.password.as_bytes(), &parsed_hash).is_ok() {
+    if !verify_password(&form.password, &user.password) {
         return HttpResponse::Unauthorized().json(ResponseMessage {
             message: "Invalid username or password".to_string(),
         });
     }
 
-    // Generate a JWT token
+    login_response(db.get_ref(), &req, &user).await
+}
+
+// Opens a session and issues its JWT; shared by password and passkey login.
+// Suspended and banned accounts are turned away here with the reason and expiry.
+pub async fn login_response(db: &DatabaseConnection, req: &HttpRequest, user: &users::Model) -> HttpResponse {
+    if let Some(restriction) = active_restriction(user) {
+        return HttpResponse::Forbidden().json(AccountRestrictedResponse {
+            message: restriction.message(),
+            status: restriction.status,
+            reason: restriction.reason,
+            until: restriction.until,
+        });
+    }
+
+    let expires_at = Utc::now() + Duration::seconds(3600); // 1 hour expiration
+    let jti = uuid::Uuid::new_v4().to_string();
+    if let Err(err) = record_session(db, req, user.id, &jti, expires_at).await {
+        return HttpResponse::InternalServerError().json(format!("Error: {:?}", err));
+    }
+
     let claims = Claims {
         sub: user.username.clone(),
-        role: role_name_from_id(user.role_id),
-        exp: (Utc::now() + Duration::seconds(3600)).timestamp() as usize, // 1 hour expiration
+        role: token_role(user),
+        exp: expires_at.timestamp() as usize,
+        jti,
     };
 
     let token = encode(
@@ -152,14 +317,29 @@ pub async fn login(
     )
     .unwrap();
 
-    HttpResponse::Ok().json(serde_json::json!({
-        "message": "Login successful",
-        "token": token,
-    }))
+    HttpResponse::Ok().json(LoginResponse {
+        message: "Login successful".to_string(),
+        token,
+    })
 }
 
 //Create User Handler
+#[utoipa::path(
+    post,
+    path = "/users/create",
+    tag = "users",
+    request_body(content((CreateUser = "application/json"), (CreateUser = "multipart/form-data"))),
+    responses(
+        (status = 200, description = "User created", body = ResponseMessage),
+        (status = 400, description = "Malformed request", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 409, description = "Username or email already taken", body = ErrorResponse),
+        (status = 500, description = "Unexpected server error", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = []))
+)]
 pub async fn create_user(
+    auth_user: AuthenticatedUser,
     db: web::Data<DatabaseConnection>,
     req: HttpRequest,
     mut payload: web::Payload,
@@ -175,6 +355,7 @@ pub async fn create_user(
         last_name: String::new(),
         username: String::new(),
         password: String::new(),
+        email: None,
         role_id: None, // Optional – default to 3 (user) if not provided
         avatar: None,
     };
@@ -215,6 +396,7 @@ pub async fn create_user(
                     "last_name" => user_data.last_name = value,
                     "username" => user_data.username = value,
                     "password" => user_data.password = value,
+                    "email" => user_data.email = Some(value),
                     "role_id" => {
                         if let Ok(parsed) = value.parse::<i32>() {
                             user_data.role_id = Some(parsed);
@@ -289,14 +471,15 @@ pub async fn create_user(
       });
   }
 
+  let email = normalize_email(user_data.email);
+  if email_taken(db.get_ref(), &email).await {
+      return HttpResponse::Conflict().json(ResponseMessage {
+          message: "Email already in use".to_string(),
+      });
+  }
+
   // Hash the password.
-  let password_bytes = user_data.password.as_bytes();
-  let mut rng = OsRng;
-  let salt = SaltString::generate(&mut rng);
-  let hashed_password = Argon2::default()
-      .hash_password(password_bytes, &salt)
-      .expect("Failed to hash password")
-      .to_string();
+  let hashed_password = hash_password(&user_data.password).expect("Failed to hash password");
 
   // Create a new ActiveModel. If role_id is not provided, default to 3 ("user").
   let new_user_model = users::ActiveModel {
@@ -304,12 +487,31 @@ pub async fn create_user(
       last_name: Set(user_data.last_name),
       username: Set(user_data.username),
       password: Set(hashed_password),
+      email: Set(email),
       role_id: Set(user_data.role_id.or(Some(3))),
       avatar: Set(avatar_bytes), // Will be None if not provided.
       ..Default::default()
   };
 
-  match users::Entity::insert(new_user_model).exec(db.get_ref()).await {
+  let actor_id = match auth_user.user_id(db.get_ref()).await {
+      Ok(actor_id) => actor_id,
+      Err(err) => return HttpResponse::InternalServerError().json(format!("Error: {:?}", err)),
+  };
+  let context = AuditContext::new(&req, actor_id);
+  let created = async {
+      let txn = db.begin().await?;
+      let user = new_user_model.insert(&txn).await?;
+      audit::record(&txn, &context, AuditEvent {
+          action: "user.create",
+          target_type: "user",
+          target_id: Some(user.id),
+          changes: audit::diff(&serde_json::Value::Null, &audit::user_fields(&user)),
+      })
+      .await?;
+      txn.commit().await
+  };
+
+  match created.await {
       Ok(_) => HttpResponse::Ok().json(ResponseMessage {
           message: "User created successfully".to_string(),
       }),
@@ -318,12 +520,25 @@ pub async fn create_user(
 }
 
 // Get All Users Handler
+#[utoipa::path(
+    get,
+    path = "/users/all",
+    tag = "users",
+    params(UserFilter),
+    responses(
+        (status = 200, description = "Users matching the filter", body = GetAllUsersResponse),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 500, description = "Unexpected server error", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = []))
+)]
+#[tracing::instrument(skip_all)]
 pub async fn get_users(
     db: web::Data<DatabaseConnection>,
     filter: web::Query<UserFilter>
 ) -> HttpResponse {
 
-    let mut query = Users::find();
+    let mut query = Users::find_live();
     if let Some(first_name) = &filter.first_name.as_ref().filter(|s| !s.trim().is_empty()) {
         // Case-insensitive match for first name
         let pattern = format!("{}%", first_name);
@@ -366,7 +581,7 @@ pub async fn get_users(
                     let filtered_chats: Vec<ChatInfo> = chat_info.into_iter()
                         .filter(|info| {
                             info.chat_name.to_lowercase().starts_with(&chat_name_pattern) &&
-                            info.author_username.to_lowercase().starts_with(&author_username_pattern)
+                            info.author_username.as_deref().unwrap_or_default().to_lowercase().starts_with(&author_username_pattern)
                         }) 
                         .collect();
 
@@ -389,12 +604,26 @@ pub async fn get_users(
 }
 
 //Get a single user by id Handler
+#[utoipa::path(
+    get,
+    path = "/users/{id}",
+    tag = "users",
+    params(("id" = i32, Path, description = "User id")),
+    responses(
+        (status = 200, description = "The user", body = UserResponse),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 404, description = "User not found", body = ErrorResponse),
+        (status = 500, description = "Unexpected server error", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = []))
+)]
 pub async fn get_user(
+    _auth_user: AuthenticatedUser,
     db: web::Data<DatabaseConnection>,
     user_id: web::Path<i32>,
 ) -> HttpResponse {
     let user_id = user_id.into_inner();
-    match Users::find_by_id(user_id).one(db.get_ref()).await {
+    match Users::find_live_by_id(user_id).one(db.get_ref()).await {
         Ok(Some(user)) => {
             let mut user_resp = UserResponse::from(user);
             let chat_info = get_user_chat_info(db.get_ref(), user_resp.id).await;
@@ -408,7 +637,59 @@ pub async fn get_user(
     }
 }
 
+// Get Own Profile Handler
+#[utoipa::path(
+    get,
+    path = "/users/me",
+    tag = "users",
+    responses(
+        (status = 200, description = "The signed-in user", body = UserResponse),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 404, description = "User not found", body = ErrorResponse),
+        (status = 500, description = "Unexpected server error", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn get_me(
+    auth_user: AuthenticatedUser,
+    db: web::Data<DatabaseConnection>,
+) -> HttpResponse {
+    use sea_orm::ColumnTrait;
+
+    match Users::find_live()
+        .filter(users::Column::Username.eq(&auth_user.0.sub))
+        .one(db.get_ref())
+        .await
+    {
+        Ok(Some(user)) => {
+            let mut user_resp = UserResponse::from(user);
+            user_resp.chats = get_user_chat_info(db.get_ref(), user_resp.id).await;
+            HttpResponse::Ok().json(user_resp)
+        }
+        Ok(None) => HttpResponse::NotFound().json(ResponseMessage {
+            message: "User not found".to_string()
+        }),
+        Err(err) => HttpResponse::InternalServerError().json(format!("Error: {:?}", err)),
+    }
+}
+
 //Edit User Handler
+#[utoipa::path(
+    put,
+    path = "/users/{id}",
+    tag = "users",
+    request_body(content((UpdateUser = "application/json"), (UpdateUser = "multipart/form-data"))),
+    params(("id" = i32, Path, description = "User id")),
+    responses(
+        (status = 200, description = "User updated", body = ResponseMessage),
+        (status = 400, description = "Malformed request", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 404, description = "User not found", body = ErrorResponse),
+        (status = 409, description = "Username or email already taken", body = ErrorResponse),
+        (status = 500, description = "Unexpected server error", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = []))
+)]
 pub async fn update_user(
     auth_user: AuthenticatedUser,
     req: HttpRequest,
@@ -423,7 +704,7 @@ pub async fn update_user(
     };
 
     // Fetch the existing user.
-    let user = match Users::find_by_id(user_id).one(db.get_ref()).await {
+    let user = match Users::find_live_by_id(user_id).one(db.get_ref()).await {
         Ok(Some(user)) => user,
         Ok(None) => return HttpResponse::NotFound().json(ResponseMessage {
             message: "User not found".to_string(),
@@ -446,6 +727,7 @@ pub async fn update_user(
         first_name: None,
         last_name: None,
         username: None,
+        email: None,
         role_id: None,
         avatar: None, // in JSON, expected as base64 string
         password: None,
@@ -499,6 +781,7 @@ pub async fn update_user(
                     "first_name" => update_data.first_name = Some(value),
                     "last_name" => update_data.last_name = Some(value),
                     "username" => update_data.username = Some(value),
+                    "email" => update_data.email = Some(value),
                     "role_id" => {
                         if let Ok(parsed) = value.parse::<i32>() {
                             update_data.role_id = Some(parsed);
@@ -553,6 +836,8 @@ pub async fn update_user(
         return HttpResponse::BadRequest().body("Unsupported Content-Type");
     }
 
+    let role_changed = update_data.role_id.is_some_and(|role_id| Some(role_id) != user.role_id);
+
     // Use the macros to merge fields from update_data into user_model
     merge_update!(user_model, update_data, 
         first_name,
@@ -565,6 +850,21 @@ pub async fn update_user(
         role_id
     );
     
+    // Email is normalized and must stay unique across users
+    if update_data.email.is_some() {
+        let email = normalize_email(update_data.email.take());
+        if email.is_some() && email != user.email && email_taken(db.get_ref(), &email).await {
+            return HttpResponse::Conflict().json(ResponseMessage {
+                message: "Email already in use".to_string(),
+            });
+        }
+        // A new address has to be confirmed again.
+        if email != user.email {
+            user_model.email_verified_at = Set(None);
+        }
+        user_model.email = Set(email);
+    }
+
     // Handle avatar separately since it's processed differently
     if let Some(avatar) = avatar_bytes {
         user_model.avatar = Set(Some(avatar));
@@ -573,11 +873,8 @@ pub async fn update_user(
     //Handle password to be edited only for the same user that makes the request
     if let Some(new_password) = update_data.password {
         if auth_user.0.sub == user.username {
-            let password_bytes = new_password.as_bytes();
-            let mut rng = OsRng;
-            let salt = SaltString::generate(&mut rng);
-            let hashed_password = match Argon2::default().hash_password(password_bytes, &salt) {
-                Ok(hash) => hash.to_string(),
+            let hashed_password = match hash_password(&new_password) {
+                Ok(hash) => hash,
                 Err(err) => {
                     return HttpResponse::InternalServerError().json(ResponseMessage {
                         message: format!("Password hashing error: {:?}", err),
@@ -587,7 +884,20 @@ pub async fn update_user(
             user_model.password = Set(hashed_password);
         }
     }
-    match user_model.update(db.get_ref()).await {
+
+    // Admin edits and role changes are privileged, so they go to the audit log.
+    let updated = if auth_user.0.role == "admin" || role_changed {
+        match auth_user.user_id(db.get_ref()).await {
+            Ok(actor_id) => {
+                let context = AuditContext::new(&req, actor_id);
+                audit::update_user(db.get_ref(), &context, "user.update", &user, user_model).await
+            }
+            Err(err) => Err(err),
+        }
+    } else {
+        user_model.update(db.get_ref()).await
+    };
+    match updated {
         Ok(_) => HttpResponse::Ok().json(ResponseMessage {
             message: "User updated successfully".to_string(),
         }),
@@ -596,23 +906,131 @@ pub async fn update_user(
 }
 
 //Delete User Handler
+// The account is only flagged here; the purge job removes it once the grace period ends.
+#[utoipa::path(
+    delete,
+    path = "/users/{id}",
+    tag = "users",
+    params(("id" = i32, Path, description = "User id")),
+    responses(
+        (status = 200, description = "User scheduled for deletion", body = ResponseMessage),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 404, description = "User not found", body = ErrorResponse),
+        (status = 500, description = "Unexpected server error", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = []))
+)]
 pub async fn delete_user(
+    auth_user: AuthenticatedUser,
+    req: HttpRequest,
     db: web::Data<DatabaseConnection>,
     user_id: web::Path<i32>,
 ) -> HttpResponse {
     let user_id = user_id.into_inner();
-    match Users::delete_by_id(user_id).exec(db.get_ref()).await {
-        Ok(result) => {
-            if result.rows_affected > 0 {
-                HttpResponse::Ok().json(ResponseMessage {
-                    message: "User deleted succesfully".to_string(),
-                })
-            } else {
-                HttpResponse::NotFound().json(ResponseMessage {
-                    message: "User not found".to_string(),
-                })
-            }
+    let context = match auth_user.user_id(db.get_ref()).await {
+        Ok(actor_id) => AuditContext::new(&req, actor_id),
+        Err(err) => return HttpResponse::InternalServerError().json(format!("Error: {:?}", err)),
+    };
+    let txn = match db.begin().await {
+        Ok(txn) => txn,
+        Err(err) => return HttpResponse::InternalServerError().json(format!("Error: {:?}", err)),
+    };
+
+    let deleted = Users::update_many()
+        .col_expr(users::Column::DeletedAt, Expr::value(Utc::now()))
+        .filter(users::Column::Id.eq(user_id))
+        .filter(users::Column::DeletedAt.is_null())
+        .exec(&txn)
+        .await;
+    match deleted {
+        Ok(result) if result.rows_affected > 0 => {}
+        Ok(_) => return HttpResponse::NotFound().json(ResponseMessage {
+            message: "User not found".to_string(),
+        }),
+        Err(err) => return HttpResponse::InternalServerError().json(format!("Error: {:?}", err)),
+    }
+
+    let revoked = UserSessions::update_many()
+        .col_expr(user_sessions::Column::RevokedAt, Expr::value(Utc::now()))
+        .filter(user_sessions::Column::UserId.eq(user_id))
+        .filter(user_sessions::Column::RevokedAt.is_null())
+        .exec(&txn)
+        .await;
+    if let Err(err) = revoked {
+        return HttpResponse::InternalServerError().json(format!("Error: {:?}", err));
+    }
+
+    let event = AuditEvent {
+        action: "user.delete",
+        target_type: "user",
+        target_id: Some(user_id),
+        changes: None,
+    };
+    if let Err(err) = audit::record(&txn, &context, event).await {
+        return HttpResponse::InternalServerError().json(format!("Error: {:?}", err));
+    }
+
+    match txn.commit().await {
+        Ok(_) => HttpResponse::Ok().json(ResponseMessage {
+            message: "User deleted succesfully".to_string(),
+        }),
+        Err(err) => HttpResponse::InternalServerError().json(format!("Error: {:?}", err)),
+    }
+}
+
+// Restore Deleted User Handler
+#[utoipa::path(
+    post,
+    path = "/users/{id}/restore",
+    tag = "users",
+    params(("id" = i32, Path, description = "User id")),
+    responses(
+        (status = 200, description = "User restored", body = ResponseMessage),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 404, description = "Deleted user not found", body = ErrorResponse),
+        (status = 500, description = "Unexpected server error", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn restore_user(
+    auth_user: AuthenticatedUser,
+    req: HttpRequest,
+    db: web::Data<DatabaseConnection>,
+    user_id: web::Path<i32>,
+) -> HttpResponse {
+    let user_id = user_id.into_inner();
+    let context = match auth_user.user_id(db.get_ref()).await {
+        Ok(actor_id) => AuditContext::new(&req, actor_id),
+        Err(err) => return HttpResponse::InternalServerError().json(format!("Error: {:?}", err)),
+    };
+
+    let restored = async {
+        let txn = db.begin().await?;
+        let result = Users::update_many()
+            .col_expr(users::Column::DeletedAt, Expr::value(Option::<chrono::DateTime<Utc>>::None))
+            .filter(users::Column::Id.eq(user_id))
+            .filter(users::Column::DeletedAt.is_not_null())
+            .exec(&txn)
+            .await?;
+        if result.rows_affected > 0 {
+            audit::record(&txn, &context, AuditEvent {
+                action: "user.restore",
+                target_type: "user",
+                target_id: Some(user_id),
+                changes: None,
+            })
+            .await?;
         }
+        txn.commit().await?;
+        Ok::<_, sea_orm::DbErr>(result.rows_affected)
+    };
+    match restored.await {
+        Ok(rows) if rows > 0 => HttpResponse::Ok().json(ResponseMessage {
+            message: "User restored successfully".to_string(),
+        }),
+        Ok(_) => HttpResponse::NotFound().json(ResponseMessage {
+            message: "No deleted user with that id".to_string(),
+        }),
         Err(err) => HttpResponse::InternalServerError().json(format!("Error: {:?}", err)),
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,160 @@
+use actix_web::{web, HttpResponse};
+use chrono::{Duration, Utc};
+use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, QueryFilter, Set};
+use crate::config::Config;
+use crate::entities::prelude::Users;
+use crate::entities::sea_orm_active_enums::AccountStatus;
+use crate::entities::users;
+use crate::handlers::user_handler::get_secret;
+use crate::mailer::{Email, Mailer};
+use crate::models::token_model::EmailVerificationClaims;
+use crate::models::user_models::{ErrorResponse, ResponseMessage, VerifyEmail};
+use crate::utils::check_auth_user::AuthenticatedUser;
+use crate::utils::soft_delete::SoftDelete;
+
+// Mails a confirmation link for the user's current email address, if they have one.
+pub async fn send_verification_email(config: &Config, mailer: &dyn Mailer, user: &users::Model) {
+    let email = match &user.email {
+        Some(email) => email.clone(),
+        None => return,
+    };
+    let claims = EmailVerificationClaims {
+        user_id: user.id,
+        email: email.clone(),
+        exp: (Utc::now() + Duration::hours(config.email_verification_ttl_hours)).timestamp() as usize,
+    };
+    let token = encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(get_secret().as_bytes()),
+    )
+    .unwrap();
+
+    let link = format!(
+        "{}/verify-email?token={}",
+        config.frontend_url.trim_end_matches('/'),
+        token
+    );
+    let message = Email {
+        to: email,
+        subject: "Confirm your BlockChat email address".to_string(),
+        body: format!(
+            "Hi {},\n\nConfirm your email address to finish setting up your account:\n\n{}\n\nThe link expires in {} hours.\n",
+            user.first_name, link, config.email_verification_ttl_hours
+        ),
+    };
+    if let Err(err) = mailer.send(message).await {
+        log::error!("Failed to send verification email to user {}: {}", user.id, err);
+    }
+}
+
+// Verify Email Handler
+#[utoipa::path(
+    post,
+    path = "/users/verify-email",
+    tag = "auth",
+    request_body = VerifyEmail,
+    responses(
+        (status = 200, description = "Email address confirmed", body = ResponseMessage),
+        (status = 400, description = "Invalid or expired token", body = ErrorResponse),
+        (status = 500, description = "Unexpected server error", body = ErrorResponse),
+    )
+)]
+pub async fn verify_email(
+    db: web::Data<DatabaseConnection>,
+    form: web::Json<VerifyEmail>,
+) -> HttpResponse {
+    let invalid = || {
+        HttpResponse::BadRequest().json(ResponseMessage {
+            message: "Invalid or expired verification link".to_string(),
+        })
+    };
+
+    let claims = match decode::<EmailVerificationClaims>(
+        form.token.trim(),
+        &DecodingKey::from_secret(get_secret().as_bytes()),
+        &Validation::default(),
+    ) {
+        Ok(data) => data.claims,
+        Err(_) => return invalid(),
+    };
+
+    // The link only confirms the address it was sent to.
+    let user = match Users::find_live_by_id(claims.user_id)
+        .filter(users::Column::Email.eq(claims.email.as_str()))
+        .one(db.get_ref())
+        .await
+    {
+        Ok(Some(user)) => user,
+        Ok(None) => return invalid(),
+        Err(err) => return HttpResponse::InternalServerError().json(format!("Error: {:?}", err)),
+    };
+
+    let mut user_model: users::ActiveModel = user.clone().into();
+    if user.email_verified_at.is_none() {
+        user_model.email_verified_at = Set(Some(Utc::now().into()));
+    }
+    if user.status == AccountStatus::Unverified {
+        user_model.status = Set(AccountStatus::Active);
+    }
+    // A restricted account is verified for when the restriction is lifted.
+    if user.previous_status == Some(AccountStatus::Unverified) {
+        user_model.previous_status = Set(Some(AccountStatus::Active));
+    }
+    match user_model.update(db.get_ref()).await {
+        Ok(_) => HttpResponse::Ok().json(ResponseMessage {
+            message: "Email verified successfully".to_string(),
+        }),
+        Err(err) => HttpResponse::InternalServerError().json(format!("Error: {:?}", err)),
+    }
+}
+
+// Resend Verification Email Handler
+#[utoipa::path(
+    post,
+    path = "/users/verify-email/resend",
+    tag = "auth",
+    responses(
+        (status = 200, description = "Verification email sent", body = ResponseMessage),
+        (status = 400, description = "No email address or already verified", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 404, description = "User not found", body = ErrorResponse),
+        (status = 500, description = "Unexpected server error", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn resend_verification(
+    auth_user: AuthenticatedUser,
+    db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
+    mailer: web::Data<dyn Mailer>,
+) -> HttpResponse {
+    let user = match Users::find_live()
+        .filter(users::Column::Username.eq(&auth_user.0.sub))
+        .one(db.get_ref())
+        .await
+    {
+        Ok(Some(user)) => user,
+        Ok(None) => return HttpResponse::NotFound().json(ResponseMessage {
+            message: "User not found".to_string(),
+        }),
+        Err(err) => return HttpResponse::InternalServerError().json(format!("Error: {:?}", err)),
+    };
+
+    if user.email.is_none() {
+        return HttpResponse::BadRequest().json(ResponseMessage {
+            message: "No email address on this account".to_string(),
+        });
+    }
+    if user.email_verified_at.is_some() {
+        return HttpResponse::BadRequest().json(ResponseMessage {
+            message: "Email already verified".to_string(),
+        });
+    }
+
+    send_verification_email(config.get_ref(), mailer.get_ref(), &user).await;
+    HttpResponse::Ok().json(ResponseMessage {
+        message: "Verification email sent".to_string(),
+    })
+}
@@ -0,0 +1,267 @@
+use std::io::{Cursor, Write};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use chrono::Utc;
+use sea_orm::sea_query::Expr;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, QueryOrder,
+    Set,
+};
+use serde_json::{json, Value};
+use zip::write::SimpleFileOptions;
+use tokio_util::sync::CancellationToken;
+use zip::ZipWriter;
+use crate::config::Config;
+use crate::entities::prelude::{ChatParticipants, Chats, DataExports, Messages, Users};
+use crate::entities::sea_orm_active_enums::ExportStatus;
+use crate::entities::{chat_participants, chats, data_exports, messages, users};
+use crate::handlers::export_handler::download_url;
+use crate::mailer::{Email, Mailer};
+
+const EXPIRY_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+// Everything stored about one user, gathered before the archive is written.
+struct ExportData {
+    user: users::Model,
+    chats: Vec<chats::Model>,
+    memberships: Vec<(chat_participants::Model, Option<chats::Model>)>,
+    messages: Vec<messages::Model>,
+}
+
+fn timestamp(value: Option<sea_orm::prelude::DateTimeWithTimeZone>) -> Value {
+    json!(value.map(|dt| dt.with_timezone(&Utc).to_rfc3339()))
+}
+
+async fn collect(db: &DatabaseConnection, user: users::Model) -> Result<ExportData, DbErr> {
+    // Soft-deleted rows are still stored, so they are part of the export too.
+    let chats = Chats::find()
+        .filter(chats::Column::AuthorId.eq(user.id))
+        .order_by_asc(chats::Column::Id)
+        .all(db)
+        .await?;
+    let memberships = ChatParticipants::find()
+        .filter(chat_participants::Column::UserId.eq(user.id))
+        .find_also_related(Chats)
+        .order_by_asc(chat_participants::Column::Id)
+        .all(db)
+        .await?;
+    let messages = Messages::find()
+        .filter(messages::Column::UserId.eq(user.id))
+        .order_by_asc(messages::Column::Id)
+        .all(db)
+        .await?;
+    Ok(ExportData { user, chats, memberships, messages })
+}
+
+fn image_extension(data: &[u8]) -> &'static str {
+    match data {
+        [0x89, b'P', b'N', b'G', ..] => "png",
+        [0xFF, 0xD8, 0xFF, ..] => "jpg",
+        [b'G', b'I', b'F', b'8', ..] => "gif",
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => "webp",
+        _ => "bin",
+    }
+}
+
+fn chat_image_path(chat: &chats::Model) -> Option<String> {
+    chat.image
+        .as_ref()
+        .map(|image| format!("media/chats/{}.{}", chat.id, image_extension(image)))
+}
+
+fn write_archive(data: &ExportData) -> zip::result::ZipResult<Vec<u8>> {
+    let user = &data.user;
+    let avatar_path = user
+        .avatar
+        .as_ref()
+        .map(|avatar| format!("media/avatar.{}", image_extension(avatar)));
+
+    let profile = json!({
+        "id": user.id,
+        "first_name": user.first_name,
+        "last_name": user.last_name,
+        "username": user.username,
+        "email": user.email,
+        "email_verified_at": timestamp(user.email_verified_at),
+        "role_id": user.role_id,
+        "status": user.status,
+        "status_reason": user.status_reason,
+        "status_until": timestamp(user.status_until),
+        "created_at": timestamp(user.created_at),
+        "deleted_at": timestamp(user.deleted_at),
+        "avatar": avatar_path,
+    });
+    let chats: Vec<Value> = data
+        .chats
+        .iter()
+        .map(|chat| json!({
+            "id": chat.id,
+            "name": chat.name,
+            "image": chat_image_path(chat),
+            "created_at": chat.created_at.with_timezone(&Utc).to_rfc3339(),
+            "deleted_at": timestamp(chat.deleted_at),
+        }))
+        .collect();
+    let memberships: Vec<Value> = data
+        .memberships
+        .iter()
+        .map(|(membership, chat)| json!({
+            "chat_id": membership.chat_id,
+            "chat_name": chat.as_ref().map(|chat| chat.name.clone()),
+        }))
+        .collect();
+    let messages: Vec<Value> = data
+        .messages
+        .iter()
+        .map(|message| json!({
+            "id": message.id,
+            "chat_id": message.chat_id,
+            "content": message.content,
+            "metadata": message.metadata,
+            "timestamp": timestamp(message.timestamp),
+            "deleted_at": timestamp(message.deleted_at),
+        }))
+        .collect();
+
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default();
+    for (name, value) in [
+        ("profile.json", profile),
+        ("chats.json", Value::Array(chats)),
+        ("memberships.json", Value::Array(memberships)),
+        ("messages.json", Value::Array(messages)),
+    ] {
+        zip.start_file(name, options)?;
+        zip.write_all(&serde_json::to_vec_pretty(&value).expect("JSON values always serialize"))?;
+    }
+    if let (Some(path), Some(avatar)) = (avatar_path, &user.avatar) {
+        zip.start_file(path, options)?;
+        zip.write_all(avatar)?;
+    }
+    for chat in &data.chats {
+        if let (Some(path), Some(image)) = (chat_image_path(chat), &chat.image) {
+            zip.start_file(path, options)?;
+            zip.write_all(image)?;
+        }
+    }
+    Ok(zip.finish()?.into_inner())
+}
+
+async fn build_archive(db: &DatabaseConnection, config: &Config, export: &data_exports::Model) -> Result<String, String> {
+    let user = Users::find_by_id(export.user_id)
+        .one(db)
+        .await
+        .map_err(|e| format!("{:?}", e))?
+        .ok_or_else(|| "User no longer exists".to_string())?;
+    let data = collect(db, user).await.map_err(|e| format!("{:?}", e))?;
+    let archive = tokio::task::spawn_blocking(move || write_archive(&data))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())?;
+
+    // The random part keeps archive names from being guessed from the export id.
+    let file_name = format!("export-{}-{}.zip", export.id, uuid::Uuid::new_v4().simple());
+    tokio::fs::create_dir_all(&config.export_dir)
+        .await
+        .map_err(|e| e.to_string())?;
+    tokio::fs::write(Path::new(&config.export_dir).join(&file_name), archive)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(file_name)
+}
+
+// Builds the archive for a pending export and mails the download link when it is ready.
+pub async fn build(db: DatabaseConnection, config: Config, mailer: Arc<dyn Mailer>, export: data_exports::Model) {
+    let result = build_archive(&db, &config, &export).await;
+
+    let now = Utc::now();
+    let mut export_model: data_exports::ActiveModel = export.clone().into();
+    export_model.completed_at = Set(Some(now.into()));
+    match &result {
+        Ok(file_name) => {
+            export_model.status = Set(ExportStatus::Ready);
+            export_model.file_name = Set(Some(file_name.clone()));
+            export_model.expires_at = Set(Some((now + chrono::Duration::hours(config.export_link_ttl_hours)).into()));
+        }
+        Err(err) => {
+            log::error!("Data export {} failed: {}", export.id, err);
+            export_model.status = Set(ExportStatus::Failed);
+            export_model.error = Set(Some(err.clone()));
+        }
+    }
+    let export = match export_model.update(&db).await {
+        Ok(export) => export,
+        Err(err) => {
+            log::error!("Failed to update data export {}: {:?}", export.id, err);
+            return;
+        }
+    };
+
+    if export.status != ExportStatus::Ready {
+        return;
+    }
+    let user = match Users::find_by_id(export.user_id).one(&db).await {
+        Ok(Some(user)) => user,
+        _ => return,
+    };
+    if let (Some(email), Some(link)) = (user.email.clone(), download_url(&config, &export)) {
+        let message = Email {
+            to: email,
+            subject: "Your BlockChat data export is ready".to_string(),
+            body: format!(
+                "Hi {},\n\nYour data export is ready. The link below works once and expires in {} hours.\n\n{}\n",
+                user.first_name, config.export_link_ttl_hours, link
+            ),
+        };
+        if let Err(err) = mailer.send(message).await {
+            log::error!("Failed to send data export email to user {}: {}", user.id, err);
+        }
+    }
+}
+
+// Removes archives whose download link has expired without being used.
+pub async fn run_expiry(db: DatabaseConnection, export_dir: String, shutdown: CancellationToken) {
+    let mut interval = tokio::time::interval(EXPIRY_INTERVAL);
+    loop {
+        tokio::select! {
+            _ = shutdown.cancelled() => break,
+            _ = interval.tick() => {}
+        }
+        match expire(&db, &export_dir).await {
+            Ok(0) => {}
+            Ok(count) => log::info!("Expired {} data exports", count),
+            Err(err) => log::error!("Failed to expire data exports: {:?}", err),
+        }
+    }
+}
+
+async fn expire(db: &DatabaseConnection, export_dir: &str) -> Result<u64, DbErr> {
+    let expired = DataExports::find()
+        .filter(data_exports::Column::Status.eq(ExportStatus::Ready))
+        .filter(data_exports::Column::ExpiresAt.lte(Utc::now()))
+        .all(db)
+        .await?;
+    let mut count = 0;
+    for export in expired {
+        if let Some(file_name) = &export.file_name {
+            remove_archive(export_dir, file_name).await;
+        }
+        count += DataExports::update_many()
+            .col_expr(data_exports::Column::Status, Expr::value(ExportStatus::Expired))
+            .filter(data_exports::Column::Id.eq(export.id))
+            .filter(data_exports::Column::Status.eq(ExportStatus::Ready))
+            .exec(db)
+            .await?
+            .rows_affected;
+    }
+    Ok(count)
+}
+
+pub async fn remove_archive(export_dir: &str, file_name: &str) {
+    if let Err(err) = tokio::fs::remove_file(Path::new(export_dir).join(file_name)).await {
+        if err.kind() != std::io::ErrorKind::NotFound {
+            log::error!("Failed to remove data export archive {}: {}", file_name, err);
+        }
+    }
+}
@@ -0,0 +1,45 @@
+use std::time::Duration;
+use chrono::Utc;
+use sea_orm::sea_query::Expr;
+use sea_orm::{ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter};
+use tokio_util::sync::CancellationToken;
+use crate::entities::prelude::Users;
+use crate::entities::sea_orm_active_enums::AccountStatus;
+use crate::entities::users;
+
+const INTERVAL: Duration = Duration::from_secs(60);
+
+// Lifts suspensions and bans that have reached their `status_until`, returning each
+// account to the status it had before.
+pub async fn run(db: DatabaseConnection, shutdown: CancellationToken) {
+    let mut interval = tokio::time::interval(INTERVAL);
+    loop {
+        tokio::select! {
+            _ = shutdown.cancelled() => break,
+            _ = interval.tick() => {}
+        }
+        match lift(&db).await {
+            Ok(0) => {}
+            Ok(count) => log::info!("Lifted {} expired suspensions/bans", count),
+            Err(err) => log::error!("Failed to lift expired suspensions/bans: {:?}", err),
+        }
+    }
+}
+
+/// Lifts every expired restriction once, returning how many accounts it reinstated.
+pub async fn lift(db: &DatabaseConnection) -> Result<u64, DbErr> {
+    let result = Users::update_many()
+        .col_expr(
+            users::Column::Status,
+            Expr::col(users::Column::PreviousStatus).if_null(Expr::value(AccountStatus::Active)),
+        )
+        .col_expr(users::Column::PreviousStatus, Expr::value(Option::<String>::None))
+        .col_expr(users::Column::StatusReason, Expr::value(Option::<String>::None))
+        .col_expr(users::Column::StatusSetBy, Expr::value(Option::<i32>::None))
+        .col_expr(users::Column::StatusUntil, Expr::value(Option::<chrono::DateTime<Utc>>::None))
+        .filter(users::Column::Status.is_in([AccountStatus::Suspended, AccountStatus::Banned]))
+        .filter(users::Column::StatusUntil.lte(Utc::now()))
+        .exec(db)
+        .await?;
+    Ok(result.rows_affected)
+}
@@ -0,0 +1,19 @@
+use sea_orm::DatabaseConnection;
+use crate::config::Config;
+use crate::shutdown::Shutdown;
+
+pub mod data_exports;
+pub mod lift_expired_restrictions;
+pub mod purge_deleted;
+
+// Starts the periodic maintenance tasks on the current runtime. Each stops before its
+// next run once shutdown starts, and a run already in progress is allowed to finish.
+pub fn spawn_all(db: &DatabaseConnection, config: &Config, shutdown: &Shutdown) {
+    shutdown.spawn(lift_expired_restrictions::run(db.clone(), shutdown.token()));
+    shutdown.spawn(purge_deleted::run(db.clone(), config.purge_grace_days, shutdown.token()));
+    shutdown.spawn(data_exports::run_expiry(
+        db.clone(),
+        config.export_dir.clone(),
+        shutdown.token(),
+    ));
+}
@@ -0,0 +1,66 @@
+use std::time::Duration;
+use chrono::Utc;
+use sea_orm::sea_query::Expr;
+use sea_orm::{
+    ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, TransactionTrait,
+};
+use tokio_util::sync::CancellationToken;
+use crate::entities::prelude::{Chats, Messages, Users};
+use crate::entities::{chats, messages, users};
+
+const INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+// Permanently removes users, chats and messages deleted more than `grace_days` ago.
+pub async fn run(db: DatabaseConnection, grace_days: i64, shutdown: CancellationToken) {
+    let mut interval = tokio::time::interval(INTERVAL);
+    loop {
+        tokio::select! {
+            _ = shutdown.cancelled() => break,
+            _ = interval.tick() => {}
+        }
+        match purge(&db, grace_days).await {
+            Ok((0, 0, 0)) => {}
+            Ok((users, chats, messages)) => log::info!(
+                "Purged {} users, {} chats and {} messages past the grace period",
+                users, chats, messages
+            ),
+            Err(err) => log::error!("Failed to purge deleted rows: {:?}", err),
+        }
+    }
+}
+
+/// Runs one purge pass, returning how many users, chats and messages it removed.
+pub async fn purge(db: &DatabaseConnection, grace_days: i64) -> Result<(u64, u64, u64), DbErr> {
+    let cutoff = Utc::now() - chrono::Duration::days(grace_days);
+
+    let expired_users = Users::find()
+        .filter(users::Column::DeletedAt.lte(cutoff))
+        .all(db)
+        .await?;
+    let mut purged_users = 0;
+    for user in expired_users {
+        // Messages outlive their author, so keep who wrote them before the row goes.
+        let txn = db.begin().await?;
+        Messages::update_many()
+            .col_expr(messages::Column::AuthorName, Expr::value(user.username.clone()))
+            .filter(messages::Column::UserId.eq(user.id))
+            .filter(messages::Column::AuthorName.is_null())
+            .exec(&txn)
+            .await?;
+        purged_users += Users::delete_by_id(user.id).exec(&txn).await?.rows_affected;
+        txn.commit().await?;
+    }
+
+    let purged_chats = Chats::delete_many()
+        .filter(chats::Column::DeletedAt.lte(cutoff))
+        .exec(db)
+        .await?
+        .rows_affected;
+    let purged_messages = Messages::delete_many()
+        .filter(messages::Column::DeletedAt.lte(cutoff))
+        .exec(db)
+        .await?
+        .rows_affected;
+
+    Ok((purged_users, purged_chats, purged_messages))
+}
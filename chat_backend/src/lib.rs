@@ -0,0 +1,20 @@
+// Shared by the API server and the `blockchat-admin` tool.
+pub mod app;
+pub mod config;
+pub mod entities;
+pub mod graphql;
+pub mod grpc;
+pub mod routes;
+pub mod utils;
+pub mod handlers;
+pub mod jobs;
+pub mod logging;
+pub mod models;
+pub mod seed;
+pub mod shutdown;
+pub mod telemetry;
+pub mod mailer;
+pub mod metrics;
+pub mod openapi;
+pub mod migrate;
+pub mod middleware;
@@ -0,0 +1,112 @@
+use std::io::Write;
+use chrono::{SecondsFormat, Utc};
+use env_logger::Env;
+use log::kv::{self, VisitSource, VisitValue};
+use serde_json::{Map, Value};
+
+tokio::task_local! {
+    // Id of the request being served, set by the `AssignRequestId` middleware.
+    static REQUEST_ID: String;
+}
+
+/// Runs `f` with `request_id` attached to every log line it emits.
+pub fn with_request_id<F: FnOnce() -> R, R>(request_id: String, f: F) -> R {
+    REQUEST_ID.sync_scope(request_id, f)
+}
+
+/// Wraps a future so that log lines emitted while it runs carry `request_id`.
+pub async fn scope_request_id<F: std::future::Future>(request_id: String, fut: F) -> F::Output {
+    REQUEST_ID.scope(request_id, fut).await
+}
+
+pub fn current_request_id() -> Option<String> {
+    REQUEST_ID.try_with(|id| id.clone()).ok()
+}
+
+/// Sets up `env_logger`. `LOG_FORMAT=json` writes one JSON object per line, including
+/// the key-value fields attached to a record; anything else keeps the text format.
+pub fn init() {
+    let json = std::env::var("LOG_FORMAT").is_ok_and(|f| f.eq_ignore_ascii_case("json"));
+    let mut builder = env_logger::Builder::from_env(Env::default().default_filter_or("debug"));
+
+    if json {
+        builder.format(|buf, record| {
+            let mut line = Map::new();
+            line.insert("ts".into(), Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true).into());
+            line.insert("level".into(), record.level().as_str().into());
+            line.insert("target".into(), record.target().into());
+            line.insert("message".into(), record.args().to_string().into());
+            if let Some(request_id) = current_request_id() {
+                line.insert("request_id".into(), request_id.into());
+            }
+            let _ = record.key_values().visit(&mut JsonFields(&mut line));
+            writeln!(buf, "{}", Value::Object(line))
+        });
+    } else {
+        builder.format(|buf, record| {
+            let request_id = current_request_id()
+                .map(|id| format!(" request_id={}", id))
+                .unwrap_or_default();
+            writeln!(
+                buf,
+                "[{} {:<5} {}{}] {}",
+                Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+                record.level(),
+                record.target(),
+                request_id,
+                record.args()
+            )
+        });
+    }
+    builder.init();
+}
+
+struct JsonFields<'a>(&'a mut Map<String, Value>);
+
+impl<'kvs> VisitSource<'kvs> for JsonFields<'_> {
+    fn visit_pair(&mut self, key: kv::Key<'kvs>, value: kv::Value<'kvs>) -> Result<(), kv::Error> {
+        let mut json = Value::Null;
+        value.visit(JsonValue(&mut json))?;
+        self.0.insert(key.to_string(), json);
+        Ok(())
+    }
+}
+
+struct JsonValue<'a>(&'a mut Value);
+
+impl<'v> VisitValue<'v> for JsonValue<'_> {
+    fn visit_any(&mut self, value: kv::Value) -> Result<(), kv::Error> {
+        *self.0 = value.to_string().into();
+        Ok(())
+    }
+
+    fn visit_null(&mut self) -> Result<(), kv::Error> {
+        *self.0 = Value::Null;
+        Ok(())
+    }
+
+    fn visit_u64(&mut self, value: u64) -> Result<(), kv::Error> {
+        *self.0 = value.into();
+        Ok(())
+    }
+
+    fn visit_i64(&mut self, value: i64) -> Result<(), kv::Error> {
+        *self.0 = value.into();
+        Ok(())
+    }
+
+    fn visit_f64(&mut self, value: f64) -> Result<(), kv::Error> {
+        *self.0 = value.into();
+        Ok(())
+    }
+
+    fn visit_bool(&mut self, value: bool) -> Result<(), kv::Error> {
+        *self.0 = value.into();
+        Ok(())
+    }
+
+    fn visit_str(&mut self, value: &str) -> Result<(), kv::Error> {
+        *self.0 = value.into();
+        Ok(())
+    }
+}
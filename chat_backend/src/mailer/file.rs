@@ -0,0 +1,46 @@
+use std::path::PathBuf;
+use async_trait::async_trait;
+use chrono::Utc;
+use crate::config::MailConfig;
+use super::{Email, MailError, Mailer};
+
+// Development sink: logs every message and, with `MAIL_OUTBOX_DIR` set, writes it to disk.
+pub struct FileMailer {
+    from: String,
+    outbox_dir: Option<PathBuf>,
+}
+
+impl FileMailer {
+    pub fn new(config: &MailConfig) -> Self {
+        Self {
+            from: config.from.clone(),
+            outbox_dir: config.outbox_dir.as_ref().map(PathBuf::from),
+        }
+    }
+}
+
+#[async_trait]
+impl Mailer for FileMailer {
+    async fn send(&self, email: Email) -> Result<(), MailError> {
+        log::info!("Mail to {}: {}\n{}", email.to, email.subject, email.body);
+
+        if let Some(dir) = &self.outbox_dir {
+            let message = format!(
+                "From: {}\r\nTo: {}\r\nSubject: {}\r\nDate: {}\r\n\r\n{}\r\n",
+                self.from,
+                email.to,
+                email.subject,
+                Utc::now().to_rfc2822(),
+                email.body
+            );
+            let path = dir.join(format!("{}-{}.eml", Utc::now().timestamp_millis(), uuid::Uuid::new_v4()));
+            tokio::fs::create_dir_all(dir)
+                .await
+                .map_err(|e| MailError(format!("Failed to create outbox: {:?}", e)))?;
+            tokio::fs::write(&path, message)
+                .await
+                .map_err(|e| MailError(format!("Failed to write {}: {:?}", path.display(), e)))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,37 @@
+use std::fmt;
+use std::sync::Arc;
+use async_trait::async_trait;
+use crate::config::MailConfig;
+
+pub mod file;
+pub mod smtp;
+
+pub struct Email {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+#[derive(Debug)]
+pub struct MailError(pub String);
+
+impl fmt::Display for MailError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+// Outgoing mail transport; handlers hold it as `web::Data<dyn Mailer>`.
+#[async_trait]
+pub trait Mailer: Send + Sync {
+    async fn send(&self, email: Email) -> Result<(), MailError>;
+}
+
+// Builds the transport selected by `MAIL_TRANSPORT`.
+pub fn from_config(config: &MailConfig) -> Result<Arc<dyn Mailer>, MailError> {
+    match config.transport.as_str() {
+        "smtp" => Ok(Arc::new(smtp::SmtpMailer::new(config)?)),
+        "file" => Ok(Arc::new(file::FileMailer::new(config))),
+        other => Err(MailError(format!("Unknown mail transport: {}", other))),
+    }
+}
@@ -1,70 +1,146 @@
 use dotenvy::dotenv;
 use sea_orm::Database;
-use actix_web::{web, App, HttpServer};
-use actix_cors::Cors;
-use middleware::custom_logger::CustomLogger;
+use actix_web::{web, App, HttpResponse, HttpServer};
 use log::{info, error};
-use env_logger::Env;
-
-mod entities;
-mod routes;
-mod utils;
-mod handlers;
-mod models;
-mod seed;
-mod middleware;
+use std::time::Duration;
+use chat_backend::utils::messages::MessageHub;
+use chat_backend::{app, config, grpc, jobs, logging, mailer, metrics, migrate, seed, shutdown, telemetry};
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
+    dotenv().ok();
     // Initialize the logger with default level `debug`
-    env_logger::Builder::from_env(Env::default().default_filter_or("debug")).init();
+    logging::init();
+    metrics::init();
 
-    dotenv().ok();
     let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+    let config = config::Config::from_env();
+    let mailer = match mailer::from_config(&config.mail) {
+        Ok(mailer) => web::Data::from(mailer),
+        Err(e) => {
+            error!("Failed to set up the mailer: {}", e);
+            return Err(std::io::Error::other("Mailer setup failed"));
+        }
+    };
+
+    let tracer_provider = match telemetry::init(&config.tracing) {
+        Ok(provider) => provider,
+        Err(e) => {
+            error!("Failed to set up tracing: {}", e);
+            return Err(std::io::Error::other("Tracing setup failed"));
+        }
+    };
 
     info!("Connecting to the database...");
 
-    let db = match Database::connect(&database_url).await {
+    let mut db = match Database::connect(&database_url).await {
         Ok(connection) => {
             info!("Successfully connected to the database");
             connection
         }
         Err(e) => {
             error!("Failed to connect to the database: {:?}", e);
-            return Err(std::io::Error::new(std::io::ErrorKind::Other, "Database connection failed"));
+            return Err(std::io::Error::other("Database connection failed"));
         }
     };
 
-    // Seed the database with roles
+    metrics::instrument_database(&mut db);
+
+    if let Err(e) = migrate::run(&db, config.migration_mode).await {
+        error!("Failed to migrate the database: {}", e);
+        return Err(std::io::Error::other("Database migration failed"));
+    }
+
+    // Seed the database from the configured fixture
     info!("Seeding the database...");
-    if let Err(e) = seed::seed_roles(&db).await {
-        error!("Failed to seed roles: {:?}", e);
-        return Err(std::io::Error::new(
-            std::io::ErrorKind::Other,
-            "Database seeding failed",
-        ));
+    let fixture = match seed::from_config(&config) {
+        Ok(fixture) => fixture,
+        Err(e) => {
+            error!("{}", e);
+            return Err(std::io::Error::other("Invalid seed fixture"));
+        }
+    };
+    if let Err(e) = seed::apply(&db, &fixture).await {
+        error!("Failed to seed the database: {:?}", e);
+        return Err(std::io::Error::other("Database seeding failed"));
     }
     info!("Database seeding completed.");
 
-    info!("Starting the HTTP server on 127.0.0.1:8080");
+    let shutdown = shutdown::Shutdown::new();
+    let shutdown_timeout = Duration::from_secs(config.shutdown_timeout_secs);
+    jobs::spawn_all(&db, &config, &shutdown);
+
+    info!("Starting the metrics server on {}", config.metrics_addr);
 
-    HttpServer::new(move || {
-        let cors = Cors::default()
-                        .allowed_origin("http://localhost:3000")
-                        .allowed_methods(vec!["GET", "POST", "PUT", "DELETE", "OPTIONS"])
-                        .allowed_headers(vec![
-                            actix_web::http::header::AUTHORIZATION,
-                            actix_web::http::header::ACCEPT,
-                            actix_web::http::header::CONTENT_TYPE,
-                            ])
-                        .max_age(3600);
-        App::new()
-            .wrap(CustomLogger)
-            .wrap(cors)
-            .app_data(web::Data::new(db.clone()))
-            .configure(routes::user_routes::configure)
+    let metrics_server = HttpServer::new(|| {
+        App::new().route(
+            "/metrics",
+            web::get().to(|| async {
+                HttpResponse::Ok()
+                    .content_type("text/plain; version=0.0.4")
+                    .body(metrics::render())
+            }),
+        )
     })
+    .workers(1)
+    .disable_signals()
+    .bind(&config.metrics_addr)?
+    .run();
+
+    // Messages posted over GraphQL or gRPC reach the live streams of both.
+    let messages = MessageHub::new();
+
+    info!("Starting the gRPC server on {}", config.grpc_addr);
+
+    let grpc_listener = tokio::net::TcpListener::bind(&config.grpc_addr).await?;
+    let grpc_server = grpc::serve(grpc_listener, db.clone(), messages.clone(), shutdown.token());
+
+    info!("Starting the HTTP server on 127.0.0.1:8080");
+
+    let pool = db.clone();
+    let state = app::AppState {
+        db,
+        config,
+        mailer,
+        shutdown: web::Data::new(shutdown.clone()),
+        messages,
+    };
+    let api_server = HttpServer::new(move || app::build(&state))
+    // Signals are handled below so both servers stop together.
+    .disable_signals()
+    .shutdown_timeout(shutdown_timeout.as_secs())
     .bind("127.0.0.1:8080")?
-    .run()
-    .await
+    .run();
+
+    let api_handle = api_server.handle();
+    let metrics_handle = metrics_server.handle();
+    let stopping = shutdown.clone();
+    actix_web::rt::spawn(async move {
+        shutdown::signal().await;
+        info!("Shutdown requested; finishing in-flight requests and background jobs");
+        // Stop scheduling job runs while the servers drain; this also stops the gRPC server.
+        stopping.trigger();
+        futures::join!(api_handle.stop(true), metrics_handle.stop(true));
+    });
+
+    let grpc_server = async {
+        grpc_server.await.map_err(|e| {
+            error!("gRPC server failed: {}", e);
+            std::io::Error::other("gRPC server failed")
+        })
+    };
+    let result = futures::try_join!(api_server, metrics_server, grpc_server);
+
+    if !shutdown.drain(shutdown_timeout).await {
+        error!(
+            "Background jobs were still running after {}s; exiting anyway",
+            shutdown_timeout.as_secs()
+        );
+    }
+    if let Err(e) = pool.close().await {
+        error!("Failed to close the database pool: {:?}", e);
+    }
+    telemetry::shutdown(tracer_provider);
+    info!("Shutdown complete");
+    result.map(|_| ())
 }
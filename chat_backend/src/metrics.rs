@@ -0,0 +1,130 @@
+use std::sync::LazyLock;
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounterVec, IntGauge, Opts, Registry, TextEncoder,
+};
+use sea_orm::{metric, DatabaseConnection};
+
+// Every BlockChat metric is registered here and served by `/metrics` on the admin port.
+pub static REGISTRY: LazyLock<Registry> = LazyLock::new(Registry::new);
+
+fn register<T: prometheus::core::Collector + Clone + 'static>(collector: T) -> T {
+    REGISTRY
+        .register(Box::new(collector.clone()))
+        .expect("metric registered twice");
+    collector
+}
+
+pub static HTTP_REQUESTS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register(
+        IntCounterVec::new(
+            Opts::new("http_requests_total", "HTTP requests by route pattern and status"),
+            &["method", "route", "status"],
+        )
+        .unwrap(),
+    )
+});
+
+pub static HTTP_REQUEST_DURATION: LazyLock<HistogramVec> = LazyLock::new(|| {
+    register(
+        HistogramVec::new(
+            HistogramOpts::new("http_request_duration_seconds", "HTTP request latency"),
+            &["method", "route", "status"],
+        )
+        .unwrap(),
+    )
+});
+
+pub static DB_QUERY_DURATION: LazyLock<HistogramVec> = LazyLock::new(|| {
+    register(
+        HistogramVec::new(
+            HistogramOpts::new("db_query_duration_seconds", "Database statement latency")
+                .buckets(vec![0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5]),
+            &["operation", "failed"],
+        )
+        .unwrap(),
+    )
+});
+
+// Kept up to date through `WebSocketConnection`, which every upgraded connection holds.
+pub static WEBSOCKET_CONNECTIONS: LazyLock<IntGauge> = LazyLock::new(|| {
+    register(IntGauge::new("websocket_connections_active", "Open WebSocket connections").unwrap())
+});
+
+pub static LOGIN_ATTEMPTS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register(
+        IntCounterVec::new(
+            Opts::new("login_attempts_total", "Login attempts by method and outcome"),
+            &["method", "outcome"],
+        )
+        .unwrap(),
+    )
+});
+
+pub static PASSWORD_HASH_DURATION: LazyLock<HistogramVec> = LazyLock::new(|| {
+    register(
+        HistogramVec::new(
+            HistogramOpts::new("password_hash_duration_seconds", "Argon2 hashing and verification time")
+                .buckets(vec![0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5]),
+            &["operation"],
+        )
+        .unwrap(),
+    )
+});
+
+/// Registers every metric up front, so `/metrics` lists them before they are first used.
+pub fn init() {
+    LazyLock::force(&HTTP_REQUESTS);
+    LazyLock::force(&HTTP_REQUEST_DURATION);
+    LazyLock::force(&DB_QUERY_DURATION);
+    LazyLock::force(&WEBSOCKET_CONNECTIONS);
+    LazyLock::force(&LOGIN_ATTEMPTS);
+    LazyLock::force(&PASSWORD_HASH_DURATION);
+}
+
+/// Times every statement run through `db`, labeled by its leading SQL keyword.
+pub fn instrument_database(db: &mut DatabaseConnection) {
+    db.set_metric_callback(|info: &metric::Info<'_>| {
+        let operation = info
+            .statement
+            .sql
+            .split_whitespace()
+            .next()
+            .unwrap_or("")
+            .to_ascii_lowercase();
+        DB_QUERY_DURATION
+            .with_label_values(&[operation.as_str(), if info.failed { "true" } else { "false" }])
+            .observe(info.elapsed.as_secs_f64());
+    });
+}
+
+/// Counts one open WebSocket in `WEBSOCKET_CONNECTIONS` until it is dropped, however the
+/// connection ends.
+pub struct WebSocketConnection(());
+
+impl WebSocketConnection {
+    pub fn open() -> Self {
+        WEBSOCKET_CONNECTIONS.inc();
+        Self(())
+    }
+}
+
+impl Drop for WebSocketConnection {
+    fn drop(&mut self) {
+        WEBSOCKET_CONNECTIONS.dec();
+    }
+}
+
+pub fn record_login(method: &str, success: bool) {
+    LOGIN_ATTEMPTS
+        .with_label_values(&[method, if success { "success" } else { "failure" }])
+        .inc();
+}
+
+/// The registry in the Prometheus text exposition format.
+pub fn render() -> String {
+    let mut buffer = Vec::new();
+    TextEncoder::new()
+        .encode(&REGISTRY.gather(), &mut buffer)
+        .expect("text encoding cannot fail");
+    String::from_utf8(buffer).expect("text encoding is UTF-8")
+}
@@ -4,9 +4,11 @@ use actix_web::guard::{Guard, GuardContext};
 use actix_web::{
     body::{BoxBody, MessageBody},
     dev::{Service, ServiceRequest, ServiceResponse, Transform},
+    http::{header::WWW_AUTHENTICATE, StatusCode},
     Error, HttpResponse,
 };
 use futures::future::{ok, LocalBoxFuture, Ready};
+use tracing::Instrument;
 
 use crate::utils::check_auth_user::AuthenticatedUser;
 
@@ -24,6 +26,8 @@ impl RoleGuard {
 
 impl Guard for RoleGuard {
     fn check(&self, ctx: &GuardContext<'_>) -> bool {
+        let _span = tracing::info_span!("role_guard", allowed_roles = ?self.allowed_roles).entered();
+
         // Extract headers from the request
         let headers = ctx.head().headers();
         
@@ -80,7 +84,11 @@ where
         let allowed_roles = self.allowed_roles.clone();
         let service = Rc::clone(&self.service);
         Box::pin(async move {
-            match AuthenticatedUser::from_headers(req.request()) {
+            // The span covers the check only, not the handler behind it.
+            let authenticated = AuthenticatedUser::authenticate(req.request())
+                .instrument(tracing::info_span!("role_guard", allowed_roles = ?allowed_roles))
+                .await;
+            match authenticated {
                 Ok(auth_user) => {
                     log::debug!("Token decoded. Role: {}, Sub: {}", auth_user.0.role, auth_user.0.sub);
                     if allowed_roles.contains(&auth_user.0.role) {
@@ -91,7 +99,14 @@ where
                         log::debug!("Role {} not allowed. Allowed roles: {:?}", auth_user.0.role, allowed_roles);
                     }
                 },
-                Err(_) => {}
+                Err(err) => {
+                    // Suspended and banned accounts are told why they are locked out, and
+                    // clients whose token expired or was revoked that they should sign in again.
+                    let response = err.error_response();
+                    if response.status() == StatusCode::FORBIDDEN || response.headers().contains_key(WWW_AUTHENTICATE) {
+                        return Ok(req.into_response(response));
+                    }
+                }
             }
             let response = HttpResponse::Unauthorized().json("Unauthorized");
             Ok(req.into_response(response))
@@ -1,9 +1,10 @@
 use actix_web::{
     dev::{Service, ServiceRequest, ServiceResponse, Transform},
-    Error,
+    Error, HttpMessage,
 };
 use futures::future::{ok, LocalBoxFuture, Ready};
 use std::time::Instant;
+use crate::utils::check_auth_user::AuthenticatedUserId;
 
 pub struct CustomLogger;
 
@@ -48,13 +49,23 @@ where
         Box::pin(async move {
             let res = fut.await?;
             let status = res.response().status();
-            let elapsed = start.elapsed().as_millis();
+            let elapsed = start.elapsed().as_millis() as u64;
+            let route = res.request().match_pattern().unwrap_or_else(|| "unmatched".to_string());
+            let user_id = res.request().extensions().get::<AuthenticatedUserId>().map(|id| id.0);
 
-            // Log based on status code
+            // Log based on status code; the key-value fields show up in JSON log output.
             if status.is_success() {
-                log::info!("{} {} -> {} ({}ms)", method, uri, status, elapsed);
+                log::info!(
+                    method = method.as_str(), path = uri.path(), route = route.as_str(),
+                    status = status.as_u16(), latency_ms = elapsed, user_id = user_id;
+                    "{} {} -> {} ({}ms)", method, uri, status, elapsed
+                );
             } else {
-                log::error!("{} {} -> {} ({}ms)", method, uri, status, elapsed);
+                log::error!(
+                    method = method.as_str(), path = uri.path(), route = route.as_str(),
+                    status = status.as_u16(), latency_ms = elapsed, user_id = user_id;
+                    "{} {} -> {} ({}ms)", method, uri, status, elapsed
+                );
             }
 
             Ok(res)
@@ -0,0 +1,93 @@
+use actix_web::{
+    dev::{Service, ServiceRequest, ServiceResponse, Transform},
+    http::header::{HeaderName, HeaderValue, LINK},
+    Error,
+};
+use chrono::{DateTime, Utc};
+use futures::future::{ok, LocalBoxFuture, Ready};
+
+pub const DEPRECATION: HeaderName = HeaderName::from_static("deprecation");
+pub const SUNSET: HeaderName = HeaderName::from_static("sunset");
+
+// Marks every response of the routes it wraps as deprecated (RFC 9745), announces when
+// they go away (RFC 8594) and links the same path under `successor`, e.g. `/api/v1`.
+pub struct Deprecated {
+    successor: &'static str,
+    deprecation: HeaderValue,
+    sunset: HeaderValue,
+}
+
+impl Deprecated {
+    pub fn new(successor: &'static str, deprecated_at: DateTime<Utc>, sunset: DateTime<Utc>) -> Self {
+        let deprecation = format!("@{}", deprecated_at.timestamp());
+        let sunset = sunset.format("%a, %d %b %Y %H:%M:%S GMT").to_string();
+        Self {
+            successor,
+            deprecation: HeaderValue::from_str(&deprecation).expect("valid Deprecation header"),
+            sunset: HeaderValue::from_str(&sunset).expect("valid Sunset header"),
+        }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for Deprecated
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = DeprecatedMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(DeprecatedMiddleware {
+            service,
+            successor: self.successor,
+            deprecation: self.deprecation.clone(),
+            sunset: self.sunset.clone(),
+        })
+    }
+}
+
+pub struct DeprecatedMiddleware<S> {
+    service: S,
+    successor: &'static str,
+    deprecation: HeaderValue,
+    sunset: HeaderValue,
+}
+
+impl<S, B> Service<ServiceRequest> for DeprecatedMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    actix_web::dev::forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let link = HeaderValue::from_str(&format!(
+            "<{}{}>; rel=\"successor-version\"",
+            self.successor,
+            req.path()
+        ))
+        .ok();
+        let deprecation = self.deprecation.clone();
+        let sunset = self.sunset.clone();
+        let fut = self.service.call(req);
+
+        Box::pin(async move {
+            let mut res = fut.await?;
+            let headers = res.headers_mut();
+            headers.insert(DEPRECATION, deprecation);
+            headers.insert(SUNSET, sunset);
+            if let Some(link) = link {
+                headers.append(LINK, link);
+            }
+            Ok(res)
+        })
+    }
+}
@@ -1,2 +1,6 @@
 pub mod custom_logger;
-pub mod claims;
\ No newline at end of file
+pub mod claims;
+pub mod deprecation;
+pub mod metrics;
+pub mod request_id;
+pub mod trace;
@@ -0,0 +1,137 @@
+use std::rc::Rc;
+use actix_web::{
+    body::{self, BoxBody, MessageBody},
+    dev::{Service, ServiceRequest, ServiceResponse, Transform},
+    http::header::{self, HeaderName, HeaderValue},
+    Error, HttpMessage,
+};
+use futures::future::{ok, LocalBoxFuture, Ready};
+use serde_json::{json, Value};
+use crate::logging;
+
+pub const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+/// The id of the current request, stored in the request extensions.
+#[derive(Clone)]
+pub struct RequestId(pub String);
+
+// Incoming ids are reused only when they are short and printable.
+fn incoming_id(req: &ServiceRequest) -> Option<String> {
+    req.headers()
+        .get(&REQUEST_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(str::trim)
+        .filter(|id| !id.is_empty() && id.len() <= 128 && id.chars().all(|c| c.is_ascii_graphic()))
+        .map(str::to_string)
+}
+
+// Adds the request id to an error body, turning plain-text and JSON-string bodies into
+// `{ "message": ..., "request_id": ... }`.
+fn with_request_id(body: &[u8], request_id: &str, fallback: &str) -> Value {
+    let mut value = match serde_json::from_slice::<Value>(body) {
+        Ok(Value::Object(map)) => Value::Object(map),
+        Ok(Value::String(message)) => json!({ "message": message }),
+        Ok(other) => json!({ "message": other }),
+        Err(_) if body.is_empty() => json!({ "message": fallback }),
+        Err(_) => json!({ "message": String::from_utf8_lossy(body) }),
+    };
+    value["request_id"] = Value::String(request_id.to_string());
+    value
+}
+
+pub struct AssignRequestId;
+
+impl<S, B> Transform<S, ServiceRequest> for AssignRequestId
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<BoxBody>;
+    type Error = Error;
+    type Transform = AssignRequestIdMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(AssignRequestIdMiddleware { service: Rc::new(service) })
+    }
+}
+
+pub struct AssignRequestIdMiddleware<S> {
+    service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for AssignRequestIdMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<BoxBody>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    actix_web::dev::forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let request_id = incoming_id(&req).unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+        req.extensions_mut().insert(RequestId(request_id.clone()));
+
+        let service = self.service.clone();
+        let fut = logging::with_request_id(request_id.clone(), || service.call(req));
+
+        Box::pin(logging::scope_request_id(request_id.clone(), async move {
+            // Holding on to the request would stop the router from writing the path
+            // parameters into it, so errors are passed on as they are. Handlers and the
+            // middleware in this app answer with error responses instead.
+            let res = fut.await?.map_into_boxed_body();
+
+            let status = res.status();
+            let mut res = if status.is_client_error() || status.is_server_error() {
+                let (http_req, response) = res.into_parts();
+                let (response, body) = response.into_parts();
+                let bytes = body::to_bytes(body).await.unwrap_or_default();
+                let fallback = status.canonical_reason().unwrap_or("Error");
+                let mut response = response.set_body(BoxBody::new(
+                    with_request_id(&bytes, &request_id, fallback).to_string(),
+                ));
+                response.headers_mut().insert(
+                    header::CONTENT_TYPE,
+                    HeaderValue::from_static("application/json"),
+                );
+                ServiceResponse::new(http_req, response)
+            } else {
+                res
+            };
+
+            if let Ok(value) = HeaderValue::from_str(&request_id) {
+                res.headers_mut().insert(REQUEST_ID_HEADER, value);
+            }
+            Ok(res)
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn error_bodies_gain_the_request_id() {
+        assert_eq!(
+            with_request_id(br#"{"message":"User not found"}"#, "abc", "Not Found"),
+            json!({ "message": "User not found", "request_id": "abc" })
+        );
+        assert_eq!(
+            with_request_id(br#""Error: RecordNotFound""#, "abc", "Internal Server Error"),
+            json!({ "message": "Error: RecordNotFound", "request_id": "abc" })
+        );
+        assert_eq!(
+            with_request_id(b"Invalid token", "abc", "Unauthorized"),
+            json!({ "message": "Invalid token", "request_id": "abc" })
+        );
+        assert_eq!(
+            with_request_id(b"", "abc", "Unauthorized"),
+            json!({ "message": "Unauthorized", "request_id": "abc" })
+        );
+    }
+}
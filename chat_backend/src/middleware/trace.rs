@@ -0,0 +1,109 @@
+use actix_web::{
+    dev::{Service, ServiceRequest, ServiceResponse, Transform},
+    http::header::HeaderMap,
+    Error, HttpMessage,
+};
+use futures::future::{ok, LocalBoxFuture, Ready};
+use opentelemetry::global;
+use opentelemetry::propagation::Extractor;
+use tracing::{field, Instrument};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use crate::middleware::request_id::RequestId;
+
+struct HeaderExtractor<'a>(&'a HeaderMap);
+
+impl Extractor for HeaderExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|value| value.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(|name| name.as_str()).collect()
+    }
+}
+
+// Opens a server span for each request, continuing the caller's trace when it sends a
+// `traceparent` header. Must sit inside `AssignRequestId` so the request id is known.
+pub struct TraceRequests;
+
+impl<S, B> Transform<S, ServiceRequest> for TraceRequests
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = TraceRequestsMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(TraceRequestsMiddleware { service })
+    }
+}
+
+pub struct TraceRequestsMiddleware<S> {
+    service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for TraceRequestsMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    actix_web::dev::forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let method = req.method().to_string();
+        let request_id = req
+            .extensions()
+            .get::<RequestId>()
+            .map(|id| id.0.clone())
+            .unwrap_or_default();
+        let span = tracing::info_span!(
+            "HTTP request",
+            otel.name = %method,
+            otel.kind = "server",
+            otel.status_code = field::Empty,
+            http.request.method = %method,
+            http.route = field::Empty,
+            http.response.status_code = field::Empty,
+            url.path = %req.path(),
+            request_id = %request_id,
+        );
+        let parent = global::get_text_map_propagator(|propagator| {
+            propagator.extract(&HeaderExtractor(req.headers()))
+        });
+        // Without a caller context this simply starts a new trace.
+        let _ = span.set_parent(parent);
+
+        // Handlers do part of their work synchronously inside `call`, so the span is
+        // entered for that as well as for the returned future.
+        let fut = {
+            let _entered = span.enter();
+            self.service.call(req)
+        };
+
+        Box::pin(
+            async move {
+                let res = fut.await?;
+                let current = tracing::Span::current();
+                let status = res.response().status();
+                if let Some(route) = res.request().match_pattern() {
+                    current.record("otel.name", format!("{} {}", method, route).as_str());
+                    current.record("http.route", route.as_str());
+                }
+                current.record("http.response.status_code", status.as_u16());
+                if status.is_server_error() {
+                    current.record("otel.status_code", "ERROR");
+                }
+                Ok(res)
+            }
+            .instrument(span),
+        )
+    }
+}
@@ -0,0 +1,68 @@
+use std::collections::HashSet;
+use migration::{Migrator, MigratorTrait};
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, DbErr, TransactionTrait};
+use crate::config::MigrationMode;
+
+// Arbitrary key for the Postgres advisory lock that serializes startup migrations.
+const MIGRATION_LOCK_KEY: i64 = 0x426c_6f63_6b43_6861;
+
+/// Brings the schema up to date according to `mode` before the server starts.
+///
+/// On Postgres, replicas starting at the same time queue on an advisory lock, so only
+/// the first one applies pending migrations and the others find nothing left to do.
+/// SQLite serializes writers on its own. A database that
+/// has migrations this build does not know about was migrated by a newer release, and
+/// starting against it is refused in every mode but `off`.
+pub async fn run(db: &DatabaseConnection, mode: MigrationMode) -> Result<(), DbErr> {
+    if mode == MigrationMode::Off {
+        log::info!("Startup migrations are disabled");
+        return Ok(());
+    }
+
+    let txn = db.begin().await?;
+    if db.get_database_backend() == DbBackend::Postgres {
+        // Held until the transaction ends.
+        txn.execute_unprepared(&format!("SELECT pg_advisory_xact_lock({})", MIGRATION_LOCK_KEY))
+            .await?;
+    }
+
+    let applied: HashSet<String> = Migrator::get_migration_models(&txn)
+        .await?
+        .into_iter()
+        .map(|model| model.version)
+        .collect();
+    let known: Vec<String> = Migrator::migrations()
+        .iter()
+        .map(|migration| migration.name().to_string())
+        .collect();
+
+    let mut unknown: Vec<&String> = applied.iter().filter(|name| !known.contains(name)).collect();
+    if !unknown.is_empty() {
+        unknown.sort();
+        return Err(DbErr::Custom(format!(
+            "The database schema is newer than this build; unknown migrations: {}",
+            unknown.iter().map(|name| name.as_str()).collect::<Vec<_>>().join(", ")
+        )));
+    }
+
+    let pending: Vec<&String> = known.iter().filter(|name| !applied.contains(*name)).collect();
+    if pending.is_empty() {
+        log::info!("Database schema is up to date");
+        return txn.commit().await;
+    }
+    let pending = pending.iter().map(|name| name.as_str()).collect::<Vec<_>>().join(", ");
+
+    match mode {
+        MigrationMode::Auto => {
+            log::info!("Applying migrations: {}", pending);
+            Migrator::up(&txn, None).await?;
+            txn.commit().await?;
+            log::info!("Migrations applied");
+            Ok(())
+        }
+        _ => Err(DbErr::Custom(format!(
+            "Migrations are pending ({}); apply them or set MIGRATION_MODE=auto",
+            pending
+        ))),
+    }
+}
@@ -0,0 +1,44 @@
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use chrono::{DateTime, Utc};
+use crate::entities::data_exports;
+use crate::entities::sea_orm_active_enums::ExportStatus;
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct DataExportResponse {
+    pub id: i32,
+    pub status: ExportStatus,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub downloaded_at: Option<DateTime<Utc>>,
+    /// Only set while the archive is ready and has not been downloaded yet.
+    pub download_url: Option<String>,
+}
+
+impl From<data_exports::Model> for DataExportResponse {
+    fn from(export: data_exports::Model) -> Self {
+        Self {
+            id: export.id,
+            status: export.status,
+            error: export.error,
+            created_at: export.created_at.with_timezone(&Utc),
+            completed_at: export.completed_at.map(|dt| dt.with_timezone(&Utc)),
+            expires_at: export.expires_at.map(|dt| dt.with_timezone(&Utc)),
+            downloaded_at: export.downloaded_at.map(|dt| dt.with_timezone(&Utc)),
+            download_url: None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct GetAllExportsResponse {
+    pub exports: Vec<DataExportResponse>,
+}
+
+#[derive(Debug, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DownloadExport {
+    pub token: String,
+}
@@ -0,0 +1,37 @@
+use serde::Serialize;
+use utoipa::ToSchema;
+
+#[derive(Serialize, ToSchema)]
+pub struct HealthResponse {
+    pub status: &'static str,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct CheckResult {
+    pub ok: bool,
+    pub latency_ms: u128,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct MigrationCheck {
+    #[serde(flatten)]
+    pub result: CheckResult,
+    /// Names of migrations known to `migration::Migrator` that the database has not run.
+    pub pending: Vec<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ReadinessChecks {
+    pub database: CheckResult,
+    pub migrations: MigrationCheck,
+    pub blob_store: CheckResult,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ReadinessResponse {
+    /// "ready" or "not_ready"
+    pub status: &'static str,
+    pub checks: ReadinessChecks,
+}
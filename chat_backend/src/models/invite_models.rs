@@ -0,0 +1,44 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use chrono::{DateTime, Utc};
+use crate::entities::invite_codes;
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct CreateInvite {
+    /// Generated when omitted.
+    pub code: Option<String>,
+    pub max_uses: Option<i32>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct InviteResponse {
+    pub id: i32,
+    pub code: String,
+    pub created_by: Option<i32>,
+    pub max_uses: Option<i32>,
+    pub uses: i32,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<invite_codes::Model> for InviteResponse {
+    fn from(invite: invite_codes::Model) -> Self {
+        Self {
+            id: invite.id,
+            code: invite.code,
+            created_by: invite.created_by,
+            max_uses: invite.max_uses,
+            uses: invite.uses,
+            expires_at: invite.expires_at.map(|dt| dt.with_timezone(&Utc)),
+            revoked_at: invite.revoked_at.map(|dt| dt.with_timezone(&Utc)),
+            created_at: invite.created_at.with_timezone(&Utc),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct GetAllInvitesResponse {
+    pub invites: Vec<InviteResponse>,
+}
@@ -1,2 +1,8 @@
 pub mod user_models;
 pub mod token_model;
+pub mod passkey_models;
+pub mod invite_models;
+pub mod session_models;
+pub mod export_models;
+pub mod audit_models;
+pub mod health_models;
@@ -0,0 +1,35 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use chrono::{DateTime, Utc};
+use crate::entities::user_sessions;
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct SessionResponse {
+    pub id: i32,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    /// Whether this is the session making the request.
+    pub current: bool,
+}
+
+impl SessionResponse {
+    pub fn new(session: user_sessions::Model, current_jti: &str) -> Self {
+        Self {
+            id: session.id,
+            current: session.jti == current_jti,
+            user_agent: session.user_agent,
+            ip_address: session.ip_address,
+            created_at: session.created_at.with_timezone(&Utc),
+            last_seen_at: session.last_seen_at.with_timezone(&Utc),
+            expires_at: session.expires_at.with_timezone(&Utc),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct GetAllSessionsResponse {
+    pub sessions: Vec<SessionResponse>,
+}
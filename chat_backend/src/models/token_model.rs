@@ -6,4 +6,21 @@ pub struct Claims {
     pub sub: String,
     pub role: String,
     pub exp: usize,
+    // Identifies the row in `user_sessions` this token belongs to.
+    pub jti: String,
+}
+
+// Payload of the link mailed to confirm an email address.
+#[derive(Deserialize, Serialize)]
+pub struct EmailVerificationClaims {
+    pub user_id: i32,
+    pub email: String,
+    pub exp: usize,
+}
+
+// Payload of the one-time download link for a data export.
+#[derive(Deserialize, Serialize)]
+pub struct ExportDownloadClaims {
+    pub export_id: i32,
+    pub exp: usize,
 }
@@ -1,22 +1,29 @@
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 use chrono::{DateTime, Utc};
+use crate::entities::sea_orm_active_enums::AccountStatus;
 use crate::entities::users;
 use base64::{engine::general_purpose, Engine as _};
 
-#[derive(Serialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct ChatInfo {
     pub chat_name: String,
-    pub author_id: i32,
-    pub author_username: String,
+    pub author_id: Option<i32>,
+    pub author_username: Option<String>,
 }
 
 // Response struct for a user (without sensitive data).
-#[derive(Serialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct UserResponse {
     pub id: i32,
     pub first_name: String,
     pub last_name: String,
     pub username: String,
+    pub email: Option<String>,
+    pub email_verified: bool,
+    pub status: AccountStatus,
+    pub status_reason: Option<String>,
+    pub status_until: Option<DateTime<Utc>>,
     pub role_id: Option<i32>,
     pub avatar: Option<String>,
     pub created_at: Option<DateTime<Utc>>,
@@ -30,6 +37,11 @@ impl From<users::Model> for UserResponse {
             first_name: user.first_name,
             last_name: user.last_name,
             username: user.username,
+            email: user.email,
+            email_verified: user.email_verified_at.is_some(),
+            status: user.status,
+            status_reason: user.status_reason,
+            status_until: user.status_until.map(|dt| dt.with_timezone(&Utc)),
             role_id: user.role_id,
             avatar: user.avatar.map(|data| general_purpose::STANDARD.encode(data)),
             created_at: user.created_at.map(|dt| dt.with_timezone(&Utc)),
@@ -39,12 +51,13 @@ impl From<users::Model> for UserResponse {
 }
 
 
-#[derive(Serialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct GetAllUsersResponse {
     pub users: Vec<UserResponse>,
 }
 
-#[derive(Deserialize)]
+#[derive(Debug, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct UserFilter {
     pub first_name: Option<String>,
     pub last_name: Option<String>,
@@ -53,41 +66,98 @@ pub struct UserFilter {
     pub author_username: Option<String>,
 }
 
-#[derive(Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct CreateUser {
     pub first_name: String,
     pub last_name: String,
     pub username: String,
     pub password: String,
+    pub email: Option<String>,
     pub role_id: Option<i32>,
     pub avatar: Option<String>,
 }
 
-#[derive(Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct UpdateUser {
     pub first_name: Option<String>,
     pub last_name: Option<String>,
     pub username: Option<String>,
+    pub email: Option<String>,
     pub role_id: Option<i32>,
     pub avatar: Option<String>,
     pub password: Option<String>,
 }
 
-#[derive(Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct RegisterUser {
     pub first_name: String,
     pub last_name: String,
     pub username: String,
     pub password: String,
+    pub email: Option<String>,
+    pub invite_code: Option<String>,
 }
 
-#[derive(Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct LoginUser {
     pub username: String,
     pub password: String,
 }
 
-#[derive(Serialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ForgotPassword {
+    pub email: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ResetPassword {
+    pub token: String,
+    pub password: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct VerifyEmail {
+    pub token: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ModerateUser {
+    pub reason: String,
+    /// Lifted automatically once reached; indefinite when omitted.
+    pub until: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct EraseAccount {
+    /// Re-entered to confirm erasing one's own account.
+    pub password: String,
+}
+
+// Returned by `login` when the account is suspended or banned.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct AccountRestrictedResponse {
+    pub message: String,
+    pub status: AccountStatus,
+    pub reason: Option<String>,
+    pub until: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct ResponseMessage {
     pub message: String,
 }
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct LoginResponse {
+    pub message: String,
+    /// Bearer token for the `Authorization` header.
+    pub token: String,
+}
+
+/// Body of every 4xx and 5xx response. `AssignRequestId` adds `request_id` to whatever
+/// the handler returned, so some errors carry further fields.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ErrorResponse {
+    pub message: String,
+    pub request_id: String,
+}
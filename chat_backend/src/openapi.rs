@@ -0,0 +1,94 @@
+use utoipa::openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::{Modify, OpenApi};
+use crate::handlers::{
+    audit_handler, erasure_handler, export_handler, health_handler, invite_handler, moderation_handler,
+    passkey_handler, password_handler, session_handler, user_handler, verification_handler,
+};
+
+// The API description served at `/openapi.json`. Request and response bodies come from the
+// `ToSchema` derives in `models`, so a handler only needs its `#[utoipa::path]` listed here.
+// `tests/openapi.rs` fails when the committed `openapi.json` no longer matches.
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "BlockChat API",
+        version = "1.0.0",
+        description = "Accounts, sessions and administration for BlockChat. Errors share the `ErrorResponse` envelope."
+    ),
+    paths(health_handler::healthz, health_handler::readyz),
+    nest((path = "/api/v1", api = V1Api)),
+    modifiers(&BearerAuth),
+    tags(
+        (name = "auth", description = "Registration, login and account recovery"),
+        (name = "passkeys", description = "WebAuthn registration and login"),
+        (name = "users", description = "Profiles and account lifecycle"),
+        (name = "sessions", description = "Signed-in devices"),
+        (name = "exports", description = "Personal data exports"),
+        (name = "moderation", description = "Suspensions and bans (admin)"),
+        (name = "invites", description = "Registration invite codes (admin)"),
+        (name = "audit", description = "Audit trail of privileged actions (admin)"),
+        (name = "health", description = "Liveness and readiness probes"),
+    )
+)]
+pub struct ApiDoc;
+
+// The routes of `routes::v1`, documented under their `/api/v1` prefix only; the
+// deprecated unversioned aliases are left out.
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        user_handler::register,
+        user_handler::login,
+        password_handler::forgot_password,
+        password_handler::reset_password,
+        verification_handler::verify_email,
+        verification_handler::resend_verification,
+        passkey_handler::register_start,
+        passkey_handler::register_finish,
+        passkey_handler::login_start,
+        passkey_handler::login_finish,
+        session_handler::get_my_sessions,
+        session_handler::revoke_my_session,
+        export_handler::get_my_exports,
+        export_handler::request_export,
+        export_handler::get_my_export,
+        export_handler::download_export,
+        erasure_handler::erase_my_account,
+        user_handler::get_users,
+        user_handler::create_user,
+        moderation_handler::suspend_user,
+        moderation_handler::ban_user,
+        moderation_handler::reinstate_user,
+        user_handler::restore_user,
+        erasure_handler::erase_user,
+        session_handler::get_user_sessions,
+        session_handler::revoke_user_session,
+        user_handler::get_me,
+        user_handler::get_user,
+        user_handler::update_user,
+        user_handler::delete_user,
+        invite_handler::get_invites,
+        invite_handler::create_invite,
+        invite_handler::revoke_invite,
+        audit_handler::get_audit_events,
+    )
+)]
+struct V1Api;
+
+// The JWT issued by `login`, sent as `Authorization: Bearer <token>`.
+struct BearerAuth;
+
+impl Modify for BearerAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer_auth",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("JWT")
+                    .build(),
+            ),
+        );
+    }
+}
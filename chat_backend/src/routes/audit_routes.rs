@@ -0,0 +1,15 @@
+use actix_web::web;
+use crate::handlers::audit_handler;
+use crate::middleware::claims::RoleGuard;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/audit-events")
+            // Admin-only: browsing the audit trail of privileged actions
+            .wrap(RoleGuard::new(vec!["admin"]))
+            .service(
+                web::resource("")
+                    .route(web::get().to(audit_handler::get_audit_events))
+            )
+    );
+}
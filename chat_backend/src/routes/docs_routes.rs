@@ -0,0 +1,9 @@
+use actix_web::web;
+use utoipa::OpenApi;
+use utoipa_swagger_ui::SwaggerUi;
+use crate::openapi::ApiDoc;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    // Public: the spec at `/openapi.json` and Swagger UI over it at `/docs/`.
+    cfg.service(SwaggerUi::new("/docs/{_:.*}").url("/openapi.json", ApiDoc::openapi()));
+}
@@ -0,0 +1,22 @@
+use actix_web::web;
+use crate::handlers::graphql_handler;
+use crate::middleware::claims::RoleGuard;
+use crate::routes::V1;
+use crate::utils::messages::CHAT_API_ROLES;
+
+// Registered with full paths ahead of the `V1` scope: GraphQL was never served at the
+// root, so it has no deprecated alias.
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::resource(format!("{}/graphql", V1))
+            .wrap(RoleGuard::new(CHAT_API_ROLES.to_vec()))
+            .route(web::post().to(graphql_handler::graphql))
+    )
+    // Authenticates the upgrade request or the `connection_init` message itself.
+    .service(
+        web::resource(format!("{}/graphql/ws", V1))
+            .route(web::get().to(graphql_handler::graphql_ws))
+    )
+    // Public, like the REST docs.
+    .route("/graphiql", web::get().to(graphql_handler::graphiql));
+}
@@ -0,0 +1,8 @@
+use actix_web::web;
+use crate::handlers::health_handler;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    // Public probes for the orchestrator; they reveal nothing about users.
+    cfg.route("/healthz", web::get().to(health_handler::healthz))
+        .route("/readyz", web::get().to(health_handler::readyz));
+}
@@ -0,0 +1,20 @@
+use actix_web::web;
+use crate::handlers::invite_handler;
+use crate::middleware::claims::RoleGuard;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/invites")
+            // Admin-only: managing invite codes for invite-only registration
+            .wrap(RoleGuard::new(vec!["admin"]))
+            .service(
+                web::resource("")
+                    .route(web::get().to(invite_handler::get_invites))
+                    .route(web::post().to(invite_handler::create_invite))
+            )
+            .service(
+                web::resource("/{id:\\d+}")
+                    .route(web::delete().to(invite_handler::revoke_invite))
+            )
+    );
+}
@@ -1 +1,30 @@
+use actix_web::web;
+
 pub mod user_routes;
+pub mod invite_routes;
+pub mod audit_routes;
+pub mod health_routes;
+pub mod docs_routes;
+pub mod graphql_routes;
+
+// Prefix of the current API version.
+pub const V1: &str = "/api/v1";
+
+// API version 1, mounted under `V1` and, deprecated, at the root where it started out.
+//
+// A later version lists only the routes it changes, as resources with their full path,
+// and then configures the version before it for everything else. The router tries them
+// in that order, so the new resources shadow the old ones:
+//
+//     pub fn v2(cfg: &mut web::ServiceConfig) {
+//         cfg.service(web::resource("/users/me").route(web::get().to(v2_handler::get_me)))
+//             .configure(v1);
+//     }
+//
+// A shadowing resource answers for every method on its path, so it must route the
+// methods it does not change to their old handlers too.
+pub fn v1(cfg: &mut web::ServiceConfig) {
+    cfg.configure(user_routes::configure)
+        .configure(invite_routes::configure)
+        .configure(audit_routes::configure);
+}
@@ -1,5 +1,8 @@
 use actix_web::web;
-use crate::handlers::user_handler;
+use crate::handlers::{
+    erasure_handler, export_handler, moderation_handler, passkey_handler, password_handler, session_handler, user_handler,
+    verification_handler,
+};
 use crate::middleware::claims::RoleGuard;
 
 pub fn configure(cfg: &mut web::ServiceConfig) {
@@ -8,10 +11,74 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
             // Public endpoints – no guard attached.
             .route("/register", web::post().to(user_handler::register))
             .route("/login", web::post().to(user_handler::login))
+            .route("/passkeys/login/start", web::post().to(passkey_handler::login_start))
+            .route("/passkeys/login/finish", web::post().to(passkey_handler::login_finish))
+            .route("/password/forgot", web::post().to(password_handler::forgot_password))
+            .route("/password/reset", web::post().to(password_handler::reset_password))
+            .route("/verify-email", web::post().to(verification_handler::verify_email))
+
+            // Reachable with the limited token issued to accounts awaiting verification
+            .service(
+                web::resource("/verify-email/resend")
+                    .wrap(RoleGuard::new(vec!["admin", "user", "unverified"]))
+                    .route(web::post().to(verification_handler::resend_verification))
+            )
+
+            // Passkey registration for the signed-in user
+            .service(
+                web::resource("/passkeys/register/start")
+                    .wrap(RoleGuard::new(vec!["admin", "user"]))
+                    .route(web::post().to(passkey_handler::register_start))
+            )
+            .service(
+                web::resource("/passkeys/register/finish")
+                    .wrap(RoleGuard::new(vec!["admin", "user"]))
+                    .route(web::post().to(passkey_handler::register_finish))
+            )
+
+            // Profile of the signed-in user
+            .service(
+                web::resource("/me")
+                    .wrap(RoleGuard::new(vec!["admin", "user"]))
+                    .route(web::get().to(user_handler::get_me))
+            )
+
+            // Sessions of the signed-in user
+            .service(
+                web::resource("/sessions")
+                    .wrap(RoleGuard::new(vec!["admin", "user"]))
+                    .route(web::get().to(session_handler::get_my_sessions))
+            )
+            .service(
+                web::resource("/sessions/{session_id:\\d+}")
+                    .wrap(RoleGuard::new(vec!["admin", "user"]))
+                    .route(web::delete().to(session_handler::revoke_my_session))
+            )
+
+            // Personal data exports of the signed-in user
+            .service(
+                web::resource("/exports")
+                    .wrap(RoleGuard::new(vec!["admin", "user"]))
+                    .route(web::get().to(export_handler::get_my_exports))
+                    .route(web::post().to(export_handler::request_export))
+            )
+            .service(
+                web::resource("/exports/{export_id:\\d+}")
+                    .wrap(RoleGuard::new(vec!["admin", "user"]))
+                    .route(web::get().to(export_handler::get_my_export))
+            )
+            // The signed token in the query string authorizes the download.
+            .route("/exports/download", web::get().to(export_handler::download_export))
+            .service(
+                web::resource("/erase")
+                    .wrap(RoleGuard::new(vec!["admin", "user"]))
+                    .route(web::post().to(erasure_handler::erase_my_account))
+            )
             
             // Admin-only endpoints with their own resources
             .service(
                 web::resource("/all")
+                    .wrap(RoleGuard::new(vec!["admin"]))
                     .route(web::get().to(user_handler::get_users))
             )
             .service(
@@ -19,6 +86,41 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
                     .wrap(RoleGuard::new(vec!["admin"]))
                     .route(web::post().to(user_handler::create_user))
             )
+            .service(
+                web::resource("/{id:\\d+}/suspend")
+                    .wrap(RoleGuard::new(vec!["admin"]))
+                    .route(web::post().to(moderation_handler::suspend_user))
+            )
+            .service(
+                web::resource("/{id:\\d+}/ban")
+                    .wrap(RoleGuard::new(vec!["admin"]))
+                    .route(web::post().to(moderation_handler::ban_user))
+            )
+            .service(
+                web::resource("/{id:\\d+}/reinstate")
+                    .wrap(RoleGuard::new(vec!["admin"]))
+                    .route(web::post().to(moderation_handler::reinstate_user))
+            )
+            .service(
+                web::resource("/{id:\\d+}/restore")
+                    .wrap(RoleGuard::new(vec!["admin"]))
+                    .route(web::post().to(user_handler::restore_user))
+            )
+            .service(
+                web::resource("/{id:\\d+}/erase")
+                    .wrap(RoleGuard::new(vec!["admin"]))
+                    .route(web::post().to(erasure_handler::erase_user))
+            )
+            .service(
+                web::resource("/{id:\\d+}/sessions")
+                    .wrap(RoleGuard::new(vec!["admin"]))
+                    .route(web::get().to(session_handler::get_user_sessions))
+            )
+            .service(
+                web::resource("/{id:\\d+}/sessions/{session_id:\\d+}")
+                    .wrap(RoleGuard::new(vec!["admin"]))
+                    .route(web::delete().to(session_handler::revoke_user_session))
+            )
             
             // Single resource for user ID operations with different role guards
             .service(
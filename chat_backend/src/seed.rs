@@ -1,35 +1,316 @@
-use sea_orm::{entity::*, query::*, DatabaseConnection, DbErr};
-use crate::entities::roles;
+use std::collections::HashMap;
+use std::path::Path;
+use sea_orm::{entity::*, query::*, DatabaseConnection, DatabaseTransaction, DbErr};
+use serde::Deserialize;
+use crate::config::Config;
+use crate::entities::sea_orm_active_enums::AccountStatus;
+use crate::entities::{chat_participants, chats, messages, roles, users};
+use crate::handlers::user_handler::normalize_email;
+use crate::utils::password::hash_password;
 
-pub async fn seed_roles(db: &DatabaseConnection) -> Result<(), DbErr> {
-    // Check if the roles table is empty
-    let role_count = roles::Entity::find().count(db).await?;
+// Built-in profiles, selected by `SEED_PROFILE`.
+const PROFILES: &[(&str, &str)] = &[
+    ("production-minimal", include_str!("../fixtures/production-minimal.toml")),
+    ("demo", include_str!("../fixtures/demo.toml")),
+    ("e2e-test", include_str!("../fixtures/e2e-test.toml")),
+];
 
-    if role_count == 0 {
-        // Roles to seed
-        let roles_to_add = vec![
-            roles::ActiveModel {
-                name: Set(String::from("chat_admin")),
-                ..Default::default()
-            },
+/// Seed data, read from TOML or JSON. Every entry is keyed on a natural key (role name,
+/// username, chat name, or chat + author + content for messages), so applying the same
+/// fixture twice changes nothing.
+///
+/// Roles are inserted in file order, and `register` assumes the third one is `user`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Fixture {
+    #[serde(default)]
+    pub roles: Vec<RoleFixture>,
+    #[serde(default)]
+    pub users: Vec<UserFixture>,
+    #[serde(default)]
+    pub chats: Vec<ChatFixture>,
+    #[serde(default)]
+    pub messages: Vec<MessageFixture>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RoleFixture {
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UserFixture {
+    pub username: String,
+    pub first_name: String,
+    pub last_name: String,
+    pub email: Option<String>,
+    pub role: String,
+    // Exactly one of `password` (hashed while seeding) and `password_hash` (stored as is).
+    // Like `status`, it only applies when the account is created.
+    pub password: Option<String>,
+    pub password_hash: Option<String>,
+    #[serde(default = "active")]
+    pub status: AccountStatus,
+}
+
+fn active() -> AccountStatus {
+    AccountStatus::Active
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ChatFixture {
+    pub name: String,
+    pub author: Option<String>,
+    // Usernames of the chat's participants.
+    #[serde(default)]
+    pub members: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MessageFixture {
+    pub chat: String,
+    pub author: String,
+    pub content: String,
+}
+
+impl Fixture {
+    /// One of the built-in profiles: `production-minimal`, `demo` or `e2e-test`.
+    pub fn profile(name: &str) -> Result<Self, String> {
+        let (_, source) = PROFILES
+            .iter()
+            .find(|(profile, _)| *profile == name)
+            .ok_or_else(|| format!("Unknown seed profile: {}", name))?;
+        toml::from_str(source).map_err(|e| format!("Invalid {} profile: {}", name, e))
+    }
+
+    /// A fixture file; `.json` files are read as JSON and anything else as TOML.
+    pub fn from_file(path: &Path) -> Result<Self, String> {
+        let source = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let parsed = if path.extension().is_some_and(|ext| ext == "json") {
+            serde_json::from_str(&source).map_err(|e| e.to_string())
+        } else {
+            toml::from_str(&source).map_err(|e| e.to_string())
+        };
+        parsed.map_err(|e| format!("Invalid fixture {}: {}", path.display(), e))
+    }
+}
+
+/// The fixture selected by `SEED_FILE`, or else by `SEED_PROFILE`.
+pub fn from_config(config: &Config) -> Result<Fixture, String> {
+    match &config.seed_file {
+        Some(path) => Fixture::from_file(Path::new(path)),
+        None => Fixture::profile(&config.seed_profile),
+    }
+}
+
+/// Applies `fixture` in one transaction.
+pub async fn apply(db: &DatabaseConnection, fixture: &Fixture) -> Result<(), DbErr> {
+    let txn = db.begin().await?;
+    let role_ids = upsert_roles(&txn, &fixture.roles).await?;
+    let user_ids = upsert_users(&txn, &fixture.users, &role_ids).await?;
+    let chat_ids = upsert_chats(&txn, &fixture.chats, &user_ids).await?;
+    insert_messages(&txn, &fixture.messages, &chat_ids, &user_ids).await?;
+    txn.commit().await?;
+    log::info!(
+        "Seeded {} roles, {} users, {} chats and {} messages",
+        fixture.roles.len(),
+        fixture.users.len(),
+        fixture.chats.len(),
+        fixture.messages.len()
+    );
+    Ok(())
+}
+
+fn lookup(ids: &HashMap<String, i32>, kind: &str, key: &str) -> Result<i32, DbErr> {
+    ids.get(key)
+        .copied()
+        .ok_or_else(|| DbErr::Custom(format!("Fixture refers to unknown {} {:?}", kind, key)))
+}
+
+async fn upsert_roles(
+    txn: &DatabaseTransaction,
+    fixtures: &[RoleFixture],
+) -> Result<HashMap<String, i32>, DbErr> {
+    for fixture in fixtures {
+        let exists = roles::Entity::find()
+            .filter(roles::Column::Name.eq(fixture.name.as_str()))
+            .one(txn)
+            .await?
+            .is_some();
+        if !exists {
             roles::ActiveModel {
-                name: Set(String::from("admin")),
+                name: Set(fixture.name.clone()),
                 ..Default::default()
-            },
-            roles::ActiveModel {
-                name: Set(String::from("user")),
+            }
+            .insert(txn)
+            .await?;
+        }
+    }
+    // Users may refer to roles that were seeded earlier rather than by this fixture.
+    Ok(roles::Entity::find()
+        .all(txn)
+        .await?
+        .into_iter()
+        .map(|role| (role.name, role.id))
+        .collect())
+}
+
+async fn upsert_users(
+    txn: &DatabaseTransaction,
+    fixtures: &[UserFixture],
+    role_ids: &HashMap<String, i32>,
+) -> Result<HashMap<String, i32>, DbErr> {
+    let mut ids = HashMap::new();
+    for fixture in fixtures {
+        let role_id = lookup(role_ids, "role", &fixture.role)?;
+        let existing = users::Entity::find()
+            .filter(users::Column::Username.eq(fixture.username.as_str()))
+            .one(txn)
+            .await?;
+
+        if fixture.password.is_some() == fixture.password_hash.is_some() {
+            return Err(DbErr::Custom(format!(
+                "User {:?} needs exactly one of password and password_hash",
+                fixture.username
+            )));
+        }
+
+        // Password and status only seed new accounts. Users change the one and admins
+        // the other, and applying the fixture again on startup must not undo either.
+        let is_new = existing.is_none();
+        let mut user: users::ActiveModel = match existing {
+            Some(user) => user.into(),
+            None => users::ActiveModel {
+                username: Set(fixture.username.clone()),
+                password: Set(match &fixture.password {
+                    Some(plain) => hash_password(plain)
+                        .map_err(|e| DbErr::Custom(format!("Password hashing error: {}", e)))?,
+                    None => fixture.password_hash.clone().unwrap_or_default(),
+                }),
+                status: Set(fixture.status),
                 ..Default::default()
             },
-        ];
+        };
+        user.first_name = Set(fixture.first_name.clone());
+        user.last_name = Set(fixture.last_name.clone());
+        user.email = Set(normalize_email(fixture.email.clone()));
+        user.role_id = Set(Some(role_id));
+        let user = if is_new {
+            user.insert(txn).await?
+        } else {
+            user.update(txn).await?
+        };
+        ids.insert(user.username, user.id);
+    }
+    Ok(ids)
+}
 
-        // Insert roles into the database
-        for role in roles_to_add {
-            roles::Entity::insert(role).exec(db).await?;
+async fn upsert_chats(
+    txn: &DatabaseTransaction,
+    fixtures: &[ChatFixture],
+    user_ids: &HashMap<String, i32>,
+) -> Result<HashMap<String, i32>, DbErr> {
+    let mut ids = HashMap::new();
+    for fixture in fixtures {
+        let author_id = match &fixture.author {
+            Some(author) => Some(lookup(user_ids, "user", author)?),
+            None => None,
+        };
+        let chat = match chats::Entity::find()
+            .filter(chats::Column::Name.eq(fixture.name.as_str()))
+            .one(txn)
+            .await?
+        {
+            Some(chat) if chat.author_id == author_id => chat,
+            Some(chat) => {
+                let mut chat: chats::ActiveModel = chat.into();
+                chat.author_id = Set(author_id);
+                chat.update(txn).await?
+            }
+            None => {
+                chats::ActiveModel {
+                    name: Set(fixture.name.clone()),
+                    author_id: Set(author_id),
+                    ..Default::default()
+                }
+                .insert(txn)
+                .await?
+            }
+        };
+
+        for member in &fixture.members {
+            let user_id = lookup(user_ids, "user", member)?;
+            let is_member = chat_participants::Entity::find()
+                .filter(chat_participants::Column::ChatId.eq(chat.id))
+                .filter(chat_participants::Column::UserId.eq(user_id))
+                .one(txn)
+                .await?
+                .is_some();
+            if !is_member {
+                chat_participants::ActiveModel {
+                    chat_id: Set(chat.id),
+                    user_id: Set(user_id),
+                    ..Default::default()
+                }
+                .insert(txn)
+                .await?;
+            }
         }
-        println!("Roles seeded successfully.");
-    } else {
-        println!("Roles already exist. Skipping seeding.");
+        ids.insert(chat.name, chat.id);
     }
+    Ok(ids)
+}
 
+async fn insert_messages(
+    txn: &DatabaseTransaction,
+    fixtures: &[MessageFixture],
+    chat_ids: &HashMap<String, i32>,
+    user_ids: &HashMap<String, i32>,
+) -> Result<(), DbErr> {
+    for fixture in fixtures {
+        let chat_id = lookup(chat_ids, "chat", &fixture.chat)?;
+        let user_id = lookup(user_ids, "user", &fixture.author)?;
+        let exists = messages::Entity::find()
+            .filter(messages::Column::ChatId.eq(chat_id))
+            .filter(messages::Column::UserId.eq(user_id))
+            .filter(messages::Column::Content.eq(fixture.content.as_str()))
+            .one(txn)
+            .await?
+            .is_some();
+        if !exists {
+            messages::ActiveModel {
+                chat_id: Set(chat_id),
+                user_id: Set(Some(user_id)),
+                content: Set(fixture.content.clone()),
+                ..Default::default()
+            }
+            .insert(txn)
+            .await?;
+        }
+    }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn built_in_profiles_parse() {
+        for (name, _) in PROFILES {
+            Fixture::profile(name).unwrap();
+        }
+    }
+
+    #[test]
+    fn production_minimal_keeps_the_role_order_register_relies_on() {
+        let fixture = Fixture::profile("production-minimal").unwrap();
+        let names: Vec<&str> = fixture.roles.iter().map(|role| role.name.as_str()).collect();
+        assert_eq!(names, ["chat_admin", "admin", "user"]);
+    }
+}
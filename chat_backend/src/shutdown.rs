@@ -0,0 +1,70 @@
+use std::future::Future;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+use tokio_util::task::TaskTracker;
+
+/// Coordinates a graceful stop: periodic jobs watch the token and stop before their next
+/// run, and background work spawned through [`Shutdown::spawn`] is waited for before the
+/// database pool is closed.
+///
+/// Realtime endpoints close their sockets with code 1012 (service restart) when the
+/// [`Shutdown::token`] is cancelled, so clients reconnect to another instance.
+#[derive(Clone, Default)]
+pub struct Shutdown {
+    token: CancellationToken,
+    tasks: TaskTracker,
+}
+
+impl Shutdown {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts shutting down; new job runs are skipped from here on.
+    pub fn trigger(&self) {
+        self.token.cancel();
+    }
+
+    /// A token that is cancelled once shutdown starts.
+    pub fn token(&self) -> CancellationToken {
+        self.token.clone()
+    }
+
+    /// Runs `task` in the background and keeps shutdown waiting until it finishes.
+    pub fn spawn<F>(&self, task: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        self.tasks.spawn(task);
+    }
+
+    /// Waits up to `deadline` for tracked work to finish. Returns `false` if some was
+    /// still running when the deadline passed.
+    pub async fn drain(&self, deadline: Duration) -> bool {
+        self.trigger();
+        self.tasks.close();
+        tokio::time::timeout(deadline, self.tasks.wait()).await.is_ok()
+    }
+}
+
+/// Resolves on SIGTERM or Ctrl-C.
+pub async fn signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                tokio::select! {
+                    _ = terminate.recv() => {}
+                    _ = tokio::signal::ctrl_c() => {}
+                }
+                return;
+            }
+            Err(e) => log::error!("Failed to listen for SIGTERM: {}", e),
+        }
+    }
+    if let Err(e) = tokio::signal::ctrl_c().await {
+        log::error!("Failed to listen for Ctrl-C: {}", e);
+        std::future::pending::<()>().await;
+    }
+}
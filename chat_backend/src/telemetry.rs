@@ -0,0 +1,138 @@
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::sync::Mutex;
+use std::time::UNIX_EPOCH;
+use opentelemetry::trace::{Status, TracerProvider};
+use opentelemetry::{global, KeyValue};
+use opentelemetry_otlp::{Protocol, WithExportConfig};
+use opentelemetry_sdk::error::{OTelSdkError, OTelSdkResult};
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::trace::{SdkTracerProvider, SpanData, SpanExporter};
+use opentelemetry_sdk::Resource;
+use serde_json::{json, Map, Value};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::{EnvFilter, Layer};
+use crate::config::TracingConfig;
+
+/// Sets up span export according to `OTEL_TRACES_EXPORTER` and installs the W3C
+/// `traceparent` propagator. Returns the provider so it can be flushed on shutdown,
+/// or `None` when tracing is off.
+///
+/// The `otlp` exporter sends OTLP over HTTP and reads the standard
+/// `OTEL_EXPORTER_OTLP_*` variables for its endpoint and headers.
+pub fn init(config: &TracingConfig) -> Result<Option<SdkTracerProvider>, String> {
+    let resource = Resource::builder().with_service_name("blockchat").build();
+    let provider = match config.exporter.as_str() {
+        "none" | "" => return Ok(None),
+        "otlp" => {
+            let exporter = opentelemetry_otlp::SpanExporter::builder()
+                .with_http()
+                .with_protocol(Protocol::HttpBinary)
+                .build()
+                .map_err(|e| format!("Failed to build the OTLP exporter: {}", e))?;
+            SdkTracerProvider::builder()
+                .with_batch_exporter(exporter)
+                .with_resource(resource)
+                .build()
+        }
+        "file" => {
+            let exporter = FileSpanExporter::open(&config.file)
+                .map_err(|e| format!("Failed to open {}: {}", config.file, e))?;
+            SdkTracerProvider::builder()
+                .with_batch_exporter(exporter)
+                .with_resource(resource)
+                .build()
+        }
+        other => return Err(format!("Unknown traces exporter: {}", other)),
+    };
+
+    global::set_text_map_propagator(TraceContextPropagator::new());
+    global::set_tracer_provider(provider.clone());
+
+    let filter = EnvFilter::try_new(&config.filter)
+        .map_err(|e| format!("Invalid trace filter {:?}: {}", config.filter, e))?;
+    let subscriber = tracing_subscriber::registry().with(
+        tracing_opentelemetry::layer()
+            .with_tracer(provider.tracer("chat_backend"))
+            .with_filter(filter),
+    );
+    // Logging stays on the `log` facade, so only spans go through this subscriber.
+    tracing::subscriber::set_global_default(subscriber)
+        .map_err(|e| format!("Failed to install the trace subscriber: {}", e))?;
+
+    Ok(Some(provider))
+}
+
+/// Exports whatever spans are still buffered.
+pub fn shutdown(provider: Option<SdkTracerProvider>) {
+    if let Some(provider) = provider {
+        if let Err(e) = provider.shutdown() {
+            log::error!("Failed to flush traces: {}", e);
+        }
+    }
+}
+
+// Writes finished spans to a local file, one JSON object per line, for development
+// without a collector.
+#[derive(Debug)]
+struct FileSpanExporter {
+    file: Mutex<File>,
+}
+
+impl FileSpanExporter {
+    fn open(path: &str) -> std::io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self { file: Mutex::new(file) })
+    }
+}
+
+fn attributes(values: &[KeyValue]) -> Value {
+    let mut map = Map::new();
+    for kv in values {
+        map.insert(kv.key.to_string(), Value::String(kv.value.to_string()));
+    }
+    Value::Object(map)
+}
+
+fn span_json(span: &SpanData) -> Value {
+    let micros = |t: std::time::SystemTime| {
+        t.duration_since(UNIX_EPOCH).map(|d| d.as_micros() as u64).unwrap_or(0)
+    };
+    let status = match &span.status {
+        Status::Unset => Value::Null,
+        Status::Ok => json!("ok"),
+        Status::Error { description } => json!({ "error": description }),
+    };
+    json!({
+        "trace_id": span.span_context.trace_id().to_string(),
+        "span_id": span.span_context.span_id().to_string(),
+        "parent_span_id": span.parent_span_id.to_string(),
+        "name": span.name,
+        "kind": format!("{:?}", span.span_kind),
+        "start_us": micros(span.start_time),
+        "duration_us": micros(span.end_time).saturating_sub(micros(span.start_time)),
+        "attributes": attributes(&span.attributes),
+        "events": span.events.iter().map(|event| json!({
+            "name": event.name,
+            "attributes": attributes(&event.attributes),
+        })).collect::<Vec<_>>(),
+        "status": status,
+    })
+}
+
+impl SpanExporter for FileSpanExporter {
+    async fn export(&self, batch: Vec<SpanData>) -> OTelSdkResult {
+        let mut lines = String::new();
+        for span in &batch {
+            lines.push_str(&span_json(span).to_string());
+            lines.push('\n');
+        }
+        let mut file = self
+            .file
+            .lock()
+            .map_err(|_| OTelSdkError::InternalFailure("trace file lock poisoned".to_string()))?;
+        file.write_all(lines.as_bytes())
+            .and_then(|_| file.flush())
+            .map_err(|e| OTelSdkError::InternalFailure(e.to_string()))
+    }
+}
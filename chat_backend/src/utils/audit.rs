@@ -0,0 +1,142 @@
+use actix_web::{HttpMessage, HttpRequest};
+use sea_orm::{ActiveModelTrait, ConnectionTrait, DatabaseConnection, DbErr, Set, TransactionTrait};
+use serde_json::{json, Map, Value};
+use crate::entities::{audit_events, users};
+use crate::middleware::request_id::RequestId;
+
+/// Who performed an audited action and where the request came from.
+pub struct AuditContext {
+    pub actor_id: Option<i32>,
+    pub ip_address: Option<String>,
+    pub request_id: Option<String>,
+}
+
+impl AuditContext {
+    pub fn new(req: &HttpRequest, actor_id: Option<i32>) -> Self {
+        Self {
+            actor_id,
+            ip_address: req.connection_info().realip_remote_addr().map(str::to_string),
+            request_id: req.extensions().get::<RequestId>().map(|id| id.0.clone()),
+        }
+    }
+
+    /// Actions run by an operator through `blockchat-admin`, which have no account or
+    /// request behind them.
+    pub fn operator() -> Self {
+        Self {
+            actor_id: None,
+            ip_address: None,
+            request_id: None,
+        }
+    }
+}
+
+/// One row of the audit trail. Write it on the same transaction as the change it
+/// describes, so the two are committed together.
+pub struct AuditEvent {
+    pub action: &'static str,
+    pub target_type: &'static str,
+    pub target_id: Option<i32>,
+    pub changes: Option<Value>,
+}
+
+pub async fn record<C: ConnectionTrait>(
+    db: &C,
+    context: &AuditContext,
+    event: AuditEvent,
+) -> Result<audit_events::Model, DbErr> {
+    audit_events::ActiveModel {
+        actor_id: Set(context.actor_id),
+        action: Set(event.action.to_string()),
+        target_type: Set(event.target_type.to_string()),
+        target_id: Set(event.target_id),
+        changes: Set(event.changes),
+        ip_address: Set(context.ip_address.clone()),
+        request_id: Set(context.request_id.clone()),
+        ..Default::default()
+    }
+    .insert(db)
+    .await
+}
+
+/// The `{ field: { before, after } }` entries for every top-level field that differs.
+/// `Value::Null` on either side stands for a row that did not exist.
+pub fn diff(before: &Value, after: &Value) -> Option<Value> {
+    let empty = Map::new();
+    let before = before.as_object().unwrap_or(&empty);
+    let after = after.as_object().unwrap_or(&empty);
+
+    let mut changes = Map::new();
+    for key in before.keys().chain(after.keys().filter(|k| !before.contains_key(*k))) {
+        let old = before.get(key).unwrap_or(&Value::Null);
+        let new = after.get(key).unwrap_or(&Value::Null);
+        if old != new {
+            changes.insert(key.clone(), json!({ "before": old, "after": new }));
+        }
+    }
+    (!changes.is_empty()).then_some(Value::Object(changes))
+}
+
+/// The audited view of a user: credentials and avatar bytes never enter the log.
+pub fn user_fields(user: &users::Model) -> Value {
+    json!({
+        "first_name": user.first_name,
+        "last_name": user.last_name,
+        "username": user.username,
+        "email": user.email,
+        "role_id": user.role_id,
+        "status": user.status,
+        "status_reason": user.status_reason,
+        "status_until": user.status_until.map(|dt| dt.to_rfc3339()),
+        "previous_status": user.previous_status,
+        "has_avatar": user.avatar.is_some(),
+        "deleted_at": user.deleted_at.map(|dt| dt.to_rfc3339()),
+    })
+}
+
+
+/// Saves changes to a user together with an audit event holding the field diff.
+pub async fn update_user(
+    db: &DatabaseConnection,
+    context: &AuditContext,
+    action: &'static str,
+    before: &users::Model,
+    changes: users::ActiveModel,
+) -> Result<users::Model, DbErr> {
+    let txn = db.begin().await?;
+    let after = changes.update(&txn).await?;
+    record(&txn, context, AuditEvent {
+        action,
+        target_type: "user",
+        target_id: Some(after.id),
+        changes: diff(&user_fields(before), &user_fields(&after)),
+    })
+    .await?;
+    txn.commit().await?;
+    Ok(after)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diff_lists_only_changed_fields() {
+        let before = json!({ "username": "alice", "role_id": 3 });
+        let after = json!({ "username": "alice", "role_id": 2 });
+        assert_eq!(
+            diff(&before, &after),
+            Some(json!({ "role_id": { "before": 3, "after": 2 } }))
+        );
+        assert_eq!(diff(&before, &before), None);
+    }
+
+    #[test]
+    fn diff_from_null_describes_a_new_row() {
+        let after = json!({ "username": "alice" });
+        assert_eq!(
+            diff(&Value::Null, &after),
+            Some(json!({ "username": { "before": null, "after": "alice" } }))
+        );
+    }
+}
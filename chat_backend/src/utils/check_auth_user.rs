@@ -1,17 +1,42 @@
-use actix_web::{http::header::HeaderMap, Error, FromRequest, HttpRequest};
-use futures::future::{ready, Ready};
+use actix_web::http::header::{HeaderMap, WWW_AUTHENTICATE};
+use actix_web::{error::InternalError, web, Error, FromRequest, HttpMessage, HttpRequest, HttpResponse};
+use futures::future::LocalBoxFuture;
 use jsonwebtoken::{decode, DecodingKey, Validation};
+use sea_orm::{ColumnTrait, DatabaseConnection, DbErr, QueryFilter};
+use crate::entities::{prelude::Users, users};
 use crate::models::token_model::Claims;
+use crate::utils::moderation::active_restriction;
+use crate::utils::sessions::touch_session;
+use crate::utils::soft_delete::SoftDelete;
 
 pub struct AuthenticatedUser(pub Claims);
 
+/// `WWW-Authenticate` value of a 401 whose token expired or was revoked (RFC 6750).
+/// Clients sign in again on it; other 401s, such as a role the route does not allow, leave
+/// the header out.
+pub const INVALID_TOKEN: &str = "Bearer error=\"invalid_token\"";
+
+fn invalid_token(message: &'static str) -> Error {
+    let response = HttpResponse::Unauthorized()
+        .insert_header((WWW_AUTHENTICATE, INVALID_TOKEN))
+        .body(message);
+    InternalError::from_response(message, response).into()
+}
+
+/// Id of the account behind a request, stored in the request extensions once it is authenticated.
+#[derive(Clone, Copy)]
+pub struct AuthenticatedUserId(pub i32);
+
 impl AuthenticatedUser {
     /// Extracts the token claims from the request headers.
     pub fn from_headers(req: &HttpRequest) -> Result<Self, Error> {
         Self::from_headers_ref(req.headers())
     }
-    
+
     /// Extracts the token claims from a header reference.
+    ///
+    /// This only checks the token itself; use [`AuthenticatedUser::authenticate`] where
+    /// the session behind it must still be live.
     pub fn from_headers_ref(headers: &HeaderMap) -> Result<Self, Error> {
         if let Some(auth_header) = headers.get("Authorization") {
             if let Ok(auth_str) = auth_header.to_str() {
@@ -21,21 +46,70 @@ impl AuthenticatedUser {
                         .map_err(|_| actix_web::error::ErrorUnauthorized("JWT secret missing"))?;
                     let decoding_key = DecodingKey::from_secret(secret.as_bytes());
                     let token_data = decode::<Claims>(token, &decoding_key, &Validation::default())
-                        .map_err(|_| actix_web::error::ErrorUnauthorized("Invalid token"))?;
+                        .map_err(|_| invalid_token("Invalid token"))?;
                     return Ok(AuthenticatedUser(token_data.claims));
                 }
             }
         }
         Err(actix_web::error::ErrorUnauthorized("Unauthorized"))
     }
+
+    /// Extracts the token claims and checks that its session has not been revoked and
+    /// that the account is not suspended or banned.
+    #[tracing::instrument(skip_all, fields(user = tracing::field::Empty))]
+    pub async fn authenticate(req: &HttpRequest) -> Result<Self, Error> {
+        let db = req
+            .app_data::<web::Data<DatabaseConnection>>()
+            .ok_or_else(|| actix_web::error::ErrorInternalServerError("Database unavailable"))?;
+        let (auth_user, user) = Self::verify(req.headers(), db.get_ref()).await?;
+        tracing::Span::current().record("user", user.id);
+        req.extensions_mut().insert(AuthenticatedUserId(user.id));
+        Ok(auth_user)
+    }
+
+    /// The checks of [`AuthenticatedUser::authenticate`] for credentials that did not come
+    /// with an HTTP request, such as the `connection_init` of a GraphQL WebSocket.
+    /// Returns the account along with the claims.
+    pub async fn verify(headers: &HeaderMap, db: &DatabaseConnection) -> Result<(Self, users::Model), Error> {
+        let auth_user = Self::from_headers_ref(headers)?;
+        let user = Self::check_session(db, &auth_user.0.jti).await?;
+        Ok((auth_user, user))
+    }
+
+    /// The account behind the session `jti`, as long as the session is live and the
+    /// account is not suspended or banned. Long-lived connections call this again to
+    /// notice a revoked session or a new restriction.
+    pub async fn check_session(db: &DatabaseConnection, jti: &str) -> Result<users::Model, Error> {
+        match touch_session(db, jti).await {
+            Ok(Some(user)) => match active_restriction(&user) {
+                None => Ok(user),
+                Some(restriction) => Err(actix_web::error::ErrorForbidden(restriction.message())),
+            },
+            Ok(None) => Err(invalid_token("Session expired or revoked")),
+            Err(err) => {
+                log::error!("Failed to check session: {:?}", err);
+                Err(actix_web::error::ErrorInternalServerError("Failed to check session"))
+            }
+        }
+    }
+
+    /// Looks up the id of the account the token was issued to.
+    pub async fn user_id(&self, db: &DatabaseConnection) -> Result<Option<i32>, DbErr> {
+        Ok(Users::find_live()
+            .filter(users::Column::Username.eq(&self.0.sub))
+            .one(db)
+            .await?
+            .map(|user| user.id))
+    }
 }
 
 
 impl FromRequest for AuthenticatedUser {
     type Error = Error;
-    type Future = Ready<Result<Self, Self::Error>>;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
 
     fn from_request(req: &HttpRequest, _payload: &mut actix_web::dev::Payload) -> Self::Future {
-        ready(AuthenticatedUser::from_headers(req))
+        let req = req.clone();
+        Box::pin(async move { AuthenticatedUser::authenticate(&req).await })
     }
-}
\ No newline at end of file
+}
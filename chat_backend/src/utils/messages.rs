@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use chrono::Utc;
+use sea_orm::sea_query::{Alias, Asterisk, Expr, Func, Order, Query, WindowStatement};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait, Iterable, PaginatorTrait,
+    QueryFilter, Set,
+};
+use tokio::sync::broadcast;
+use crate::entities::prelude::{ChatParticipants, Chats, Messages};
+use crate::entities::{chat_participants, messages};
+use crate::utils::soft_delete::SoftDelete;
+
+/// Roles that may use the chat APIs, GraphQL and gRPC, as on the HTTP routes; accounts
+/// awaiting verification may not.
+pub const CHAT_API_ROLES: &[&str] = &["admin", "user"];
+
+/// Longest message that can be posted, in characters.
+pub const MAX_MESSAGE_LENGTH: usize = 4000;
+
+/// Most messages one read of a chat's history returns.
+pub const MAX_RECENT_MESSAGES: u64 = 100;
+
+/// Fans newly posted messages out to the live subscriptions of the GraphQL and gRPC APIs.
+///
+/// A subscriber that falls more than the channel's capacity behind skips the messages it
+/// missed rather than holding up the others.
+#[derive(Clone)]
+pub struct MessageHub(broadcast::Sender<messages::Model>);
+
+impl MessageHub {
+    pub fn new() -> Self {
+        Self(broadcast::channel(256).0)
+    }
+
+    pub fn publish(&self, message: messages::Model) {
+        // Nobody listening is not an error.
+        self.0.send(message).ok();
+    }
+
+    /// Number of open subscriptions.
+    pub fn subscribers(&self) -> usize {
+        self.0.receiver_count()
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<messages::Model> {
+        self.0.subscribe()
+    }
+
+    /// Subscribes, then runs `check`. Subscribing first means nothing posted while the
+    /// check runs is missed; the subscription is dropped again if the check fails.
+    pub async fn subscribe_checked<T, E>(
+        &self,
+        check: impl Future<Output = Result<T, E>>,
+    ) -> Result<(broadcast::Receiver<messages::Model>, T), E> {
+        let receiver = self.subscribe();
+        let checked = check.await?;
+        Ok((receiver, checked))
+    }
+}
+
+impl Default for MessageHub {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug)]
+pub enum PostMessageError {
+    Empty,
+    TooLong,
+    NotParticipant,
+    Database(DbErr),
+}
+
+impl fmt::Display for PostMessageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PostMessageError::Empty => write!(f, "Message must not be empty"),
+            PostMessageError::TooLong => write!(f, "Message must be at most {} characters", MAX_MESSAGE_LENGTH),
+            PostMessageError::NotParticipant => write!(f, "You are not a participant of this chat"),
+            PostMessageError::Database(err) => write!(f, "Database error: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for PostMessageError {}
+
+impl From<DbErr> for PostMessageError {
+    fn from(err: DbErr) -> Self {
+        PostMessageError::Database(err)
+    }
+}
+
+pub async fn is_participant(db: &DatabaseConnection, chat_id: i32, user_id: i32) -> Result<bool, DbErr> {
+    let count = ChatParticipants::find()
+        .filter(chat_participants::Column::ChatId.eq(chat_id))
+        .filter(chat_participants::Column::UserId.eq(user_id))
+        .count(db)
+        .await?;
+    Ok(count > 0)
+}
+
+/// Admins may read every chat; everyone else only the chats they take part in. Each API
+/// looks membership up its own way, and `is_member` is only awaited for non-admins.
+pub async fn can_read_chat<E>(is_admin: bool, is_member: impl Future<Output = Result<bool, E>>) -> Result<bool, E> {
+    if is_admin {
+        return Ok(true);
+    }
+    is_member.await
+}
+
+/// Posts `content` to a live chat that `user_id` takes part in and publishes it on `hub`.
+pub async fn post_message(
+    db: &DatabaseConnection,
+    hub: &MessageHub,
+    user_id: i32,
+    chat_id: i32,
+    content: &str,
+) -> Result<messages::Model, PostMessageError> {
+    let content = content.trim();
+    if content.is_empty() {
+        return Err(PostMessageError::Empty);
+    }
+    if content.chars().count() > MAX_MESSAGE_LENGTH {
+        return Err(PostMessageError::TooLong);
+    }
+    if Chats::find_live_by_id(chat_id).one(db).await?.is_none() || !is_participant(db, chat_id, user_id).await? {
+        return Err(PostMessageError::NotParticipant);
+    }
+
+    let message = messages::ActiveModel {
+        user_id: Set(Some(user_id)),
+        chat_id: Set(chat_id),
+        content: Set(content.to_string()),
+        timestamp: Set(Some(Utc::now().into())),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?;
+
+    hub.publish(message.clone());
+    Ok(message)
+}
+
+/// The latest `count` live messages of each chat, oldest first, in one statement: a
+/// `ROW_NUMBER()` window numbers each chat's messages from the newest and the outer
+/// query keeps the first `count`. Every chat in `chat_ids` has an entry.
+pub async fn recent_messages(
+    db: &DatabaseConnection,
+    chat_ids: &[i32],
+    count: u64,
+) -> Result<HashMap<i32, Vec<messages::Model>>, DbErr> {
+    let numbered = Query::select()
+        .columns(messages::Column::iter())
+        .expr_window_as(
+            Func::cust(Alias::new("ROW_NUMBER")),
+            WindowStatement::partition_by(messages::Column::ChatId)
+                .order_by(messages::Column::Id, Order::Desc)
+                .to_owned(),
+            Alias::new("rn"),
+        )
+        .from(Messages)
+        .and_where(messages::Column::ChatId.is_in(chat_ids.iter().copied()))
+        .and_where(messages::Column::DeletedAt.is_null())
+        .to_owned();
+    let latest = Query::select()
+        .column(Asterisk)
+        .from_subquery(numbered, Alias::new("numbered"))
+        .and_where(Expr::col(Alias::new("rn")).lte(count))
+        .to_owned();
+
+    let rows = Messages::find()
+        .from_raw_sql(db.get_database_backend().build(&latest))
+        .all(db)
+        .await?;
+    let mut chats: HashMap<i32, Vec<messages::Model>> = chat_ids.iter().map(|chat_id| (*chat_id, Vec::new())).collect();
+    for message in rows {
+        chats.entry(message.chat_id).or_default().push(message);
+    }
+    for messages in chats.values_mut() {
+        messages.sort_by_key(|message| message.id);
+    }
+    Ok(chats)
+}
@@ -1 +1,8 @@
-pub mod check_auth_user;
\ No newline at end of file
+pub mod audit;
+pub mod check_auth_user;
+pub mod messages;
+pub mod moderation;
+pub mod password;
+pub mod sessions;
+pub mod soft_delete;
+pub mod webauthn;
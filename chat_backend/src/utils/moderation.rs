@@ -0,0 +1,82 @@
+use chrono::{DateTime, Utc};
+use sea_orm::Set;
+use crate::entities::sea_orm_active_enums::AccountStatus;
+use crate::entities::users;
+
+/// A suspension or ban that is currently in force.
+pub struct Restriction {
+    pub status: AccountStatus,
+    pub reason: Option<String>,
+    pub until: Option<DateTime<Utc>>,
+}
+
+impl Restriction {
+    pub fn message(&self) -> String {
+        let state = match self.status {
+            AccountStatus::Banned => "banned",
+            _ => "suspended",
+        };
+        match self.until {
+            Some(until) => format!("Account {} until {}", state, until.to_rfc3339()),
+            None => format!("Account {}", state),
+        }
+    }
+}
+
+/// Returns the user's suspension or ban unless it has already run out. Expired ones are
+/// cleared by the `lift_expired_restrictions` job, but may not have been yet.
+pub fn active_restriction(user: &users::Model) -> Option<Restriction> {
+    if !is_restricted(user.status) {
+        return None;
+    }
+    let until = user.status_until.map(|dt| dt.with_timezone(&Utc));
+    if until.is_some_and(|until| until <= Utc::now()) {
+        return None;
+    }
+    Some(Restriction {
+        status: user.status,
+        reason: user.status_reason.clone(),
+        until,
+    })
+}
+
+/// The status that applies to `user` now: a restriction that has run out counts as
+/// lifted even before the job has cleared it.
+pub fn current_status(user: &users::Model) -> AccountStatus {
+    if is_restricted(user.status) && active_restriction(user).is_none() {
+        return user.previous_status.unwrap_or(AccountStatus::Active);
+    }
+    user.status
+}
+
+fn is_restricted(status: AccountStatus) -> bool {
+    matches!(status, AccountStatus::Suspended | AccountStatus::Banned)
+}
+
+/// Suspends or bans `user`. The status the account had before is kept for when the
+/// restriction is lifted; turning a suspension into a ban keeps the original one.
+pub fn restrict(
+    user: &users::Model,
+    user_model: &mut users::ActiveModel,
+    status: AccountStatus,
+    reason: &str,
+    set_by: Option<i32>,
+    until: Option<DateTime<Utc>>,
+) {
+    let previous = if is_restricted(user.status) { user.previous_status } else { Some(user.status) };
+    user_model.status = Set(status);
+    user_model.previous_status = Set(previous);
+    user_model.status_reason = Set(Some(reason.trim().to_string()));
+    user_model.status_set_by = Set(set_by);
+    user_model.status_until = Set(until.map(Into::into));
+}
+
+/// Lifts the suspension or ban of `user`, returning the account to the status it had
+/// before. Restrictions recorded without one predate it and lift to active.
+pub fn lift(user: &users::Model, user_model: &mut users::ActiveModel) {
+    user_model.status = Set(user.previous_status.unwrap_or(AccountStatus::Active));
+    user_model.previous_status = Set(None);
+    user_model.status_reason = Set(None);
+    user_model.status_set_by = Set(None);
+    user_model.status_until = Set(None);
+}
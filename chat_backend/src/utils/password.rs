@@ -0,0 +1,29 @@
+use std::time::Instant;
+use argon2::password_hash::rand_core::OsRng;
+use argon2::password_hash::{Error, SaltString};
+use argon2::{Argon2, PasswordHash, PasswordHasher, PasswordVerifier};
+use crate::metrics::PASSWORD_HASH_DURATION;
+
+/// Hashes a plaintext password with Argon2 and a fresh random salt.
+#[tracing::instrument(skip_all)]
+pub fn hash_password(password: &str) -> Result<String, Error> {
+    let _timer = PASSWORD_HASH_DURATION.with_label_values(&["hash"]).start_timer();
+    let salt = SaltString::generate(&mut OsRng);
+    Ok(Argon2::default()
+        .hash_password(password.as_bytes(), &salt)?
+        .to_string())
+}
+
+/// Checks a plaintext password against a stored Argon2 hash.
+/// A hash that cannot be parsed never matches.
+#[tracing::instrument(skip_all)]
+pub fn verify_password(password: &str, password_hash: &str) -> bool {
+    let start = Instant::now();
+    let matches = PasswordHash::new(password_hash)
+        .map(|hash| Argon2::default().verify_password(password.as_bytes(), &hash).is_ok())
+        .unwrap_or(false);
+    PASSWORD_HASH_DURATION
+        .with_label_values(&["verify"])
+        .observe(start.elapsed().as_secs_f64());
+    matches
+}
@@ -0,0 +1,61 @@
+use actix_web::http::header::USER_AGENT;
+use actix_web::HttpRequest;
+use chrono::{DateTime, Duration, Utc};
+use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, Set};
+use crate::entities::prelude::{UserSessions, Users};
+use crate::entities::{user_sessions, users};
+
+// `last_seen_at` is only rewritten once per interval to keep requests read-only.
+const LAST_SEEN_RESOLUTION_SECS: i64 = 60;
+
+/// Stores the session behind a freshly issued token, with the client's user agent and IP.
+pub async fn record_session(
+    db: &DatabaseConnection,
+    req: &HttpRequest,
+    user_id: i32,
+    jti: &str,
+    expires_at: DateTime<Utc>,
+) -> Result<user_sessions::Model, DbErr> {
+    let user_agent = req
+        .headers()
+        .get(USER_AGENT)
+        .and_then(|v| v.to_str().ok())
+        .map(|ua| ua.chars().take(512).collect::<String>());
+    let ip_address = req.connection_info().realip_remote_addr().map(str::to_string);
+
+    user_sessions::ActiveModel {
+        user_id: Set(user_id),
+        jti: Set(jti.to_string()),
+        user_agent: Set(user_agent),
+        ip_address: Set(ip_address),
+        expires_at: Set(expires_at.into()),
+        ..Default::default()
+    }
+    .insert(db)
+    .await
+}
+
+/// Looks up the owner of a live session, refreshing its `last_seen_at` on the way.
+/// Returns `None` once the session has expired or been revoked, or the account deleted.
+pub async fn touch_session(db: &DatabaseConnection, jti: &str) -> Result<Option<users::Model>, DbErr> {
+    let now = Utc::now();
+    let session = UserSessions::find()
+        .filter(user_sessions::Column::Jti.eq(jti))
+        .filter(user_sessions::Column::RevokedAt.is_null())
+        .filter(user_sessions::Column::ExpiresAt.gt(now))
+        .find_also_related(Users)
+        .one(db)
+        .await?;
+
+    match session {
+        Some((session, user)) => {
+            if now - session.last_seen_at.with_timezone(&Utc) >= Duration::seconds(LAST_SEEN_RESOLUTION_SECS) {
+                let mut session_model: user_sessions::ActiveModel = session.into();
+                session_model.last_seen_at = Set(now.into());
+                session_model.update(db).await?;
+            }
+            Ok(user.filter(|user| user.deleted_at.is_none()))
+        }
+        None => Ok(None),
+    }
+}
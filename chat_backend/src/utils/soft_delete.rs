@@ -0,0 +1,39 @@
+use sea_orm::{ColumnTrait, EntityTrait, PrimaryKeyTrait, QueryFilter, Select};
+use crate::entities::{chats, messages, users};
+
+/// Entities whose rows are flagged with `deleted_at` before the purge job removes them.
+///
+/// `find_live` and `find_live_by_id` are the default way to read these tables; plain
+/// `find` also returns rows that are waiting to be purged.
+pub trait SoftDelete: EntityTrait {
+    fn deleted_at() -> Self::Column;
+
+    fn find_live() -> Select<Self> {
+        Self::find().filter(Self::deleted_at().is_null())
+    }
+
+    fn find_live_by_id<T>(id: T) -> Select<Self>
+    where
+        T: Into<<Self::PrimaryKey as PrimaryKeyTrait>::ValueType>,
+    {
+        Self::find_by_id(id).filter(Self::deleted_at().is_null())
+    }
+}
+
+impl SoftDelete for users::Entity {
+    fn deleted_at() -> Self::Column {
+        users::Column::DeletedAt
+    }
+}
+
+impl SoftDelete for chats::Entity {
+    fn deleted_at() -> Self::Column {
+        chats::Column::DeletedAt
+    }
+}
+
+impl SoftDelete for messages::Entity {
+    fn deleted_at() -> Self::Column {
+        messages::Column::DeletedAt
+    }
+}
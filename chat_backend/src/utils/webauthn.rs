@@ -0,0 +1,456 @@
+//! Relying-party side of the WebAuthn registration and authentication ceremonies.
+//!
+//! Only what BlockChat asks browsers for is supported: ES256 (P-256) credentials,
+//! "none" attestation and required user verification.
+
+use std::fmt;
+use std::io::Cursor;
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use ciborium::value::Value;
+use p256::ecdsa::signature::Verifier;
+use p256::ecdsa::{Signature, VerifyingKey};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use crate::config::Config;
+
+pub const COSE_ALG_ES256: i64 = -7;
+
+const FLAG_USER_PRESENT: u8 = 0x01;
+const FLAG_USER_VERIFIED: u8 = 0x04;
+const FLAG_ATTESTED_CREDENTIAL: u8 = 0x40;
+
+#[derive(Debug, PartialEq)]
+pub struct WebauthnError(&'static str);
+
+impl fmt::Display for WebauthnError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+/// The relying party the ceremonies are bound to.
+pub struct RelyingParty {
+    pub id: String,
+    pub name: String,
+    pub origin: String,
+}
+
+impl RelyingParty {
+    /// Ceremonies are bound to `WEBAUTHN_RP_ID` and the frontend's origin.
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            id: config.webauthn_rp_id.clone(),
+            name: "BlockChat".to_string(),
+            origin: config.frontend_url.clone(),
+        }
+    }
+}
+
+/// A credential accepted by [`verify_registration`].
+pub struct RegisteredCredential {
+    pub credential_id: Vec<u8>,
+    /// SEC1 uncompressed P-256 point.
+    pub public_key: Vec<u8>,
+    pub sign_count: u32,
+}
+
+#[derive(Deserialize)]
+struct ClientData {
+    #[serde(rename = "type")]
+    ceremony: String,
+    challenge: String,
+    origin: String,
+}
+
+struct AuthenticatorData<'a> {
+    rp_id_hash: &'a [u8],
+    flags: u8,
+    sign_count: u32,
+    attested: &'a [u8],
+}
+
+pub fn new_challenge() -> Vec<u8> {
+    let mut challenge = vec![0u8; 32];
+    OsRng.fill_bytes(&mut challenge);
+    challenge
+}
+
+pub fn encode(bytes: &[u8]) -> String {
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+pub fn decode(value: &str) -> Result<Vec<u8>, WebauthnError> {
+    URL_SAFE_NO_PAD
+        .decode(value.trim_end_matches('='))
+        .map_err(|_| WebauthnError("Invalid base64url data"))
+}
+
+/// Checks a `navigator.credentials.create()` response and extracts the new credential.
+pub fn verify_registration(
+    rp: &RelyingParty,
+    expected_challenge: &[u8],
+    client_data_json: &[u8],
+    attestation_object: &[u8],
+) -> Result<RegisteredCredential, WebauthnError> {
+    verify_client_data(rp, "webauthn.create", expected_challenge, client_data_json)?;
+
+    let attestation: Value = ciborium::de::from_reader(attestation_object)
+        .map_err(|_| WebauthnError("Malformed attestation object"))?;
+    let fmt = map_get(&attestation, "fmt")
+        .and_then(Value::as_text)
+        .ok_or(WebauthnError("Missing attestation format"))?;
+    if fmt != "none" {
+        return Err(WebauthnError("Unsupported attestation format"));
+    }
+    let raw_auth_data = map_get(&attestation, "authData")
+        .and_then(Value::as_bytes)
+        .ok_or(WebauthnError("Missing authenticator data"))?;
+
+    let auth_data = parse_authenticator_data(raw_auth_data)?;
+    verify_authenticator_data(rp, &auth_data)?;
+    if auth_data.flags & FLAG_ATTESTED_CREDENTIAL == 0 {
+        return Err(WebauthnError("No attested credential data"));
+    }
+
+    // aaguid (16) | credential id length (2) | credential id | COSE public key
+    let attested = auth_data.attested;
+    if attested.len() < 18 {
+        return Err(WebauthnError("Truncated attested credential data"));
+    }
+    let id_len = u16::from_be_bytes([attested[16], attested[17]]) as usize;
+    let key_start = 18 + id_len;
+    if attested.len() < key_start {
+        return Err(WebauthnError("Truncated credential id"));
+    }
+    let credential_id = attested[18..key_start].to_vec();
+    let cose_key: Value = ciborium::de::from_reader(Cursor::new(&attested[key_start..]))
+        .map_err(|_| WebauthnError("Malformed credential public key"))?;
+
+    Ok(RegisteredCredential {
+        credential_id,
+        public_key: cose_key_to_sec1(&cose_key)?,
+        sign_count: auth_data.sign_count,
+    })
+}
+
+/// Checks a `navigator.credentials.get()` assertion against a stored credential and
+/// returns the authenticator's new signature counter.
+pub fn verify_authentication(
+    rp: &RelyingParty,
+    expected_challenge: &[u8],
+    public_key: &[u8],
+    stored_sign_count: u32,
+    client_data_json: &[u8],
+    authenticator_data: &[u8],
+    signature: &[u8],
+) -> Result<u32, WebauthnError> {
+    verify_client_data(rp, "webauthn.get", expected_challenge, client_data_json)?;
+
+    let auth_data = parse_authenticator_data(authenticator_data)?;
+    verify_authenticator_data(rp, &auth_data)?;
+
+    let key = VerifyingKey::from_sec1_bytes(public_key)
+        .map_err(|_| WebauthnError("Stored public key is invalid"))?;
+    let signature =
+        Signature::from_der(signature).map_err(|_| WebauthnError("Malformed signature"))?;
+    let mut signed = authenticator_data.to_vec();
+    signed.extend_from_slice(&Sha256::digest(client_data_json));
+    key.verify(&signed, &signature)
+        .map_err(|_| WebauthnError("Signature verification failed"))?;
+
+    // Authenticators that keep a counter must move it forward; a stale value means a cloned key.
+    // Those without one report 0 every time, and only the single-use challenge stops a replay.
+    if (auth_data.sign_count != 0 || stored_sign_count != 0)
+        && auth_data.sign_count <= stored_sign_count
+    {
+        return Err(WebauthnError("Signature counter did not increase"));
+    }
+
+    Ok(auth_data.sign_count)
+}
+
+fn verify_client_data(
+    rp: &RelyingParty,
+    ceremony: &str,
+    expected_challenge: &[u8],
+    client_data_json: &[u8],
+) -> Result<(), WebauthnError> {
+    let client_data: ClientData = serde_json::from_slice(client_data_json)
+        .map_err(|_| WebauthnError("Malformed client data"))?;
+    if client_data.ceremony != ceremony {
+        return Err(WebauthnError("Unexpected ceremony type"));
+    }
+    if decode(&client_data.challenge)? != expected_challenge {
+        return Err(WebauthnError("Challenge mismatch"));
+    }
+    if client_data.origin != rp.origin {
+        return Err(WebauthnError("Origin mismatch"));
+    }
+    Ok(())
+}
+
+fn parse_authenticator_data(data: &[u8]) -> Result<AuthenticatorData<'_>, WebauthnError> {
+    if data.len() < 37 {
+        return Err(WebauthnError("Truncated authenticator data"));
+    }
+    Ok(AuthenticatorData {
+        rp_id_hash: &data[..32],
+        flags: data[32],
+        sign_count: u32::from_be_bytes([data[33], data[34], data[35], data[36]]),
+        attested: &data[37..],
+    })
+}
+
+fn verify_authenticator_data(
+    rp: &RelyingParty,
+    auth_data: &AuthenticatorData<'_>,
+) -> Result<(), WebauthnError> {
+    if auth_data.rp_id_hash != &Sha256::digest(rp.id.as_bytes())[..] {
+        return Err(WebauthnError("Relying party id mismatch"));
+    }
+    if auth_data.flags & FLAG_USER_PRESENT == 0 {
+        return Err(WebauthnError("User was not present"));
+    }
+    if auth_data.flags & FLAG_USER_VERIFIED == 0 {
+        return Err(WebauthnError("User was not verified"));
+    }
+    Ok(())
+}
+
+fn cose_key_to_sec1(key: &Value) -> Result<Vec<u8>, WebauthnError> {
+    let int = |label: i64| {
+        cose_get(key, label)
+            .and_then(Value::as_integer)
+            .and_then(|value| i64::try_from(value).ok())
+    };
+    // kty EC2, alg ES256, crv P-256
+    if int(1) != Some(2) || int(3) != Some(COSE_ALG_ES256) || int(-1) != Some(1) {
+        return Err(WebauthnError("Unsupported credential algorithm"));
+    }
+    let x = cose_get(key, -2).and_then(Value::as_bytes);
+    let y = cose_get(key, -3).and_then(Value::as_bytes);
+    match (x, y) {
+        (Some(x), Some(y)) if x.len() == 32 && y.len() == 32 => {
+            let mut point = Vec::with_capacity(65);
+            point.push(0x04);
+            point.extend_from_slice(x);
+            point.extend_from_slice(y);
+            VerifyingKey::from_sec1_bytes(&point)
+                .map_err(|_| WebauthnError("Credential public key is not on the curve"))?;
+            Ok(point)
+        }
+        _ => Err(WebauthnError("Malformed credential public key")),
+    }
+}
+
+fn map_get<'a>(map: &'a Value, key: &str) -> Option<&'a Value> {
+    map.as_map()?
+        .iter()
+        .find(|(k, _)| k.as_text() == Some(key))
+        .map(|(_, v)| v)
+}
+
+fn cose_get(map: &Value, label: i64) -> Option<&Value> {
+    map.as_map()?
+        .iter()
+        .find(|(k, _)| k.as_integer().and_then(|k| i64::try_from(k).ok()) == Some(label))
+        .map(|(_, v)| v)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use p256::ecdsa::signature::Signer;
+    use p256::ecdsa::SigningKey;
+
+    /// A platform authenticator living entirely in memory.
+    struct SoftAuthenticator {
+        key: SigningKey,
+        credential_id: Vec<u8>,
+        sign_count: u32,
+    }
+
+    impl SoftAuthenticator {
+        fn new() -> Self {
+            Self {
+                key: SigningKey::random(&mut OsRng),
+                credential_id: new_challenge(),
+                sign_count: 0,
+            }
+        }
+
+        fn client_data(ceremony: &str, challenge: &[u8], origin: &str) -> Vec<u8> {
+            serde_json::to_vec(&serde_json::json!({
+                "type": ceremony,
+                "challenge": encode(challenge),
+                "origin": origin,
+                "crossOrigin": false,
+            }))
+            .unwrap()
+        }
+
+        fn authenticator_data(&self, rp_id: &str, attested: Option<Vec<u8>>) -> Vec<u8> {
+            let mut flags = FLAG_USER_PRESENT | FLAG_USER_VERIFIED;
+            if attested.is_some() {
+                flags |= FLAG_ATTESTED_CREDENTIAL;
+            }
+            let mut data = Sha256::digest(rp_id.as_bytes()).to_vec();
+            data.push(flags);
+            data.extend_from_slice(&self.sign_count.to_be_bytes());
+            data.extend(attested.unwrap_or_default());
+            data
+        }
+
+        fn cose_key(&self) -> Vec<u8> {
+            let point = self.key.verifying_key().to_encoded_point(false);
+            let key = Value::Map(vec![
+                (Value::from(1), Value::from(2)),
+                (Value::from(3), Value::from(COSE_ALG_ES256)),
+                (Value::from(-1), Value::from(1)),
+                (Value::from(-2), Value::Bytes(point.x().unwrap().to_vec())),
+                (Value::from(-3), Value::Bytes(point.y().unwrap().to_vec())),
+            ]);
+            let mut out = Vec::new();
+            ciborium::ser::into_writer(&key, &mut out).unwrap();
+            out
+        }
+
+        /// Returns `(clientDataJSON, attestationObject)`.
+        fn make_credential(&self, rp_id: &str, origin: &str, challenge: &[u8]) -> (Vec<u8>, Vec<u8>) {
+            let mut attested = vec![0u8; 16];
+            attested.extend_from_slice(&(self.credential_id.len() as u16).to_be_bytes());
+            attested.extend_from_slice(&self.credential_id);
+            attested.extend(self.cose_key());
+
+            let attestation = Value::Map(vec![
+                (Value::from("fmt"), Value::from("none")),
+                (Value::from("attStmt"), Value::Map(Vec::new())),
+                (
+                    Value::from("authData"),
+                    Value::Bytes(self.authenticator_data(rp_id, Some(attested))),
+                ),
+            ]);
+            let mut attestation_object = Vec::new();
+            ciborium::ser::into_writer(&attestation, &mut attestation_object).unwrap();
+
+            (
+                Self::client_data("webauthn.create", challenge, origin),
+                attestation_object,
+            )
+        }
+
+        /// Returns `(clientDataJSON, authenticatorData, signature)`.
+        fn get_assertion(&mut self, rp_id: &str, origin: &str, challenge: &[u8]) -> (Vec<u8>, Vec<u8>, Vec<u8>) {
+            self.sign_count += 1;
+            let client_data = Self::client_data("webauthn.get", challenge, origin);
+            let auth_data = self.authenticator_data(rp_id, None);
+            let mut signed = auth_data.clone();
+            signed.extend_from_slice(&Sha256::digest(&client_data));
+            let signature: Signature = self.key.sign(&signed);
+            (client_data, auth_data, signature.to_der().as_bytes().to_vec())
+        }
+    }
+
+    fn rp() -> RelyingParty {
+        RelyingParty {
+            id: "localhost".to_string(),
+            name: "BlockChat".to_string(),
+            origin: "http://localhost:3000".to_string(),
+        }
+    }
+
+    fn register(rp: &RelyingParty, authenticator: &SoftAuthenticator) -> RegisteredCredential {
+        let challenge = new_challenge();
+        let (client_data, attestation) = authenticator.make_credential(&rp.id, &rp.origin, &challenge);
+        verify_registration(rp, &challenge, &client_data, &attestation).unwrap()
+    }
+
+    #[test]
+    fn registration_then_authentication_succeeds() {
+        let rp = rp();
+        let mut authenticator = SoftAuthenticator::new();
+        let credential = register(&rp, &authenticator);
+        assert_eq!(credential.credential_id, authenticator.credential_id);
+        assert_eq!(credential.sign_count, 0);
+
+        let mut stored_count = credential.sign_count;
+        for _ in 0..2 {
+            let challenge = new_challenge();
+            let (client_data, auth_data, signature) =
+                authenticator.get_assertion(&rp.id, &rp.origin, &challenge);
+            stored_count = verify_authentication(
+                &rp,
+                &challenge,
+                &credential.public_key,
+                stored_count,
+                &client_data,
+                &auth_data,
+                &signature,
+            )
+            .unwrap();
+        }
+        assert_eq!(stored_count, 2);
+    }
+
+    #[test]
+    fn registration_rejects_wrong_challenge_and_origin() {
+        let rp = rp();
+        let authenticator = SoftAuthenticator::new();
+        let challenge = new_challenge();
+
+        let (client_data, attestation) =
+            authenticator.make_credential(&rp.id, &rp.origin, &new_challenge());
+        assert_eq!(
+            verify_registration(&rp, &challenge, &client_data, &attestation).err(),
+            Some(WebauthnError("Challenge mismatch"))
+        );
+
+        let (client_data, attestation) =
+            authenticator.make_credential(&rp.id, "https://evil.example", &challenge);
+        assert_eq!(
+            verify_registration(&rp, &challenge, &client_data, &attestation).err(),
+            Some(WebauthnError("Origin mismatch"))
+        );
+
+        let (client_data, attestation) =
+            authenticator.make_credential("evil.example", &rp.origin, &challenge);
+        assert_eq!(
+            verify_registration(&rp, &challenge, &client_data, &attestation).err(),
+            Some(WebauthnError("Relying party id mismatch"))
+        );
+    }
+
+    #[test]
+    fn authentication_rejects_foreign_key() {
+        let rp = rp();
+        let credential = register(&rp, &SoftAuthenticator::new());
+        let mut impostor = SoftAuthenticator::new();
+
+        let challenge = new_challenge();
+        let (client_data, auth_data, signature) =
+            impostor.get_assertion(&rp.id, &rp.origin, &challenge);
+        assert_eq!(
+            verify_authentication(&rp, &challenge, &credential.public_key, 0, &client_data, &auth_data, &signature)
+                .err(),
+            Some(WebauthnError("Signature verification failed"))
+        );
+    }
+
+    #[test]
+    fn authentication_rejects_replayed_counter() {
+        let rp = rp();
+        let mut authenticator = SoftAuthenticator::new();
+        let credential = register(&rp, &authenticator);
+
+        let challenge = new_challenge();
+        let (client_data, auth_data, signature) =
+            authenticator.get_assertion(&rp.id, &rp.origin, &challenge);
+        assert_eq!(
+            verify_authentication(&rp, &challenge, &credential.public_key, 5, &client_data, &auth_data, &signature)
+                .err(),
+            Some(WebauthnError("Signature counter did not increase"))
+        );
+    }
+}
@@ -0,0 +1,169 @@
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Output, Stdio};
+use chrono::{Duration, Utc};
+use sea_orm::sea_query::Expr;
+use sea_orm::{ColumnTrait, Database, DatabaseConnection, EntityTrait, QueryFilter};
+use chat_backend::entities::prelude::{AuditEvents, Users};
+use chat_backend::entities::sea_orm_active_enums::AccountStatus;
+use chat_backend::entities::{audit_events, users};
+use chat_backend::utils::password::verify_password;
+
+// A SQLite database file of its own, driven through the `blockchat-admin` binary.
+struct Cli {
+    dir: PathBuf,
+    database_url: String,
+}
+
+impl Cli {
+    fn new() -> Self {
+        let dir = std::env::temp_dir().join(format!("blockchat-cli-{}", uuid::Uuid::new_v4().simple()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let database_url = format!("sqlite://{}?mode=rwc", dir.join("blockchat.db").display());
+        Self { dir, database_url }
+    }
+
+    // Runs the tool from the scratch directory, so no `.env` is picked up, with `stdin`
+    // piped in and `env` on top of the database URL.
+    fn run_with(&self, args: &[&str], stdin: &str, env: &[(&str, &str)]) -> Output {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_blockchat-admin"))
+            .args(args)
+            .current_dir(&self.dir)
+            .env("DATABASE_URL", &self.database_url)
+            .env("RUST_LOG", "off")
+            .envs(env.iter().copied())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .expect("start blockchat-admin");
+        child.stdin.take().unwrap().write_all(stdin.as_bytes()).unwrap();
+        child.wait_with_output().unwrap()
+    }
+
+    fn run(&self, args: &[&str]) -> String {
+        self.succeeds(args, "")
+    }
+
+    fn succeeds(&self, args: &[&str], stdin: &str) -> String {
+        let output = self.run_with(args, stdin, &[]);
+        assert!(
+            output.status.success(),
+            "blockchat-admin {:?} failed: {}",
+            args,
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8(output.stdout).unwrap()
+    }
+
+    fn fails(&self, args: &[&str], env: &[(&str, &str)]) -> String {
+        let output = self.run_with(args, "", env);
+        assert!(!output.status.success(), "blockchat-admin {:?} succeeded", args);
+        String::from_utf8_lossy(&output.stderr).into_owned()
+    }
+
+    async fn db(&self) -> DatabaseConnection {
+        Database::connect(&self.database_url).await.unwrap()
+    }
+
+    async fn user(&self, username: &str) -> users::Model {
+        Users::find()
+            .filter(users::Column::Username.eq(username))
+            .one(&self.db().await)
+            .await
+            .unwrap()
+            .unwrap_or_else(|| panic!("no user {}", username))
+    }
+}
+
+impl Drop for Cli {
+    fn drop(&mut self) {
+        std::fs::remove_dir_all(&self.dir).ok();
+    }
+}
+
+fn seeded() -> Cli {
+    let cli = Cli::new();
+    cli.run(&["migrate"]);
+    cli.run(&["seed", "--profile", "e2e-test"]);
+    cli
+}
+
+#[tokio::test]
+async fn bootstraps_the_first_admin() {
+    let cli = Cli::new();
+    cli.run(&["migrate"]);
+    cli.run(&["migrate", "--check"]);
+    // Roles come from the seed, and admins cannot be created before it.
+    let args = ["create-admin", "--username", "root", "--first-name", "Root", "--last-name", "Admin"];
+    assert!(cli.fails(&args, &[]).contains("No role named \"admin\""));
+    cli.run(&["seed"]);
+
+    let stdout = cli.succeeds(&[&args[..], &["--email", " Root@Example.TEST "]].concat(), "first-password\n");
+    assert!(stdout.starts_with("Created admin root"), "{}", stdout);
+    let admin = cli.user("root").await;
+    assert!(verify_password("first-password", &admin.password));
+    assert_eq!(admin.email.as_deref(), Some("root@example.test"));
+    assert!(admin.email_verified_at.is_some());
+    assert_eq!(admin.status, AccountStatus::Active);
+    let listed = cli.run(&["list-users", "--role", "admin"]);
+    assert!(listed.contains("root") && listed.ends_with("1 user(s)\n"), "{}", listed);
+
+    assert!(cli.fails(&args, &[]).contains("already taken"));
+    let audited = AuditEvents::find()
+        .filter(audit_events::Column::Action.eq("user.create"))
+        .filter(audit_events::Column::TargetId.eq(admin.id))
+        .one(&cli.db().await)
+        .await
+        .unwrap();
+    assert!(audited.is_some_and(|event| event.actor_id.is_none()));
+}
+
+#[tokio::test]
+async fn manages_existing_accounts() {
+    let cli = seeded();
+
+    cli.succeeds(&["reset-password", "e2e-user"], "second-password\n");
+    assert!(verify_password("second-password", &cli.user("e2e-user").await.password));
+
+    cli.run(&["set-role", "e2e-user", "admin"]);
+    let admin_role = cli.user("e2e-admin").await.role_id;
+    assert_eq!(cli.user("e2e-user").await.role_id, admin_role);
+    assert!(cli.fails(&["set-role", "e2e-user", "owner"], &[]).contains("No role named"));
+
+    cli.run(&["ban", "e2e-user", "--reason", "Spam"]);
+    let banned = cli.user("e2e-user").await;
+    assert_eq!(banned.status, AccountStatus::Banned);
+    assert_eq!(banned.status_reason.as_deref(), Some("Spam"));
+    assert!(cli.fails(&["ban", "e2e-user", "--reason", " "], &[]).contains("reason"));
+    cli.run(&["unban", "e2e-user"]);
+    assert_eq!(cli.user("e2e-user").await.status, AccountStatus::Active);
+
+    assert!(cli.fails(&["unban", "nobody"], &[]).contains("No user named"));
+}
+
+#[tokio::test]
+async fn verify_integrity_reads_the_grace_period_from_the_environment() {
+    let cli = seeded();
+    assert!(cli.run(&["verify-integrity"]).contains("All integrity checks passed"));
+
+    Users::update_many()
+        .col_expr(users::Column::DeletedAt, Expr::value(Utc::now() - Duration::days(10)))
+        .filter(users::Column::Username.eq("e2e-banned"))
+        .exec(&cli.db().await)
+        .await
+        .unwrap();
+    // Ten days is inside the default grace period of 30 days, but not one of 5.
+    cli.run(&["verify-integrity"]);
+    let stderr = cli.fails(&["verify-integrity"], &[("PURGE_GRACE_DAYS", "5")]);
+    assert!(stderr.contains("1 integrity check(s) failed"), "{}", stderr);
+}
+
+#[tokio::test]
+async fn rejects_invalid_configuration() {
+    let cli = Cli::new();
+    let stderr = cli.fails(&["migrate"], &[("REGISTRATION_MODE", "everyone")]);
+    assert!(stderr.contains("everyone"), "{}", stderr);
+    let stderr = cli.fails(&["migrate"], &[("DATABASE_URL", "")]);
+    assert!(!stderr.is_empty());
+}
@@ -0,0 +1,101 @@
+mod support;
+
+use actix_web::http::StatusCode;
+use actix_web::test::TestRequest;
+use serde_json::{json, Value};
+use chat_backend::middleware::request_id::REQUEST_ID_HEADER;
+use support::TestApp;
+
+async fn events(app: &TestApp, token: &str, query: &str) -> Value {
+    let (status, body) = app
+        .call_as(token, TestRequest::get().uri(&format!("/api/v1/audit-events?{}", query)))
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    body
+}
+
+#[actix_web::test]
+async fn privileged_actions_are_recorded_with_their_request_id() {
+    let app = TestApp::spawn().await;
+    let admin = app.token_for_role("admin").await;
+    let admin_id = app.user_id("e2e-admin").await;
+    let target = app.user_id("e2e-user").await;
+
+    let (status, headers, body) = app
+        .call_with_headers(
+            TestRequest::post()
+                .uri(&format!("/api/v1/users/{}/suspend", target))
+                .insert_header(("Authorization", format!("Bearer {}", admin)))
+                .insert_header((REQUEST_ID_HEADER, "audit-test-request"))
+                .set_json(json!({ "reason": "Spam" })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(headers.get(REQUEST_ID_HEADER).unwrap(), "audit-test-request");
+
+    let body = events(&app, &admin, "request_id=audit-test-request").await;
+    assert_eq!(body["total"], 1, "{}", body);
+    let event = &body["events"][0];
+    assert_eq!(event["action"], "user.suspend");
+    assert_eq!(event["actor_id"], admin_id);
+    assert_eq!(event["target_type"], "user");
+    assert_eq!(event["target_id"], target);
+    assert_eq!(event["changes"]["status"], json!({ "before": "active", "after": "suspended" }));
+    assert_eq!(event["changes"]["status_reason"], json!({ "before": null, "after": "Spam" }));
+
+    app.teardown().await;
+}
+
+#[actix_web::test]
+async fn events_are_filtered_and_paginated() {
+    let app = TestApp::spawn().await;
+    let admin = app.token_for_role("admin").await;
+    let target = app.user_id("e2e-user").await;
+    for action in ["suspend", "reinstate", "ban"] {
+        let (status, body) = app
+            .call_as(
+                &admin,
+                TestRequest::post()
+                    .uri(&format!("/api/v1/users/{}/{}", target, action))
+                    .set_json(json!({ "reason": "Testing" })),
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK, "{}", body);
+    }
+
+    // A trailing dot matches every action in that namespace; newest first.
+    let body = events(&app, &admin, &format!("action=user.&target_id={}&per_page=2", target)).await;
+    assert_eq!((body["total"].as_u64(), body["total_pages"].as_u64()), (Some(3), Some(2)));
+    let actions: Vec<&str> = body["events"].as_array().unwrap().iter().map(|e| e["action"].as_str().unwrap()).collect();
+    assert_eq!(actions, ["user.ban", "user.reinstate"]);
+    let body = events(&app, &admin, &format!("action=user.&target_id={}&per_page=2&page=2", target)).await;
+    assert_eq!(body["events"][0]["action"], "user.suspend");
+
+    let body = events(&app, &admin, "action=user.ban").await;
+    assert_eq!(body["total"], 1);
+    let body = events(&app, &admin, "action=user.").await;
+    assert_eq!(body["total"], 3);
+
+    app.teardown().await;
+}
+
+#[actix_web::test]
+async fn only_admins_read_the_audit_log() {
+    let app = TestApp::spawn().await;
+    let user = app.token_for_role("user").await;
+
+    // Not a token problem, so nothing tells the client to sign in again.
+    let (status, headers, _) = app
+        .call_with_headers(
+            TestRequest::get()
+                .uri("/api/v1/audit-events")
+                .insert_header(("Authorization", format!("Bearer {}", user))),
+        )
+        .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert!(headers.get("WWW-Authenticate").is_none());
+    let (status, _) = app.call(TestRequest::get().uri("/api/v1/audit-events")).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    app.teardown().await;
+}
@@ -0,0 +1,101 @@
+mod support;
+
+use actix_web::http::StatusCode;
+use actix_web::test::TestRequest;
+use sea_orm::sea_query::Expr;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+use serde_json::json;
+use chat_backend::entities::prelude::{Messages, Users};
+use chat_backend::entities::{messages, users};
+use chat_backend::handlers::erasure_handler::TOMBSTONE_AUTHOR;
+use support::{TestApp, PASSWORD};
+
+async fn erase_as(app: &TestApp, token: &str, user_id: i32) -> StatusCode {
+    let (status, _) = app
+        .call_as(token, TestRequest::post().uri(&format!("/api/v1/users/{}/erase", user_id)))
+        .await;
+    status
+}
+
+#[actix_web::test]
+async fn admins_erase_users_and_keep_their_messages_anonymous() {
+    let app = TestApp::spawn().await;
+    let admin = app.token_for_role("admin").await;
+    let target = app.user_id("e2e-user").await;
+
+    assert_eq!(erase_as(&app, &admin, target).await, StatusCode::OK);
+
+    let user = Users::find_by_id(target).one(&app.db).await.unwrap().unwrap();
+    assert_eq!(user.username, format!("deleted-user-{}", target));
+    assert_eq!(user.email, None);
+    assert!(user.deleted_at.is_some());
+    let message = Messages::find()
+        .filter(messages::Column::Content.eq("First e2e message"))
+        .one(&app.db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(message.user_id, None);
+    assert_eq!(message.author_name.as_deref(), Some(TOMBSTONE_AUTHOR));
+
+    let (status, body) = app
+        .call_as(&admin, TestRequest::get().uri("/api/v1/audit-events?action=user.erase"))
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["events"][0]["target_id"], target);
+
+    app.teardown().await;
+}
+
+#[actix_web::test]
+async fn admins_cannot_erase_themselves_or_other_admins() {
+    let app = TestApp::spawn().await;
+    let admin = app.token_for_role("admin").await;
+    let own_id = app.user_id("e2e-admin").await;
+    let other_admin = app.user_id("e2e-banned").await;
+    Users::update_many()
+        .col_expr(users::Column::RoleId, Expr::value(2))
+        .filter(users::Column::Id.eq(other_admin))
+        .exec(&app.db)
+        .await
+        .unwrap();
+
+    assert_eq!(erase_as(&app, &admin, own_id).await, StatusCode::BAD_REQUEST);
+    assert_eq!(erase_as(&app, &admin, other_admin).await, StatusCode::FORBIDDEN);
+    for id in [own_id, other_admin] {
+        let user = Users::find_by_id(id).one(&app.db).await.unwrap().unwrap();
+        assert!(user.deleted_at.is_none());
+    }
+
+    let user = app.token_for_role("user").await;
+    assert_eq!(erase_as(&app, &user, other_admin).await, StatusCode::UNAUTHORIZED);
+
+    app.teardown().await;
+}
+
+#[actix_web::test]
+async fn users_erase_their_own_account_with_their_password() {
+    let app = TestApp::spawn().await;
+    let token = app.token_for_role("user").await;
+    let erase = |password: &str| {
+        TestRequest::post()
+            .uri("/api/v1/users/erase")
+            .set_json(json!({ "password": password }))
+    };
+
+    let (status, _) = app.call_as(&token, erase("wrong-password")).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, body) = app.call_as(&token, erase(PASSWORD)).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+
+    let (status, _) = app
+        .call(
+            TestRequest::post()
+                .uri("/api/v1/users/login")
+                .set_json(json!({ "username": "e2e-user", "password": PASSWORD })),
+        )
+        .await;
+    assert_ne!(status, StatusCode::OK);
+
+    app.teardown().await;
+}
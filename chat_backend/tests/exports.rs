@@ -0,0 +1,142 @@
+mod support;
+
+use std::io::{Cursor, Read};
+use std::path::Path;
+use std::time::Duration;
+use actix_web::http::StatusCode;
+use actix_web::test::TestRequest;
+use sea_orm::sea_query::Expr;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+use serde_json::Value;
+use chat_backend::entities::prelude::{Chats, DataExports};
+use chat_backend::entities::{chats, data_exports};
+use support::TestApp;
+
+const PNG: &[u8] = &[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, 1, 2, 3];
+
+// Requests an export as `token` and waits for the background job to finish it.
+async fn ready_export(app: &TestApp, token: &str) -> Value {
+    let (status, body) = app.call_as(token, TestRequest::post().uri("/api/v1/users/exports")).await;
+    assert_eq!(status, StatusCode::ACCEPTED, "{}", body);
+    let uri = format!("/api/v1/users/exports/{}", body["id"]);
+    for _ in 0..100 {
+        let (status, body) = app.call_as(token, TestRequest::get().uri(&uri)).await;
+        assert_eq!(status, StatusCode::OK, "{}", body);
+        if body["status"] != "pending" {
+            assert_eq!(body["status"], "ready", "{}", body);
+            return body;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    panic!("export {} never finished", uri);
+}
+
+// The path and query of a download link, without the public URL in front.
+fn download_uri(export: &Value) -> String {
+    let url = export["download_url"].as_str().expect("download link of a ready export");
+    url[url.find("/api/").unwrap()..].to_string()
+}
+
+async fn file_name_of(app: &TestApp, export: &Value) -> String {
+    DataExports::find_by_id(export["id"].as_i64().unwrap() as i32)
+        .one(&app.db)
+        .await
+        .unwrap()
+        .unwrap()
+        .file_name
+        .expect("archive of a ready export")
+}
+
+fn read_entry(archive: &[u8], name: &str) -> Vec<u8> {
+    let mut zip = zip::ZipArchive::new(Cursor::new(archive)).expect("a zip archive");
+    let mut entry = zip.by_name(name).unwrap_or_else(|_| panic!("no {} in the archive", name));
+    let mut data = Vec::new();
+    entry.read_to_end(&mut data).unwrap();
+    data
+}
+
+#[actix_web::test]
+async fn download_link_serves_the_archive_once() {
+    let app = TestApp::spawn().await;
+    let token = app.token_for_role("user").await;
+    let chat = Chats::find()
+        .filter(chats::Column::Name.eq("e2e-chat"))
+        .one(&app.db)
+        .await
+        .unwrap()
+        .unwrap();
+    Chats::update_many()
+        .col_expr(chats::Column::Image, Expr::value(PNG.to_vec()))
+        .filter(chats::Column::Id.eq(chat.id))
+        .exec(&app.db)
+        .await
+        .unwrap();
+
+    let export = ready_export(&app, &token).await;
+    // The link is mailed as well.
+    assert!(!app.mailed_token("e2e-user@example.test").is_empty());
+    let file_name = file_name_of(&app, &export).await;
+
+    let (status, headers, archive) = app.call_raw(TestRequest::get().uri(&download_uri(&export))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(headers.get("content-type").unwrap(), "application/zip");
+    let chats: Value = serde_json::from_slice(&read_entry(&archive, "chats.json")).unwrap();
+    let image_path = format!("media/chats/{}.png", chat.id);
+    assert_eq!(chats[0]["image"], image_path.as_str());
+    assert_eq!(read_entry(&archive, &image_path), PNG);
+    assert!(!Path::new(&app.config().export_dir).join(&file_name).exists());
+
+    let (status, _) = app.call(TestRequest::get().uri(&download_uri(&export))).await;
+    assert_eq!(status, StatusCode::GONE);
+    let (status, body) = app
+        .call_as(&token, TestRequest::get().uri(&format!("/api/v1/users/exports/{}", export["id"])))
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["status"], "downloaded");
+    assert!(body["download_url"].is_null());
+
+    app.teardown().await;
+}
+
+#[actix_web::test]
+async fn unreadable_archive_does_not_use_up_the_link() {
+    let app = TestApp::spawn().await;
+    let token = app.token_for_role("user").await;
+    let export = ready_export(&app, &token).await;
+    let archive_path = Path::new(&app.config().export_dir).join(file_name_of(&app, &export).await);
+    let archive = std::fs::read(&archive_path).unwrap();
+
+    std::fs::remove_file(&archive_path).unwrap();
+    let (status, _) = app.call(TestRequest::get().uri(&download_uri(&export))).await;
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+
+    // Once the archive is back, the same link still works.
+    std::fs::write(&archive_path, &archive).unwrap();
+    let (status, _, body) = app.call_raw(TestRequest::get().uri(&download_uri(&export))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, archive);
+
+    app.teardown().await;
+}
+
+#[actix_web::test]
+async fn forged_or_expired_links_are_gone() {
+    let app = TestApp::spawn().await;
+    let token = app.token_for_role("user").await;
+    let export = ready_export(&app, &token).await;
+
+    let (status, _) = app
+        .call(TestRequest::get().uri("/api/v1/users/exports/download?token=not-a-token"))
+        .await;
+    assert_eq!(status, StatusCode::GONE);
+
+    DataExports::update_many()
+        .col_expr(data_exports::Column::ExpiresAt, Expr::value(chrono::Utc::now() - chrono::Duration::minutes(1)))
+        .exec(&app.db)
+        .await
+        .unwrap();
+    let (status, _) = app.call(TestRequest::get().uri(&download_uri(&export))).await;
+    assert_eq!(status, StatusCode::GONE);
+
+    app.teardown().await;
+}
@@ -0,0 +1,254 @@
+mod support;
+
+use actix_web::http::StatusCode;
+use actix_web::test::TestRequest;
+use async_graphql::Request;
+use futures::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::Message as WsMessage;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+use chat_backend::graphql::{self, Viewer};
+use chat_backend::utils::messages::MessageHub;
+use chat_backend::models::token_model::Claims;
+use support::TestApp;
+
+const GRAPHQL: &str = "/api/v1/graphql";
+
+type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+async fn next_json(socket: &mut Socket) -> Value {
+    match socket.next().await.expect("socket closed").unwrap() {
+        WsMessage::Text(text) => serde_json::from_str(&text).unwrap(),
+        other => panic!("expected a text frame, got {:?}", other),
+    }
+}
+
+// The close code the server ends the connection with.
+async fn close_code(socket: &mut Socket) -> u16 {
+    match socket.next().await.expect("socket closed").unwrap() {
+        WsMessage::Close(Some(frame)) => frame.code.into(),
+        other => panic!("expected a close frame, got {:?}", other),
+    }
+}
+
+// Opens a graphql-transport-ws connection on `base_url` as `token` and subscribes to new
+// messages in `chat_id`, returning once the subscription is live.
+async fn subscribe_over_ws(app: &TestApp, base_url: &str, token: &str, chat_id: &Value) -> Socket {
+    let mut request = format!("{}{}/ws", base_url.replacen("http", "ws", 1), GRAPHQL)
+        .into_client_request()
+        .unwrap();
+    request
+        .headers_mut()
+        .insert("Sec-WebSocket-Protocol", "graphql-transport-ws".parse().unwrap());
+    let (mut socket, _) = tokio_tungstenite::connect_async(request).await.unwrap();
+
+    let init = json!({ "type": "connection_init", "payload": { "Authorization": format!("Bearer {}", token) } });
+    socket.send(WsMessage::text(init.to_string())).await.unwrap();
+    assert_eq!(next_json(&mut socket).await["type"], "connection_ack");
+
+    let subscribers = app.message_hub().subscribers();
+    let subscribe = json!({
+        "id": "1",
+        "type": "subscribe",
+        "payload": { "query": format!("subscription {{ messageAdded(chatId: {}) {{ content }} }}", chat_id) },
+    });
+    socket.send(WsMessage::text(subscribe.to_string())).await.unwrap();
+    while app.message_hub().subscribers() == subscribers {
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    socket
+}
+
+async fn send_message(app: &TestApp, token: &str, chat_id: &Value, content: &str) {
+    let send = format!("mutation {{ sendMessage(chatId: {}, content: \"{}\") {{ id }} }}", chat_id, content);
+    let body = query(app, token, &send).await;
+    assert!(body.get("errors").is_none(), "{}", body);
+}
+
+async fn query(app: &TestApp, token: &str, query: &str) -> Value {
+    let (status, body) = app
+        .call_as(token, TestRequest::post().uri(GRAPHQL).set_json(json!({ "query": query })))
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    body
+}
+
+#[actix_web::test]
+async fn resolves_chats_authors_and_messages_in_one_request() {
+    let app = TestApp::spawn().await;
+    let token = app.token_for_role("admin").await;
+
+    let body = query(
+        &app,
+        &token,
+        "{ me { username chats { name author { username } messages(last: 5) { content authorName } } } }",
+    )
+    .await;
+    assert!(body.get("errors").is_none(), "{}", body);
+    assert_eq!(
+        body["data"]["me"],
+        json!({
+            "username": "e2e-admin",
+            "chats": [{
+                "name": "e2e-chat",
+                "author": { "username": "e2e-user" },
+                "messages": [{ "content": "First e2e message", "authorName": "e2e-user" }],
+            }],
+        })
+    );
+
+    app.teardown().await;
+}
+
+#[actix_web::test]
+async fn requires_a_session_and_the_admin_role_for_users() {
+    let app = TestApp::spawn().await;
+
+    let (status, _) = app
+        .call(TestRequest::post().uri(GRAPHQL).set_json(json!({ "query": "{ me { id } }" })))
+        .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let user = app.token_for_role("user").await;
+    let body = query(&app, &user, "{ users { username } }").await;
+    assert_eq!(body["errors"][0]["message"], "Unauthorized");
+
+    let admin = app.token_for_role("admin").await;
+    let body = query(&app, &admin, "{ users(username: \"e2e-\") { username email } }").await;
+    let usernames: Vec<&str> = body["data"]["users"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|user| user["username"].as_str().unwrap())
+        .collect();
+    assert_eq!(usernames, ["e2e-admin", "e2e-user", "e2e-banned"]);
+
+    app.teardown().await;
+}
+
+#[actix_web::test]
+async fn only_participants_read_and_post_messages() {
+    let app = TestApp::spawn().await;
+    let (status, body) = app
+        .call(TestRequest::post().uri("/api/v1/users/register").set_json(json!({
+            "first_name": "Out",
+            "last_name": "Sider",
+            "username": "outsider",
+            "password": "outsider-password",
+        })))
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let outsider = app.login("outsider", "outsider-password").await;
+    let member = app.token_for_role("user").await;
+
+    let chat_id = query(&app, &member, "{ me { chats { id } } }").await["data"]["me"]["chats"][0]["id"].clone();
+    let read = format!("{{ chat(id: {}) {{ name }} }}", chat_id);
+    assert_eq!(query(&app, &outsider, &read).await["data"]["chat"], Value::Null);
+    assert_eq!(query(&app, &member, &read).await["data"]["chat"]["name"], "e2e-chat");
+
+    let send = format!("mutation {{ sendMessage(chatId: {}, content: \"hello\") {{ content author {{ username }} }} }}", chat_id);
+    let body = query(&app, &outsider, &send).await;
+    assert_eq!(body["errors"][0]["message"], "You are not a participant of this chat");
+    let body = query(&app, &member, &send).await;
+    assert_eq!(body["data"]["sendMessage"], json!({ "content": "hello", "author": { "username": "e2e-user" } }));
+
+    app.teardown().await;
+}
+
+#[actix_web::test]
+async fn subscribers_receive_messages_sent_to_their_chat() {
+    let app = TestApp::spawn().await;
+    let hub = MessageHub::new();
+    let schema = graphql::build_schema(hub.clone());
+    let viewer = |username: &str, id: i32| {
+        let claims = Claims { sub: username.to_string(), role: "user".to_string(), exp: 0, jti: String::new() };
+        Viewer::new(claims, id)
+    };
+    let user_id = app.user_id("e2e-user").await;
+    let admin_id = app.user_id("e2e-admin").await;
+
+    let mut request = Request::new("{ me { chats { id } } }");
+    graphql::add_operation_data(&mut request.data, app.db.clone(), viewer("e2e-user", user_id));
+    let chat_id = schema.execute(request).await.data.into_json().unwrap()["me"]["chats"][0]["id"].clone();
+
+    let mut request = Request::new(format!("subscription {{ messageAdded(chatId: {}) {{ content authorName }} }}", chat_id));
+    graphql::add_operation_data(&mut request.data, app.db.clone(), viewer("e2e-admin", admin_id));
+    let mut messages = schema.execute_stream(request);
+
+    let mut request = Request::new(format!("mutation {{ sendMessage(chatId: {}, content: \"live\") {{ id }} }}", chat_id));
+    graphql::add_operation_data(&mut request.data, app.db.clone(), viewer("e2e-user", user_id));
+    let (received, sent) = futures::join!(messages.next(), async {
+        while hub.subscribers() == 0 {
+            tokio::task::yield_now().await;
+        }
+        schema.execute(request).await
+    });
+    assert!(sent.errors.is_empty(), "{:?}", sent.errors);
+    let received = received.unwrap().data.into_json().unwrap();
+    assert_eq!(received["messageAdded"], json!({ "content": "live", "authorName": "e2e-user" }));
+
+    app.teardown().await;
+}
+
+#[actix_web::test]
+async fn websocket_subscriptions_end_when_the_session_is_revoked() {
+    let app = TestApp::spawn().await;
+    let base_url = app.serve();
+    let admin = app.token_for_role("admin").await;
+    let user = app.token_for_role("user").await;
+    let chat_id = query(&app, &user, "{ me { chats { id } } }").await["data"]["me"]["chats"][0]["id"].clone();
+    let mut socket = subscribe_over_ws(&app, &base_url, &user, &chat_id).await;
+
+    send_message(&app, &admin, &chat_id, "before").await;
+    let next = next_json(&mut socket).await;
+    assert_eq!(next["type"], "next", "{}", next);
+    assert_eq!(next["payload"]["data"]["messageAdded"]["content"], "before");
+
+    let (status, body) = app.call_as(&user, TestRequest::get().uri("/api/v1/users/sessions")).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let current = body["sessions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|session| session["current"] == true)
+        .unwrap()["id"]
+        .clone();
+    let (status, body) = app
+        .call_as(&user, TestRequest::delete().uri(&format!("/api/v1/users/sessions/{}", current)))
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+
+    // The next event is not delivered; the connection is closed instead.
+    send_message(&app, &admin, &chat_id, "after").await;
+    assert_eq!(close_code(&mut socket).await, 4401);
+
+    app.teardown().await;
+}
+
+#[actix_web::test]
+async fn websocket_subscriptions_end_when_the_account_is_suspended() {
+    let app = TestApp::spawn().await;
+    let base_url = app.serve();
+    let admin = app.token_for_role("admin").await;
+    let user = app.token_for_role("user").await;
+    let chat_id = query(&app, &user, "{ me { chats { id } } }").await["data"]["me"]["chats"][0]["id"].clone();
+    let mut socket = subscribe_over_ws(&app, &base_url, &user, &chat_id).await;
+
+    let target = app.user_id("e2e-user").await;
+    let (status, body) = app
+        .call_as(
+            &admin,
+            TestRequest::post()
+                .uri(&format!("/api/v1/users/{}/suspend", target))
+                .set_json(json!({ "reason": "Spam" })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+
+    send_message(&app, &admin, &chat_id, "after").await;
+    assert_eq!(close_code(&mut socket).await, 4403);
+
+    app.teardown().await;
+}
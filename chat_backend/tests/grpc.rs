@@ -0,0 +1,135 @@
+mod support;
+
+use actix_web::test::TestRequest;
+use chrono::Utc;
+use futures::StreamExt;
+use sea_orm::sea_query::Expr;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+use serde_json::json;
+use tonic::{Code, Request};
+use chat_backend::entities::{prelude::UserSessions, user_sessions};
+use chat_backend::grpc::pb::chat_event::Event;
+use chat_backend::grpc::pb::chats_client::ChatsClient;
+use chat_backend::grpc::pb::users_client::UsersClient;
+use chat_backend::grpc::pb::{
+    GetMeRequest, ListChatsRequest, ListMessagesRequest, ListUsersRequest, SendMessageRequest, StreamChatEventsRequest,
+};
+use support::TestApp;
+
+fn authed<T>(token: &str, message: T) -> Request<T> {
+    let mut request = Request::new(message);
+    request
+        .metadata_mut()
+        .insert("authorization", format!("Bearer {}", token).parse().unwrap());
+    request
+}
+
+#[actix_web::test]
+async fn calls_need_a_live_session_and_the_right_role() {
+    let app = TestApp::spawn().await;
+    let mut users = UsersClient::connect(app.serve_grpc().await).await.unwrap();
+
+    let err = users.get_me(GetMeRequest {}).await.unwrap_err();
+    assert_eq!(err.code(), Code::Unauthenticated);
+    let err = users.get_me(authed("not-a-token", GetMeRequest {})).await.unwrap_err();
+    assert_eq!(err.code(), Code::Unauthenticated);
+
+    let user = app.token_for_role("user").await;
+    let err = users.list_users(authed(&user, ListUsersRequest::default())).await.unwrap_err();
+    assert_eq!(err.code(), Code::PermissionDenied);
+
+    let admin = app.token_for_role("admin").await;
+    let listed = users
+        .list_users(authed(&admin, ListUsersRequest { username_prefix: Some("e2e-".to_string()) }))
+        .await
+        .unwrap()
+        .into_inner();
+    let usernames: Vec<&str> = listed.users.iter().map(|user| user.username.as_str()).collect();
+    assert_eq!(usernames, ["e2e-admin", "e2e-user", "e2e-banned"]);
+
+    // A token outlives its session only until the next check.
+    UserSessions::update_many()
+        .col_expr(user_sessions::Column::RevokedAt, Expr::value(Utc::now()))
+        .filter(user_sessions::Column::UserId.eq(app.user_id("e2e-user").await))
+        .exec(&app.db)
+        .await
+        .unwrap();
+    let err = users.get_me(authed(&user, GetMeRequest {})).await.unwrap_err();
+    assert_eq!(err.code(), Code::Unauthenticated);
+
+    app.teardown().await;
+}
+
+#[actix_web::test]
+async fn reads_the_callers_chats_and_messages() {
+    let app = TestApp::spawn().await;
+    let endpoint = app.serve_grpc().await;
+    let token = app.token_for_role("user").await;
+    let mut users = UsersClient::connect(endpoint.clone()).await.unwrap();
+    let mut chats = ChatsClient::connect(endpoint).await.unwrap();
+
+    let me = users.get_me(authed(&token, GetMeRequest {})).await.unwrap().into_inner();
+    assert_eq!(me.username, "e2e-user");
+    assert_eq!(me.email.as_deref(), Some("e2e-user@example.test"));
+
+    let listed = chats.list_chats(authed(&token, ListChatsRequest {})).await.unwrap().into_inner();
+    let names: Vec<&str> = listed.chats.iter().map(|chat| chat.name.as_str()).collect();
+    assert_eq!(names, ["e2e-chat"]);
+
+    let chat_id = listed.chats[0].id;
+    let messages = chats
+        .list_messages(authed(&token, ListMessagesRequest { chat_id, last: None }))
+        .await
+        .unwrap()
+        .into_inner()
+        .messages;
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0].content, "First e2e message");
+    assert_eq!(messages[0].author_name.as_deref(), Some("e2e-user"));
+
+    let err = chats
+        .send_message(authed(&token, SendMessageRequest { chat_id, content: "  ".to_string() }))
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::InvalidArgument);
+
+    app.teardown().await;
+}
+
+#[actix_web::test]
+async fn streams_messages_posted_through_grpc_and_graphql() {
+    let app = TestApp::spawn().await;
+    let endpoint = app.serve_grpc().await;
+    let admin = app.token_for_role("admin").await;
+    let user = app.token_for_role("user").await;
+    let mut chats = ChatsClient::connect(endpoint).await.unwrap();
+    let chat_id = chats.list_chats(authed(&user, ListChatsRequest {})).await.unwrap().into_inner().chats[0].id;
+
+    // The stream is subscribed by the time the response headers arrive.
+    let mut events = chats
+        .stream_chat_events(authed(&admin, StreamChatEventsRequest { chat_ids: vec![chat_id] }))
+        .await
+        .unwrap()
+        .into_inner();
+
+    chats
+        .send_message(authed(&user, SendMessageRequest { chat_id, content: "over gRPC".to_string() }))
+        .await
+        .unwrap();
+    let query = format!("mutation {{ sendMessage(chatId: {}, content: \"over GraphQL\") {{ id }} }}", chat_id);
+    let (status, body) = app
+        .call_as(&user, TestRequest::post().uri("/api/v1/graphql").set_json(json!({ "query": query })))
+        .await;
+    assert!(status.is_success() && body.get("errors").is_none(), "{}", body);
+
+    for expected in ["over gRPC", "over GraphQL"] {
+        let event = events.next().await.unwrap().unwrap();
+        let Some(Event::MessageAdded(message)) = event.event else {
+            panic!("unexpected event {:?}", event);
+        };
+        assert_eq!(message.content, expected);
+        assert_eq!(message.author_name.as_deref(), Some("e2e-user"));
+    }
+
+    app.teardown().await;
+}
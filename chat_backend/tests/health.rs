@@ -0,0 +1,38 @@
+mod support;
+
+use actix_web::http::StatusCode;
+use actix_web::test::TestRequest;
+use support::TestApp;
+
+#[actix_web::test]
+async fn liveness_and_readiness_need_no_token() {
+    let app = TestApp::spawn().await;
+
+    let (status, body) = app.call(TestRequest::get().uri("/healthz")).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["status"], "ok");
+    let (status, body) = app.call(TestRequest::get().uri("/readyz")).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["status"], "ready");
+    assert_eq!(body["checks"]["migrations"]["pending"].as_array().map(Vec::len), Some(0));
+
+    app.teardown().await;
+}
+
+#[actix_web::test]
+async fn readiness_fails_when_exports_cannot_be_written() {
+    // Nothing can be created below a regular file.
+    let blocker = std::env::temp_dir().join(format!("blockchat-blocker-{}", uuid::Uuid::new_v4().simple()));
+    std::fs::write(&blocker, b"").unwrap();
+    let export_dir = blocker.join("exports").to_string_lossy().into_owned();
+    let app = TestApp::spawn_with(|config| config.export_dir = export_dir).await;
+
+    let (status, body) = app.call(TestRequest::get().uri("/readyz")).await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE, "{}", body);
+    assert_eq!(body["status"], "not_ready");
+    assert_eq!(body["checks"]["database"]["ok"], true);
+    assert_eq!(body["checks"]["blob_store"]["ok"], false);
+
+    app.teardown().await;
+    std::fs::remove_file(&blocker).ok();
+}
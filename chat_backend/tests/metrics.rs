@@ -0,0 +1,47 @@
+mod support;
+
+use std::time::Duration;
+use futures::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::Message;
+use chat_backend::metrics::WEBSOCKET_CONNECTIONS;
+use support::TestApp;
+
+// Polls the gauge, as a connection is only counted out once the server notices it closed.
+async fn wait_for_connections(expected: i64) {
+    for _ in 0..100 {
+        if WEBSOCKET_CONNECTIONS.get() == expected {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert_eq!(WEBSOCKET_CONNECTIONS.get(), expected);
+}
+
+#[actix_web::test]
+async fn graphql_websockets_are_counted_while_open() {
+    let app = TestApp::spawn().await;
+    let token = app.token_for_role("user").await;
+    let url = format!("{}/api/v1/graphql/ws", app.serve().replacen("http", "ws", 1));
+    let mut request = url.into_client_request().unwrap();
+    request
+        .headers_mut()
+        .insert("Sec-WebSocket-Protocol", "graphql-transport-ws".parse().unwrap());
+
+    assert_eq!(WEBSOCKET_CONNECTIONS.get(), 0);
+    let (mut socket, _) = tokio_tungstenite::connect_async(request).await.unwrap();
+    let init = json!({ "type": "connection_init", "payload": { "Authorization": format!("Bearer {}", token) } });
+    socket.send(Message::text(init.to_string())).await.unwrap();
+    let ack: Value = match socket.next().await.unwrap().unwrap() {
+        Message::Text(text) => serde_json::from_str(&text).unwrap(),
+        other => panic!("expected connection_ack, got {:?}", other),
+    };
+    assert_eq!(ack["type"], "connection_ack");
+    assert_eq!(WEBSOCKET_CONNECTIONS.get(), 1);
+
+    socket.close(None).await.unwrap();
+    wait_for_connections(0).await;
+
+    app.teardown().await;
+}
@@ -0,0 +1,204 @@
+mod support;
+
+use actix_web::http::StatusCode;
+use actix_web::test::TestRequest;
+use chrono::{Duration, Utc};
+use sea_orm::sea_query::Expr;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+use serde_json::{json, Value};
+use chat_backend::entities::sea_orm_active_enums::AccountStatus;
+use chat_backend::entities::{prelude::Users, users};
+use chat_backend::jobs::lift_expired_restrictions;
+use support::{TestApp, PASSWORD};
+
+async fn set_status(app: &TestApp, username: &str, status: AccountStatus) {
+    Users::update_many()
+        .col_expr(users::Column::Status, Expr::value(status))
+        .filter(users::Column::Username.eq(username))
+        .exec(&app.db)
+        .await
+        .unwrap();
+}
+
+async fn status_of(app: &TestApp, username: &str) -> AccountStatus {
+    Users::find()
+        .filter(users::Column::Username.eq(username))
+        .one(&app.db)
+        .await
+        .unwrap()
+        .unwrap()
+        .status
+}
+
+async fn login_status(app: &TestApp, username: &str) -> (StatusCode, Value) {
+    app.call(
+        TestRequest::post()
+            .uri("/api/v1/users/login")
+            .set_json(json!({ "username": username, "password": PASSWORD })),
+    )
+    .await
+}
+
+#[actix_web::test]
+async fn suspension_locks_the_account_out_until_reinstated() {
+    let app = TestApp::spawn().await;
+    let admin = app.token_for_role("admin").await;
+    let user = app.token_for_role("user").await;
+    let target = app.user_id("e2e-user").await;
+
+    let (status, body) = app
+        .call_as(
+            &admin,
+            TestRequest::post()
+                .uri(&format!("/api/v1/users/{}/suspend", target))
+                .set_json(json!({ "reason": "Spam" })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["status"], "suspended");
+
+    // Existing tokens stop working along with new logins.
+    let (status, _) = app.call_as(&user, TestRequest::get().uri("/api/v1/users/me")).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, body) = login_status(&app, "e2e-user").await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(body["reason"], "Spam");
+
+    let (status, body) = app
+        .call_as(&admin, TestRequest::post().uri(&format!("/api/v1/users/{}/reinstate", target)))
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["status"], "active");
+    let (status, _) = login_status(&app, "e2e-user").await;
+    assert_eq!(status, StatusCode::OK);
+
+    app.teardown().await;
+}
+
+#[actix_web::test]
+async fn restrictions_need_a_reason_and_not_the_admin_themselves() {
+    let app = TestApp::spawn().await;
+    let admin = app.token_for_role("admin").await;
+    let own = app.user_id("e2e-admin").await;
+    let target = app.user_id("e2e-user").await;
+
+    let (status, _) = app
+        .call_as(
+            &admin,
+            TestRequest::post()
+                .uri(&format!("/api/v1/users/{}/ban", target))
+                .set_json(json!({ "reason": " " })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _) = app
+        .call_as(
+            &admin,
+            TestRequest::post()
+                .uri(&format!("/api/v1/users/{}/ban", target))
+                .set_json(json!({ "reason": "Spam", "until": Utc::now() - Duration::hours(1) })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _) = app
+        .call_as(
+            &admin,
+            TestRequest::post()
+                .uri(&format!("/api/v1/users/{}/ban", own))
+                .set_json(json!({ "reason": "Spam" })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // Only admins moderate.
+    let user = app.token_for_role("user").await;
+    let (status, _) = app
+        .call_as(
+            &user,
+            TestRequest::post()
+                .uri(&format!("/api/v1/users/{}/ban", own))
+                .set_json(json!({ "reason": "Spam" })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    app.teardown().await;
+}
+
+#[actix_web::test]
+async fn reinstating_returns_an_unverified_account_to_unverified() {
+    let app = TestApp::spawn().await;
+    let admin = app.token_for_role("admin").await;
+    let target = app.user_id("e2e-user").await;
+    set_status(&app, "e2e-user", AccountStatus::Unverified).await;
+
+    let (status, body) = app
+        .call_as(
+            &admin,
+            TestRequest::post()
+                .uri(&format!("/api/v1/users/{}/suspend", target))
+                .set_json(json!({ "reason": "Spam" })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    // A ban on top of the suspension still remembers the account was unverified.
+    let (status, body) = app
+        .call_as(
+            &admin,
+            TestRequest::post()
+                .uri(&format!("/api/v1/users/{}/ban", target))
+                .set_json(json!({ "reason": "More spam" })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+
+    let (status, body) = app
+        .call_as(&admin, TestRequest::post().uri(&format!("/api/v1/users/{}/reinstate", target)))
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["status"], "unverified");
+
+    app.teardown().await;
+}
+
+#[actix_web::test]
+async fn expired_restrictions_lift_to_the_previous_status() {
+    let app = TestApp::spawn().await;
+    let admin = app.token_for_role("admin").await;
+    let target = app.user_id("e2e-user").await;
+    set_status(&app, "e2e-user", AccountStatus::Unverified).await;
+
+    let (status, body) = app
+        .call_as(
+            &admin,
+            TestRequest::post()
+                .uri(&format!("/api/v1/users/{}/suspend", target))
+                .set_json(json!({ "reason": "Cooling off", "until": Utc::now() + Duration::hours(1) })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let (status, _) = login_status(&app, "e2e-user").await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(lift_expired_restrictions::lift(&app.db).await.unwrap(), 0);
+
+    Users::update_many()
+        .col_expr(users::Column::StatusUntil, Expr::value(Utc::now() - Duration::minutes(1)))
+        .filter(users::Column::Id.eq(target))
+        .exec(&app.db)
+        .await
+        .unwrap();
+    // Before the job gets to it, a login already gets the limited token of an
+    // unverified account.
+    let token = app.login("e2e-user", PASSWORD).await;
+    let (status, _) = app.call_as(&token, TestRequest::get().uri("/api/v1/users/me")).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    assert_eq!(lift_expired_restrictions::lift(&app.db).await.unwrap(), 1);
+    assert_eq!(status_of(&app, "e2e-user").await, AccountStatus::Unverified);
+    // The fixture's permanent ban is left alone.
+    assert_eq!(status_of(&app, "e2e-banned").await, AccountStatus::Banned);
+
+    app.teardown().await;
+}
@@ -0,0 +1,40 @@
+mod support;
+
+use std::path::Path;
+use actix_web::http::StatusCode;
+use actix_web::test::TestRequest;
+use chat_backend::openapi::ApiDoc;
+use support::TestApp;
+use utoipa::OpenApi;
+
+// The committed spec is what the frontend generates its types from, so it must follow
+// every change to a handler or model. Run with `UPDATE_OPENAPI=1` to rewrite it.
+#[test]
+fn committed_spec_matches_the_handlers() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("openapi.json");
+    let generated = ApiDoc::openapi().to_pretty_json().expect("spec serializes") + "\n";
+
+    if std::env::var_os("UPDATE_OPENAPI").is_some() {
+        std::fs::write(&path, &generated).expect("write openapi.json");
+        return;
+    }
+
+    let committed = std::fs::read_to_string(&path).unwrap_or_default();
+    assert!(
+        committed == generated,
+        "openapi.json is out of date; regenerate it with `UPDATE_OPENAPI=1 cargo test --test openapi`"
+    );
+}
+
+#[actix_web::test]
+async fn spec_is_served_without_a_token() {
+    let app = TestApp::spawn().await;
+
+    let (status, body) = app.call(TestRequest::get().uri("/openapi.json")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["info"]["title"], "BlockChat API");
+    assert!(body["paths"]["/api/v1/users/login"]["post"].is_object());
+    assert!(body["components"]["securitySchemes"]["bearer_auth"].is_object());
+
+    app.teardown().await;
+}
@@ -0,0 +1,242 @@
+mod support;
+
+use actix_web::http::StatusCode;
+use actix_web::test::TestRequest;
+use argon2::password_hash::rand_core::OsRng;
+use ciborium::value::Value as Cbor;
+use p256::ecdsa::signature::Signer;
+use p256::ecdsa::{Signature, SigningKey};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use chat_backend::utils::webauthn::{self, COSE_ALG_ES256};
+use support::TestApp;
+
+/// An in-memory authenticator without a signature counter, as many platform
+/// authenticators are: its counter stays at 0.
+struct SoftAuthenticator {
+    key: SigningKey,
+    credential_id: Vec<u8>,
+    rp_id: String,
+    origin: String,
+}
+
+impl SoftAuthenticator {
+    fn new(app: &TestApp) -> Self {
+        Self {
+            key: SigningKey::random(&mut OsRng),
+            credential_id: webauthn::new_challenge(),
+            rp_id: app.config().webauthn_rp_id.clone(),
+            origin: app.config().frontend_url.clone(),
+        }
+    }
+
+    fn client_data(&self, ceremony: &str, options: &Value) -> Vec<u8> {
+        serde_json::to_vec(&json!({
+            "type": ceremony,
+            "challenge": options["publicKey"]["challenge"],
+            "origin": self.origin,
+        }))
+        .unwrap()
+    }
+
+    fn authenticator_data(&self, attested: Option<Vec<u8>>) -> Vec<u8> {
+        // User present and verified, plus attested credential data when registering.
+        let flags = if attested.is_some() { 0x45 } else { 0x05 };
+        let mut data = Sha256::digest(self.rp_id.as_bytes()).to_vec();
+        data.push(flags);
+        data.extend_from_slice(&0u32.to_be_bytes());
+        data.extend(attested.unwrap_or_default());
+        data
+    }
+
+    /// The body for `register/finish`, answering the options from `register/start`.
+    fn register(&self, options: &Value) -> Value {
+        let point = self.key.verifying_key().to_encoded_point(false);
+        let cose_key = Cbor::Map(vec![
+            (Cbor::from(1), Cbor::from(2)),
+            (Cbor::from(3), Cbor::from(COSE_ALG_ES256)),
+            (Cbor::from(-1), Cbor::from(1)),
+            (Cbor::from(-2), Cbor::Bytes(point.x().unwrap().to_vec())),
+            (Cbor::from(-3), Cbor::Bytes(point.y().unwrap().to_vec())),
+        ]);
+        let mut attested = vec![0u8; 16];
+        attested.extend_from_slice(&(self.credential_id.len() as u16).to_be_bytes());
+        attested.extend_from_slice(&self.credential_id);
+        ciborium::ser::into_writer(&cose_key, &mut attested).unwrap();
+        let attestation = Cbor::Map(vec![
+            (Cbor::from("fmt"), Cbor::from("none")),
+            (Cbor::from("attStmt"), Cbor::Map(Vec::new())),
+            (Cbor::from("authData"), Cbor::Bytes(self.authenticator_data(Some(attested)))),
+        ]);
+        let mut attestation_object = Vec::new();
+        ciborium::ser::into_writer(&attestation, &mut attestation_object).unwrap();
+
+        json!({
+            "state": options["state"],
+            "name": "Test key",
+            "credential": {
+                "rawId": webauthn::encode(&self.credential_id),
+                "response": {
+                    "clientDataJSON": webauthn::encode(&self.client_data("webauthn.create", options)),
+                    "attestationObject": webauthn::encode(&attestation_object),
+                },
+            },
+        })
+    }
+
+    /// The body for `login/finish`, answering the options from `login/start`.
+    fn assert(&self, options: &Value) -> Value {
+        let client_data = self.client_data("webauthn.get", options);
+        let auth_data = self.authenticator_data(None);
+        let mut signed = auth_data.clone();
+        signed.extend_from_slice(&Sha256::digest(&client_data));
+        let signature: Signature = self.key.sign(&signed);
+
+        json!({
+            "state": options["state"],
+            "credential": {
+                "rawId": webauthn::encode(&self.credential_id),
+                "response": {
+                    "clientDataJSON": webauthn::encode(&client_data),
+                    "authenticatorData": webauthn::encode(&auth_data),
+                    "signature": webauthn::encode(signature.to_der().as_bytes()),
+                    "userHandle": null,
+                },
+            },
+        })
+    }
+}
+
+async fn register_passkey(app: &TestApp, token: &str, authenticator: &SoftAuthenticator) {
+    let (status, options) = app
+        .call_as(token, TestRequest::post().uri("/api/v1/users/passkeys/register/start"))
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", options);
+    let (status, body) = app
+        .call_as(
+            token,
+            TestRequest::post()
+                .uri("/api/v1/users/passkeys/register/finish")
+                .set_json(authenticator.register(&options)),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+}
+
+async fn login_start(app: &TestApp, username: &str) -> Value {
+    let (status, options) = app
+        .call(
+            TestRequest::post()
+                .uri("/api/v1/users/passkeys/login/start")
+                .set_json(json!({ "username": username })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", options);
+    options
+}
+
+async fn login_finish(app: &TestApp, body: &Value) -> (StatusCode, Value) {
+    app.call(TestRequest::post().uri("/api/v1/users/passkeys/login/finish").set_json(body))
+        .await
+}
+
+#[actix_web::test]
+async fn registered_passkeys_sign_in() {
+    let app = TestApp::spawn().await;
+    let token = app.token_for_role("user").await;
+    let authenticator = SoftAuthenticator::new(&app);
+    register_passkey(&app, &token, &authenticator).await;
+
+    let options = login_start(&app, "e2e-user").await;
+    assert_eq!(
+        options["publicKey"]["allowCredentials"][0]["id"],
+        webauthn::encode(&authenticator.credential_id)
+    );
+    let (status, body) = login_finish(&app, &authenticator.assert(&options)).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+
+    let token = body["token"].as_str().unwrap();
+    let (status, me) = app.call_as(token, TestRequest::get().uri("/api/v1/users/me")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(me["username"], "e2e-user");
+
+    app.teardown().await;
+}
+
+#[actix_web::test]
+async fn a_challenge_is_answered_at_most_once() {
+    let app = TestApp::spawn().await;
+    let token = app.token_for_role("user").await;
+    let authenticator = SoftAuthenticator::new(&app);
+
+    let (_, options) = app
+        .call_as(&token, TestRequest::post().uri("/api/v1/users/passkeys/register/start"))
+        .await;
+    let registration = authenticator.register(&options);
+    let (status, _) = app
+        .call_as(
+            &token,
+            TestRequest::post().uri("/api/v1/users/passkeys/register/finish").set_json(&registration),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = app
+        .call_as(
+            &token,
+            TestRequest::post().uri("/api/v1/users/passkeys/register/finish").set_json(&registration),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // The counter stays at 0, so only the spent challenge stops a captured assertion.
+    let assertion = authenticator.assert(&login_start(&app, "e2e-user").await);
+    let (status, _) = login_finish(&app, &assertion).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, body) = login_finish(&app, &assertion).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["message"], "Invalid or expired passkey challenge");
+
+    app.teardown().await;
+}
+
+#[actix_web::test]
+async fn challenges_only_finish_their_own_ceremony() {
+    let app = TestApp::spawn().await;
+    let user = app.token_for_role("user").await;
+    let admin = app.token_for_role("admin").await;
+    let authenticator = SoftAuthenticator::new(&app);
+    register_passkey(&app, &user, &authenticator).await;
+
+    // A login challenge cannot register a passkey ...
+    let options = login_start(&app, "e2e-user").await;
+    let (status, _) = app
+        .call_as(
+            &user,
+            TestRequest::post()
+                .uri("/api/v1/users/passkeys/register/finish")
+                .set_json(SoftAuthenticator::new(&app).register(&options)),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // ... nor can another account finish the user's registration.
+    let (_, options) = app
+        .call_as(&user, TestRequest::post().uri("/api/v1/users/passkeys/register/start"))
+        .await;
+    let (status, _) = app
+        .call_as(
+            &admin,
+            TestRequest::post()
+                .uri("/api/v1/users/passkeys/register/finish")
+                .set_json(SoftAuthenticator::new(&app).register(&options)),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // A login started for one account does not accept another account's passkey.
+    let options = login_start(&app, "e2e-admin").await;
+    let (status, _) = login_finish(&app, &authenticator.assert(&options)).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    app.teardown().await;
+}
@@ -0,0 +1,70 @@
+mod support;
+
+use actix_web::http::StatusCode;
+use actix_web::test::TestRequest;
+use serde_json::json;
+use support::TestApp;
+
+const EMAIL: &str = "e2e-user@example.test";
+
+async fn forgot(app: &TestApp, email: &str) {
+    let (status, body) = app
+        .call(TestRequest::post().uri("/api/v1/users/password/forgot").set_json(json!({ "email": email })))
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+}
+
+async fn reset(app: &TestApp, token: &str, password: &str) -> StatusCode {
+    app.call(
+        TestRequest::post()
+            .uri("/api/v1/users/password/reset")
+            .set_json(json!({ "token": token, "password": password })),
+    )
+    .await
+    .0
+}
+
+#[actix_web::test]
+async fn reset_links_change_the_password_once_and_end_sessions() {
+    let app = TestApp::spawn().await;
+    let old_session = app.token_for_role("user").await;
+
+    forgot(&app, EMAIL).await;
+    let token = app.mailed_token(EMAIL);
+    assert_eq!(reset(&app, &token, "").await, StatusCode::BAD_REQUEST);
+    assert_eq!(reset(&app, &token, "new-password").await, StatusCode::OK);
+
+    app.login("e2e-user", "new-password").await;
+    let (status, _) = app.call_as(&old_session, TestRequest::get().uri("/api/v1/users/me")).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(reset(&app, &token, "another-password").await, StatusCode::BAD_REQUEST);
+
+    app.teardown().await;
+}
+
+#[actix_web::test]
+async fn only_the_newest_link_works() {
+    let app = TestApp::spawn().await;
+
+    forgot(&app, EMAIL).await;
+    let first = app.mailed_token(EMAIL);
+    forgot(&app, EMAIL).await;
+    let second = app.mailed_token(EMAIL);
+
+    assert_eq!(reset(&app, &first, "new-password").await, StatusCode::BAD_REQUEST);
+    assert_eq!(reset(&app, &second, "new-password").await, StatusCode::OK);
+    assert_eq!(reset(&app, "not-a-token", "new-password").await, StatusCode::BAD_REQUEST);
+
+    app.teardown().await;
+}
+
+#[actix_web::test]
+async fn unknown_addresses_get_the_same_answer_and_no_mail() {
+    let app = TestApp::spawn().await;
+
+    forgot(&app, "nobody@example.test").await;
+    forgot(&app, "not an address").await;
+    assert!(app.mailer.sent.lock().unwrap().is_empty());
+
+    app.teardown().await;
+}
@@ -0,0 +1,104 @@
+mod support;
+
+use actix_web::http::StatusCode;
+use actix_web::test::TestRequest;
+use serde_json::{json, Value};
+use chat_backend::config::RegistrationMode;
+use support::TestApp;
+
+async fn register(app: &TestApp, username: &str, extra: Value) -> (StatusCode, Value) {
+    let mut body = json!({
+        "first_name": "New",
+        "last_name": "User",
+        "username": username,
+        "password": "new-password",
+    });
+    body.as_object_mut().unwrap().extend(extra.as_object().unwrap().clone());
+    app.call(TestRequest::post().uri("/api/v1/users/register").set_json(body)).await
+}
+
+#[actix_web::test]
+async fn closed_registration_turns_everyone_away() {
+    let app = TestApp::spawn_with(|config| config.registration_mode = RegistrationMode::Closed).await;
+
+    let (status, _) = register(&app, "newcomer", json!({})).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    app.teardown().await;
+}
+
+#[actix_web::test]
+async fn unverified_accounts_are_limited_until_the_mailed_link_is_used() {
+    let app = TestApp::spawn_with(|config| config.registration_mode = RegistrationMode::EmailVerified).await;
+
+    let (status, _) = register(&app, "newcomer", json!({})).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "an email address is required");
+    let (status, body) = register(&app, "newcomer", json!({ "email": "Newcomer@Example.test" })).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+
+    // The limited token only reaches the resend endpoint.
+    let limited = app.login("newcomer", "new-password").await;
+    let (status, _) = app.call_as(&limited, TestRequest::get().uri("/api/v1/users/me")).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, body) = app
+        .call_as(&limited, TestRequest::post().uri("/api/v1/users/verify-email/resend"))
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+
+    let token = app.mailed_token("newcomer@example.test");
+    let (status, body) = app
+        .call(TestRequest::post().uri("/api/v1/users/verify-email").set_json(json!({ "token": token })))
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+
+    let verified = app.login("newcomer", "new-password").await;
+    let (status, me) = app.call_as(&verified, TestRequest::get().uri("/api/v1/users/me")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(me["status"], "active");
+    assert_eq!(me["email_verified"], true);
+
+    let (status, _) = app
+        .call(TestRequest::post().uri("/api/v1/users/verify-email").set_json(json!({ "token": "forged" })))
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    app.teardown().await;
+}
+
+#[actix_web::test]
+async fn invite_codes_are_spent_and_revocable() {
+    let app = TestApp::spawn_with(|config| config.registration_mode = RegistrationMode::InviteCode).await;
+    let admin = app.token_for_role("admin").await;
+
+    let (status, invite) = app
+        .call_as(
+            &admin,
+            TestRequest::post().uri("/api/v1/invites").set_json(json!({ "code": "WELCOME", "max_uses": 1 })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", invite);
+
+    let (status, _) = register(&app, "first", json!({})).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, body) = register(&app, "first", json!({ "invite_code": "WELCOME" })).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let (status, _) = register(&app, "second", json!({ "invite_code": "WELCOME" })).await;
+    assert_eq!(status, StatusCode::FORBIDDEN, "the only use is spent");
+
+    let (_, invite) = app
+        .call_as(&admin, TestRequest::post().uri("/api/v1/invites").set_json(json!({ "code": "TEAM" })))
+        .await;
+    let (status, _) = app
+        .call_as(&admin, TestRequest::delete().uri(&format!("/api/v1/invites/{}", invite["id"])))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = register(&app, "second", json!({ "invite_code": "TEAM" })).await;
+    assert_eq!(status, StatusCode::FORBIDDEN, "revoked invites do not work");
+
+    // Only admins manage invites.
+    let user = app.token_for_role("user").await;
+    let (status, _) = app.call_as(&user, TestRequest::get().uri("/api/v1/invites")).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    app.teardown().await;
+}
@@ -0,0 +1,49 @@
+mod support;
+
+use actix_web::http::StatusCode;
+use actix_web::test::TestRequest;
+use sea_orm::sea_query::Expr;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+use serde_json::json;
+use chat_backend::entities::{prelude::Users, users};
+use chat_backend::seed::{self, Fixture};
+use chat_backend::utils::password::hash_password;
+use support::{TestApp, PASSWORD};
+
+#[actix_web::test]
+async fn reseeding_keeps_passwords_and_moderation_of_existing_users() {
+    let app = TestApp::spawn().await;
+    let admin = app.token_for_role("admin").await;
+
+    Users::update_many()
+        .col_expr(users::Column::Password, Expr::value(hash_password("changed-password").unwrap()))
+        .filter(users::Column::Username.eq("e2e-user"))
+        .exec(&app.db)
+        .await
+        .unwrap();
+    let banned = app.user_id("e2e-banned").await;
+    let (status, body) = app
+        .call_as(&admin, TestRequest::post().uri(&format!("/api/v1/users/{}/reinstate", banned)))
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let (status, _) = app
+        .call_as(
+            &admin,
+            TestRequest::put()
+                .uri(&format!("/api/v1/users/{}", app.user_id("e2e-user").await))
+                .set_json(json!({ "first_name": "Renamed" })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    // What the server does on every start.
+    seed::apply(&app.db, &Fixture::profile("e2e-test").unwrap()).await.unwrap();
+
+    app.login("e2e-user", "changed-password").await;
+    app.login("e2e-banned", PASSWORD).await;
+    // Profile fields still follow the fixture.
+    let user = Users::find_by_id(app.user_id("e2e-user").await).one(&app.db).await.unwrap().unwrap();
+    assert_eq!(user.first_name, "E2E");
+
+    app.teardown().await;
+}
@@ -0,0 +1,107 @@
+mod support;
+
+use actix_web::http::header::WWW_AUTHENTICATE;
+use actix_web::http::StatusCode;
+use actix_web::test::TestRequest;
+use serde_json::{json, Value};
+use chat_backend::utils::check_auth_user::INVALID_TOKEN;
+use support::{TestApp, PASSWORD};
+
+async fn my_sessions(app: &TestApp, token: &str) -> Vec<Value> {
+    let (status, body) = app.call_as(token, TestRequest::get().uri("/api/v1/users/sessions")).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    body["sessions"].as_array().unwrap().clone()
+}
+
+#[actix_web::test]
+async fn users_list_and_revoke_their_own_sessions() {
+    let app = TestApp::spawn().await;
+    let laptop = app.token_for_role("user").await;
+    let phone = app
+        .call(
+            TestRequest::post()
+                .uri("/api/v1/users/login")
+                .insert_header(("User-Agent", "phone"))
+                .set_json(json!({ "username": "e2e-user", "password": PASSWORD })),
+        )
+        .await
+        .1["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let sessions = my_sessions(&app, &laptop).await;
+    assert_eq!(sessions.len(), 2);
+    assert_eq!(sessions.iter().filter(|session| session["current"] == true).count(), 1);
+    let phone_session = sessions.iter().find(|session| session["user_agent"] == "phone").unwrap();
+
+    let uri = format!("/api/v1/users/sessions/{}", phone_session["id"]);
+    let (status, _) = app.call_as(&laptop, TestRequest::delete().uri(&uri)).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = app.call_as(&laptop, TestRequest::delete().uri(&uri)).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // The revoked token stops working at once, marked so clients sign in again; the
+    // other keeps going.
+    let (status, headers, _) = app
+        .call_with_headers(
+            TestRequest::get()
+                .uri("/api/v1/users/me")
+                .insert_header(("Authorization", format!("Bearer {}", phone))),
+        )
+        .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(headers.get(WWW_AUTHENTICATE).unwrap(), INVALID_TOKEN);
+    assert_eq!(my_sessions(&app, &laptop).await.len(), 1);
+
+    app.teardown().await;
+}
+
+#[actix_web::test]
+async fn users_cannot_revoke_sessions_of_others() {
+    let app = TestApp::spawn().await;
+    let user = app.token_for_role("user").await;
+    let admin = app.token_for_role("admin").await;
+    let admin_session = my_sessions(&app, &admin).await[0]["id"].clone();
+
+    let (status, _) = app
+        .call_as(&user, TestRequest::delete().uri(&format!("/api/v1/users/sessions/{}", admin_session)))
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = app.call_as(&admin, TestRequest::get().uri("/api/v1/users/me")).await;
+    assert_eq!(status, StatusCode::OK);
+
+    app.teardown().await;
+}
+
+#[actix_web::test]
+async fn admins_revoke_sessions_of_any_user_with_an_audit_record() {
+    let app = TestApp::spawn().await;
+    let user = app.token_for_role("user").await;
+    let admin = app.token_for_role("admin").await;
+    let user_id = app.user_id("e2e-user").await;
+
+    let (status, body) = app
+        .call_as(&admin, TestRequest::get().uri(&format!("/api/v1/users/{}/sessions", user_id)))
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let session_id = body["sessions"][0]["id"].clone();
+
+    let (status, _) = app
+        .call_as(
+            &admin,
+            TestRequest::delete().uri(&format!("/api/v1/users/{}/sessions/{}", user_id, session_id)),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = app.call_as(&user, TestRequest::get().uri("/api/v1/users/me")).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, body) = app
+        .call_as(&admin, TestRequest::get().uri("/api/v1/audit-events?action=session.revoke"))
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["events"][0]["target_id"], user_id);
+
+    app.teardown().await;
+}
@@ -0,0 +1,154 @@
+mod support;
+
+use actix_web::http::StatusCode;
+use actix_web::test::TestRequest;
+use chrono::{Duration, Utc};
+use sea_orm::sea_query::Expr;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+use serde_json::json;
+use chat_backend::entities::prelude::{Chats, Messages, Users};
+use chat_backend::entities::{chats, messages, users};
+use chat_backend::jobs::purge_deleted;
+use support::{TestApp, PASSWORD};
+
+async fn delete_user(app: &TestApp, admin: &str, user_id: i32) {
+    let (status, body) = app
+        .call_as(admin, TestRequest::delete().uri(&format!("/api/v1/users/{}", user_id)))
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+}
+
+async fn backdate_deletion(app: &TestApp, user_id: i32, days: i64) {
+    Users::update_many()
+        .col_expr(users::Column::DeletedAt, Expr::value(Utc::now() - Duration::days(days)))
+        .filter(users::Column::Id.eq(user_id))
+        .exec(&app.db)
+        .await
+        .unwrap();
+}
+
+#[actix_web::test]
+async fn deleted_users_are_hidden_until_restored() {
+    let app = TestApp::spawn().await;
+    let admin = app.token_for_role("admin").await;
+    let user = app.token_for_role("user").await;
+    let target = app.user_id("e2e-user").await;
+
+    delete_user(&app, &admin, target).await;
+
+    // The row stays, but the account and its sessions are gone from the outside.
+    assert!(Users::find_by_id(target).one(&app.db).await.unwrap().is_some());
+    let (status, _) = app
+        .call_as(&admin, TestRequest::get().uri(&format!("/api/v1/users/{}", target)))
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = app.call_as(&user, TestRequest::get().uri("/api/v1/users/me")).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _) = app
+        .call(
+            TestRequest::post()
+                .uri("/api/v1/users/login")
+                .set_json(json!({ "username": "e2e-user", "password": PASSWORD })),
+        )
+        .await;
+    assert_ne!(status, StatusCode::OK);
+
+    // Deleting twice finds nothing left to delete.
+    let (status, _) = app
+        .call_as(&admin, TestRequest::delete().uri(&format!("/api/v1/users/{}", target)))
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let restore = format!("/api/v1/users/{}/restore", target);
+    let (status, body) = app.call_as(&admin, TestRequest::post().uri(&restore)).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    app.login("e2e-user", PASSWORD).await;
+
+    // Only deleted users can be restored.
+    let (status, _) = app.call_as(&admin, TestRequest::post().uri(&restore)).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    app.teardown().await;
+}
+
+#[actix_web::test]
+async fn restoring_users_is_reserved_to_admins() {
+    let app = TestApp::spawn().await;
+    let admin = app.token_for_role("admin").await;
+    let user = app.token_for_role("user").await;
+    let target = app.user_id("e2e-banned").await;
+
+    delete_user(&app, &admin, target).await;
+    let (status, _) = app
+        .call_as(&user, TestRequest::post().uri(&format!("/api/v1/users/{}/restore", target)))
+        .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    app.teardown().await;
+}
+
+#[actix_web::test]
+async fn purge_keeps_shared_chats_and_authorship_of_purged_users() {
+    let app = TestApp::spawn().await;
+    let admin = app.token_for_role("admin").await;
+    let grace_days = app.config().purge_grace_days;
+    let target = app.user_id("e2e-user").await;
+
+    delete_user(&app, &admin, target).await;
+
+    // Still inside the grace period: nothing is purged.
+    assert_eq!(purge_deleted::purge(&app.db, grace_days).await.unwrap(), (0, 0, 0));
+    assert!(Users::find_by_id(target).one(&app.db).await.unwrap().is_some());
+
+    backdate_deletion(&app, target, grace_days + 1).await;
+    assert_eq!(purge_deleted::purge(&app.db, grace_days).await.unwrap(), (1, 0, 0));
+    assert!(Users::find_by_id(target).one(&app.db).await.unwrap().is_none());
+
+    let chat = Chats::find()
+        .filter(chats::Column::Name.eq("e2e-chat"))
+        .one(&app.db)
+        .await
+        .unwrap()
+        .expect("the chat outlives its author");
+    assert_eq!(chat.author_id, None);
+    let message = Messages::find()
+        .filter(messages::Column::ChatId.eq(chat.id))
+        .one(&app.db)
+        .await
+        .unwrap()
+        .expect("the message outlives its author");
+    assert_eq!(message.user_id, None);
+    assert_eq!(message.author_name.as_deref(), Some("e2e-user"));
+
+    app.teardown().await;
+}
+
+#[actix_web::test]
+async fn purge_removes_chats_and_messages_past_the_grace_period() {
+    let app = TestApp::spawn().await;
+    let grace_days = app.config().purge_grace_days;
+    let expired = Utc::now() - Duration::days(grace_days + 1);
+
+    Messages::update_many()
+        .col_expr(messages::Column::DeletedAt, Expr::value(expired))
+        .exec(&app.db)
+        .await
+        .unwrap();
+    assert_eq!(purge_deleted::purge(&app.db, grace_days).await.unwrap(), (0, 0, 1));
+    assert_eq!(Messages::find().all(&app.db).await.unwrap().len(), 0);
+
+    Chats::update_many()
+        .col_expr(chats::Column::DeletedAt, Expr::value(Utc::now()))
+        .exec(&app.db)
+        .await
+        .unwrap();
+    assert_eq!(purge_deleted::purge(&app.db, grace_days).await.unwrap(), (0, 0, 0));
+    Chats::update_many()
+        .col_expr(chats::Column::DeletedAt, Expr::value(expired))
+        .exec(&app.db)
+        .await
+        .unwrap();
+    assert_eq!(purge_deleted::purge(&app.db, grace_days).await.unwrap(), (0, 1, 0));
+
+    app.teardown().await;
+}
@@ -0,0 +1,231 @@
+//! Shared setup for the integration tests: the real `App` from `chat_backend::app`
+//! against a throwaway database that is migrated and seeded with the `e2e-test` fixture.
+//!
+//! Each test gets its own in-memory SQLite database. Set `TEST_DATABASE_URL` to a
+//! Postgres URL to run them against Postgres instead, each in a schema of its own.
+#![allow(dead_code)]
+
+use std::sync::{Arc, Mutex};
+use actix_web::http::header::HeaderMap;
+use actix_web::http::StatusCode;
+use actix_web::{test, web, HttpServer};
+use async_trait::async_trait;
+use sea_orm::{ConnectOptions, ConnectionTrait, Database, DatabaseConnection};
+use serde_json::{json, Value};
+use chat_backend::app::{self, AppState};
+use chat_backend::config::{Config, MigrationMode, RegistrationMode};
+use chat_backend::mailer::{Email, MailError, Mailer};
+use chat_backend::seed::{self, Fixture};
+use chat_backend::shutdown::Shutdown;
+use chat_backend::utils::messages::MessageHub;
+use chat_backend::{grpc, migrate};
+
+/// Password of every account in the `e2e-test` fixture.
+pub const PASSWORD: &str = "e2e-password";
+
+/// Keeps outgoing mail in memory so tests can read links out of it.
+#[derive(Default)]
+pub struct RecordingMailer {
+    pub sent: Mutex<Vec<Email>>,
+}
+
+#[async_trait]
+impl Mailer for RecordingMailer {
+    async fn send(&self, email: Email) -> Result<(), MailError> {
+        self.sent.lock().unwrap().push(email);
+        Ok(())
+    }
+}
+
+pub struct TestApp {
+    pub db: DatabaseConnection,
+    pub mailer: Arc<RecordingMailer>,
+    state: AppState,
+    // The Postgres schema and the connection that drops it afterwards.
+    schema: Option<(String, DatabaseConnection)>,
+}
+
+impl TestApp {
+    /// A migrated and seeded database of its own, with open registration.
+    pub async fn spawn() -> Self {
+        Self::spawn_with(|_| {}).await
+    }
+
+    /// Like [`TestApp::spawn`], with the configuration adjusted by `configure`.
+    pub async fn spawn_with(configure: impl FnOnce(&mut Config)) -> Self {
+        if std::env::var("JWT_SECRET").is_err() {
+            std::env::set_var("JWT_SECRET", "integration-test-secret");
+        }
+
+        let (db, schema) = match std::env::var("TEST_DATABASE_URL") {
+            Ok(url) => {
+                let schema = format!("test_{}", uuid::Uuid::new_v4().simple());
+                let admin_db = Database::connect(&url).await.expect("connect to TEST_DATABASE_URL");
+                admin_db
+                    .execute_unprepared(&format!("CREATE SCHEMA \"{}\"", schema))
+                    .await
+                    .expect("create test schema");
+                let mut options = ConnectOptions::new(url);
+                options.set_schema_search_path(schema.clone()).max_connections(5).sqlx_logging(false);
+                let db = Database::connect(options).await.expect("connect to test schema");
+                (db, Some((schema, admin_db)))
+            }
+            Err(_) => {
+                // A single connection, as every connection to `:memory:` is a new database.
+                let mut options = ConnectOptions::new("sqlite::memory:");
+                options.max_connections(1).sqlx_logging(false);
+                let db = Database::connect(options).await.expect("open in-memory SQLite");
+                (db, None)
+            }
+        };
+        migrate::run(&db, MigrationMode::Auto).await.expect("run migrations");
+        seed::apply(&db, &Fixture::profile("e2e-test").unwrap())
+            .await
+            .expect("seed e2e-test fixture");
+
+        let mut config = Config::from_env();
+        config.registration_mode = RegistrationMode::Open;
+        config.export_dir = std::env::temp_dir()
+            .join(format!("blockchat-test-{}", uuid::Uuid::new_v4().simple()))
+            .to_string_lossy()
+            .into_owned();
+        configure(&mut config);
+
+        let mailer = Arc::new(RecordingMailer::default());
+        let state = AppState {
+            db: db.clone(),
+            config,
+            mailer: web::Data::from(mailer.clone() as Arc<dyn Mailer>),
+            shutdown: web::Data::new(Shutdown::new()),
+            messages: MessageHub::new(),
+        };
+        Self { db, mailer, state, schema }
+    }
+
+    /// The configuration the app runs with.
+    pub fn config(&self) -> &Config {
+        &self.state.config
+    }
+
+    /// The hub new messages are published on, shared by every API.
+    pub fn message_hub(&self) -> &MessageHub {
+        &self.state.messages
+    }
+
+    /// Sends `req` through the full middleware stack and returns the status and the JSON
+    /// body (`Value::Null` when the body is empty or not JSON).
+    pub async fn call(&self, req: test::TestRequest) -> (StatusCode, Value) {
+        let (status, _, body) = self.call_with_headers(req).await;
+        (status, body)
+    }
+
+    /// Like [`TestApp::call`], also returning the response headers.
+    pub async fn call_with_headers(&self, req: test::TestRequest) -> (StatusCode, HeaderMap, Value) {
+        let (status, headers, body) = self.call_raw(req).await;
+        (status, headers, serde_json::from_slice(&body).unwrap_or(Value::Null))
+    }
+
+    /// Like [`TestApp::call_with_headers`], with the body as it was sent.
+    pub async fn call_raw(&self, req: test::TestRequest) -> (StatusCode, HeaderMap, Vec<u8>) {
+        let service = test::init_service(app::build(&self.state)).await;
+        let res = test::call_service(&service, req.to_request()).await;
+        let status = res.status();
+        let headers = res.headers().clone();
+        let body = test::read_body(res).await;
+        (status, headers, body.to_vec())
+    }
+
+    /// Like [`TestApp::call`], authenticated with `token`.
+    pub async fn call_as(&self, token: &str, req: test::TestRequest) -> (StatusCode, Value) {
+        self.call(req.insert_header(("Authorization", format!("Bearer {}", token))))
+            .await
+    }
+
+    /// Serves the app over real HTTP on a free local port, for tests of HTTP clients.
+    /// Returns the base URL; the server stops with the test's runtime.
+    pub fn serve(&self) -> String {
+        let state = self.state.clone();
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("bind a local port");
+        let address = listener.local_addr().unwrap();
+        let server = HttpServer::new(move || app::build(&state))
+            .listen(listener)
+            .expect("listen on the local port")
+            .workers(1)
+            .disable_signals()
+            .run();
+        actix_web::rt::spawn(server);
+        format!("http://{}", address)
+    }
+
+    /// Serves the gRPC API on a free local port, sharing the database and message hub
+    /// with the HTTP app. Returns the endpoint URL; the server stops with the test's runtime.
+    pub async fn serve_grpc(&self) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.expect("bind a local port");
+        let address = listener.local_addr().unwrap();
+        let server = grpc::serve(
+            listener,
+            self.db.clone(),
+            self.state.messages.clone(),
+            self.state.shutdown.token(),
+        );
+        tokio::spawn(server);
+        format!("http://{}", address)
+    }
+
+    /// The `token` query parameter of the link in the last email sent to `to`.
+    pub fn mailed_token(&self, to: &str) -> String {
+        let sent = self.mailer.sent.lock().unwrap();
+        let email = sent.iter().rev().find(|email| email.to == to).unwrap_or_else(|| panic!("no email to {}", to));
+        let start = email.body.find("token=").expect("link with a token") + "token=".len();
+        email.body[start..].split_whitespace().next().unwrap().to_string()
+    }
+
+    /// Logs in through `/api/v1/users/login` and returns the bearer token.
+    pub async fn login(&self, username: &str, password: &str) -> String {
+        let (status, body) = self
+            .call(
+                test::TestRequest::post()
+                    .uri("/api/v1/users/login")
+                    .set_json(json!({ "username": username, "password": password })),
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK, "login as {} failed: {}", username, body);
+        body["token"].as_str().expect("token in login response").to_string()
+    }
+
+    /// A token for the fixture account that has `role` ("admin" or "user").
+    pub async fn token_for_role(&self, role: &str) -> String {
+        let username = match role {
+            "admin" => "e2e-admin",
+            "user" => "e2e-user",
+            other => panic!("no e2e-test account has role {}", other),
+        };
+        self.login(username, PASSWORD).await
+    }
+
+    /// Id of a fixture account.
+    pub async fn user_id(&self, username: &str) -> i32 {
+        use chat_backend::entities::{prelude::Users, users};
+        use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+        Users::find()
+            .filter(users::Column::Username.eq(username))
+            .one(&self.db)
+            .await
+            .unwrap()
+            .unwrap_or_else(|| panic!("no user {}", username))
+            .id
+    }
+
+    /// Drops the test database. Postgres schemas of tests that panic are left behind;
+    /// they are all named `test_<uuid>`.
+    pub async fn teardown(self) {
+        self.db.close().await.ok();
+        if let Some((schema, admin_db)) = self.schema {
+            admin_db
+                .execute_unprepared(&format!("DROP SCHEMA \"{}\" CASCADE", schema))
+                .await
+                .expect("drop test schema");
+        }
+        std::fs::remove_dir_all(&self.state.config.export_dir).ok();
+    }
+}
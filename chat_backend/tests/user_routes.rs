@@ -0,0 +1,105 @@
+mod support;
+
+use actix_web::http::StatusCode;
+use actix_web::test::TestRequest;
+use serde_json::json;
+use support::{TestApp, PASSWORD};
+
+#[actix_web::test]
+async fn role_guard_middleware_requires_an_allowed_role() {
+    let app = TestApp::spawn().await;
+    let user_id = app.user_id("e2e-user").await;
+    let uri = format!("/api/v1/users/{}/sessions", user_id);
+
+    let (status, _) = app.call(TestRequest::get().uri(&uri)).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let user = app.token_for_role("user").await;
+    let (status, _) = app.call_as(&user, TestRequest::get().uri(&uri)).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let admin = app.token_for_role("admin").await;
+    let (status, body) = app.call_as(&admin, TestRequest::get().uri(&uri)).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+
+    app.teardown().await;
+}
+
+#[actix_web::test]
+async fn listing_all_users_is_reserved_to_admins() {
+    let app = TestApp::spawn().await;
+
+    let (status, _) = app.call(TestRequest::get().uri("/api/v1/users/all")).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let user = app.token_for_role("user").await;
+    let (status, _) = app.call_as(&user, TestRequest::get().uri("/api/v1/users/all")).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let admin = app.token_for_role("admin").await;
+    let (status, body) = app.call_as(&admin, TestRequest::get().uri("/api/v1/users/all")).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+
+    app.teardown().await;
+}
+
+#[actix_web::test]
+async fn route_guards_leave_no_matching_route_for_other_roles() {
+    let app = TestApp::spawn().await;
+    let target = app.user_id("e2e-banned").await;
+    let uri = format!("/api/v1/users/{}", target);
+
+    // A failed route guard falls through to the resource's default, 405.
+    let user = app.token_for_role("user").await;
+    let (status, _) = app.call_as(&user, TestRequest::delete().uri(&uri)).await;
+    assert_eq!(status, StatusCode::METHOD_NOT_ALLOWED);
+
+    let admin = app.token_for_role("admin").await;
+    let (status, body) = app.call_as(&admin, TestRequest::delete().uri(&uri)).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+
+    app.teardown().await;
+}
+
+#[actix_web::test]
+async fn users_can_only_update_their_own_profile() {
+    let app = TestApp::spawn().await;
+    let own = app.user_id("e2e-user").await;
+    let other = app.user_id("e2e-admin").await;
+    let user = app.token_for_role("user").await;
+    let change = json!({ "first_name": "Changed" });
+
+    let (status, body) = app
+        .call_as(&user, TestRequest::put().uri(&format!("/api/v1/users/{}", own)).set_json(&change))
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+
+    let (status, _) = app
+        .call_as(&user, TestRequest::put().uri(&format!("/api/v1/users/{}", other)).set_json(&change))
+        .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let admin = app.token_for_role("admin").await;
+    let (status, body) = app
+        .call_as(&admin, TestRequest::put().uri(&format!("/api/v1/users/{}", own)).set_json(&change))
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+
+    app.teardown().await;
+}
+
+#[actix_web::test]
+async fn banned_accounts_cannot_log_in() {
+    let app = TestApp::spawn().await;
+
+    let (status, body) = app
+        .call(
+            TestRequest::post()
+                .uri("/api/v1/users/login")
+                .set_json(json!({ "username": "e2e-banned", "password": PASSWORD })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(body["status"], "banned");
+
+    app.teardown().await;
+}
@@ -0,0 +1,69 @@
+mod support;
+
+use actix_web::http::header::LINK;
+use actix_web::http::StatusCode;
+use actix_web::test::TestRequest;
+use chrono::{TimeZone, Utc};
+use chat_backend::middleware::deprecation::{DEPRECATION, SUNSET};
+use support::TestApp;
+
+#[actix_web::test]
+async fn versioned_routes_are_not_deprecated() {
+    let app = TestApp::spawn().await;
+    let token = app.token_for_role("user").await;
+
+    let req = TestRequest::get()
+        .uri("/api/v1/users/me")
+        .insert_header(("Authorization", format!("Bearer {}", token)));
+    let (status, headers, body) = app.call_with_headers(req).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["username"], "e2e-user");
+    assert!(headers.get(DEPRECATION).is_none());
+    assert!(headers.get(SUNSET).is_none());
+
+    let (status, headers, _) = app.call_with_headers(TestRequest::get().uri("/healthz")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(headers.get(DEPRECATION).is_none());
+
+    app.teardown().await;
+}
+
+#[actix_web::test]
+async fn unversioned_aliases_announce_their_sunset() {
+    let app = TestApp::spawn().await;
+    let token = app.token_for_role("user").await;
+
+    let req = TestRequest::get()
+        .uri("/users/me")
+        .insert_header(("Authorization", format!("Bearer {}", token)));
+    let (status, headers, body) = app.call_with_headers(req).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["username"], "e2e-user");
+    assert!(headers.get(DEPRECATION).unwrap().to_str().unwrap().starts_with('@'));
+    assert!(headers.get(SUNSET).unwrap().to_str().unwrap().ends_with(" GMT"));
+    assert_eq!(headers.get(LINK).unwrap(), "</api/v1/users/me>; rel=\"successor-version\"");
+
+    // Error responses from the aliases carry the headers as well.
+    let (status, headers, _) = app.call_with_headers(TestRequest::get().uri("/users/me")).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert!(headers.get(DEPRECATION).is_some());
+
+    app.teardown().await;
+}
+
+#[actix_web::test]
+async fn deprecation_and_sunset_dates_come_from_the_config() {
+    let deprecated = Utc.with_ymd_and_hms(2027, 1, 15, 0, 0, 0).unwrap();
+    let sunset = Utc.with_ymd_and_hms(2027, 7, 1, 12, 0, 0).unwrap();
+    let app = TestApp::spawn_with(|config| {
+        config.legacy_routes_deprecated = deprecated;
+        config.legacy_routes_sunset = sunset;
+    })
+    .await;
+
+    let (_, headers, _) = app.call_with_headers(TestRequest::get().uri("/users/me")).await;
+    assert_eq!(headers.get(DEPRECATION).unwrap(), format!("@{}", deprecated.timestamp()).as_str());
+    assert_eq!(headers.get(SUNSET).unwrap(), "Thu, 01 Jul 2027 12:00:00 GMT");
+
+    app.teardown().await;
+}
@@ -15,7 +15,7 @@ export default defineNuxtConfig({
   css: ['~/assets/css/main.css'],
   runtimeConfig: {
     public: {
-      apiBase: process.env.NUXT_PUBLIC_API_BASE_URL || "localhost:8080"
+      apiBase: process.env.NUXT_PUBLIC_API_BASE_URL || "localhost:8080/api/v1"
     }
   },
 });